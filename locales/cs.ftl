@@ -0,0 +1,107 @@
+## Notifications
+
+notification-summary = Varování o limitu nabití
+notification-body = Nabití baterie dosáhlo limitu { $threshold } %, odpojte prosím nabíječku
+alert-updated = Aktualizováno: { $summary }
+alert-resolved = Vyřešeno: { $summary }
+alert-resolved-body = Baterie se už nenabíjí nad limit
+test-summary = Testovací oznámení
+test-body = rusty-battery vás může upozornit tímto kanálem
+
+## Desktop notification actions
+
+action-snooze = Odložit o { $minutes } min
+action-dismiss = Skrýt do dalšího cyklu
+action-unplugged = Nabíječka odpojena
+
+## Email body
+
+email-percentage = Nabití: { $percentage } %
+email-state = Stav: { $state }
+email-model = Model: { $model }
+email-threshold = Limit: { $threshold } %
+
+## Battery
+
+battery-state =
+    { $state ->
+        [Charging] Nabíjí se
+        [Discharging] Vybíjí se
+       *[other] Neznámý stav
+    }
+
+## Device listing
+
+devices-battery =
+    { $count ->
+        [0] Nebyly nalezeny žádné baterie
+        [one] Nalezena { $count } baterie
+        [few] Nalezeny { $count } baterie
+       *[other] Nalezeno { $count } baterií
+    }
+devices-kde-connect =
+    { $count ->
+        [0] Nebyla nalezena žádná zařízení KDE Connect
+        [one] Nalezeno { $count } zařízení KDE Connect
+        [few] Nalezena { $count } zařízení KDE Connect
+       *[other] Nalezeno { $count } zařízení KDE Connect
+    }
+devices-kde-connect-battery =
+    { $count ->
+        [0] Nebyly nalezeny žádné baterie KDE Connect
+        [one] Nalezena { $count } baterie KDE Connect
+        [few] Nalezeny { $count } baterie KDE Connect
+       *[other] Nalezeno { $count } baterií KDE Connect
+    }
+device-battery = { $model } ({ $serial_number }): { $percentage } %, { $state }
+device-kde-connect =
+    { $name } ({ $id }): { $kind ->
+        [unknown] neznámý typ
+       *[other] { $kind }
+    }, { $reachable ->
+        [true] dostupné
+       *[other] nedostupné
+    }, { $paired ->
+        [true] spárované
+       *[other] nespárované
+    }
+
+## Control responses
+
+control-snoozed = Upozornění odložena do { $until }
+control-paused = Upozornění pozastavena
+control-resumed = Upozornění obnovena
+control-threshold = Limit nastaven na { $threshold } %
+control-threshold-boost = Limit nastaven na { $threshold } % po skončení zvýšení na { $boost }
+control-reloaded = Znovu načteno: nabití = { $percentage } %, stav = { $state }, limit = { $threshold } %
+control-test =
+    { $channel }: { $delivered ->
+        [true] doručeno
+       *[other] selhalo
+    }
+
+## Boost
+
+boost-cancelled = Zvýšení limitu zrušeno
+boost-inactive = Žádné zvýšení limitu není aktivní
+boost-raised = Limit zvýšen na { $boost }
+boost-until-time = { $threshold } % do { $time }
+boost-until-unplug = { $threshold } % do odpojení nabíječky
+
+## Status
+
+status-running = rusty-battery běží: pid = { $pid }
+status-battery = Baterie: model = { $model }, nabití = { $percentage } %, stav = { $state }, limit = { $threshold } %
+status-boost = Zvýšení: limit = { $boost }
+status-paused = Upozornění jsou pozastavena
+status-channels = Kanály
+status-channel = { $index }. { $channel }: okruh = { $circuit }, pokusy = { $attempts }, selhání = { $failures }, selhání po sobě = { $consecutive }, upozornění = { $alert }
+status-alert = { $alert } odesláno před { $secs } s
+status-alert-none = žádné
+
+## Installation
+
+install-installed = Nainstalováno { $path }
+install-enabled = Povoleno a spuštěno { $unit }
+uninstall-nothing = Není co odinstalovat
+uninstall-removed = Odstraněno { $path }
@@ -0,0 +1,104 @@
+## Notifications
+
+notification-summary = Charge limit warning
+notification-body = Battery percentage reached the { $threshold }% threshold, please unplug your charger
+alert-updated = Updated: { $summary }
+alert-resolved = Resolved: { $summary }
+alert-resolved-body = Battery is no longer charging above the threshold
+test-summary = Test notification
+test-body = rusty-battery can alert you via this channel
+
+## Desktop notification actions
+
+action-snooze = Snooze { $minutes } min
+action-dismiss = Dismiss until next cycle
+action-unplugged = Charger unplugged
+
+## Email body
+
+email-percentage = Percentage: { $percentage }%
+email-state = State: { $state }
+email-model = Model: { $model }
+email-threshold = Threshold: { $threshold }%
+
+## Battery
+
+battery-state =
+    { $state ->
+        [Charging] Charging
+        [Discharging] Discharging
+       *[other] Unknown
+    }
+
+## Device listing
+
+devices-battery =
+    { $count ->
+        [0] No batteries found
+        [one] Found { $count } battery
+       *[other] Found { $count } batteries
+    }
+devices-kde-connect =
+    { $count ->
+        [0] No KDE Connect devices found
+        [one] Found { $count } KDE Connect device
+       *[other] Found { $count } KDE Connect devices
+    }
+devices-kde-connect-battery =
+    { $count ->
+        [0] No KDE Connect batteries found
+        [one] Found { $count } KDE Connect battery
+       *[other] Found { $count } KDE Connect batteries
+    }
+device-battery = { $model } ({ $serial_number }): { $percentage }%, { $state }
+device-kde-connect =
+    { $name } ({ $id }): { $kind ->
+        [unknown] unknown type
+       *[other] { $kind }
+    }, { $reachable ->
+        [true] reachable
+       *[other] unreachable
+    }, { $paired ->
+        [true] paired
+       *[other] not paired
+    }
+
+## Control responses
+
+control-snoozed = Alerts snoozed until { $until }
+control-paused = Alerts paused
+control-resumed = Alerts resumed
+control-threshold = Threshold set to { $threshold }%
+control-threshold-boost = Threshold set to { $threshold }% once the boost of { $boost } ends
+control-reloaded = Reloaded: percentage = { $percentage }%, state = { $state }, threshold = { $threshold }%
+control-test =
+    { $channel }: { $delivered ->
+        [true] delivered
+       *[other] failed
+    }
+
+## Boost
+
+boost-cancelled = Boost cancelled
+boost-inactive = No boost is active
+boost-raised = Threshold raised to { $boost }
+boost-until-time = { $threshold }% until { $time }
+boost-until-unplug = { $threshold }% until the charger is unplugged
+
+## Status
+
+status-running = rusty-battery is running: pid = { $pid }
+status-battery = Battery: model = { $model }, percentage = { $percentage }%, state = { $state }, threshold = { $threshold }%
+status-boost = Boost: threshold = { $boost }
+status-paused = Alerts are paused
+status-channels = Channels
+status-channel = { $index }. { $channel }: circuit = { $circuit }, attempts = { $attempts }, failures = { $failures }, consecutive failures = { $consecutive }, alert = { $alert }
+status-alert = { $alert } sent { $secs } seconds ago
+status-alert-none = None
+
+## Installation
+
+install-installed = Installed { $path }
+install-enabled = Enabled and started { $unit }
+uninstall-nothing = Nothing to uninstall
+uninstall-removed = Removed { $path }
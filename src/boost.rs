@@ -0,0 +1,390 @@
+//! Temporary override of the threshold to charge the battery fully.
+//!
+//! The `boost` subcommand persists the override in the state directory and
+//! the `notify` loop picks it up after every battery refresh, so the override
+//! survives restarts of the process.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::{env, result};
+
+use chrono::{Local, NaiveDateTime};
+use serde_json::{json, Value};
+
+use crate::device::BatteryState;
+use crate::{cli, common, error, locale};
+
+type Result<T> = result::Result<T, error::Boost>;
+
+/// Name of the directory of the persisted state.
+const DIR_NAME: &str = "rusty-battery";
+/// Name of the boost state file.
+const FILE_NAME: &str = "boost.json";
+
+/// Format of the time the boost ends at.
+pub const TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// When the boost ends.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Until {
+    /// Boost ends at the local time.
+    Time(NaiveDateTime),
+    /// Boost ends once the charger is unplugged.
+    Unplug,
+}
+
+/// Threshold overriding the normal one until the boost ends.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Boost {
+    pub threshold: u8,
+    pub until: Until,
+}
+
+impl Boost {
+    /// Return JSON representation of the `Boost`.
+    pub fn to_json(self) -> Value {
+        json!({
+            "threshold": self.threshold,
+            "until": match self.until {
+                Until::Time(time) => Some(time.format(TIME_FORMAT).to_string()),
+                Until::Unplug => None,
+            },
+        })
+    }
+
+    /// Parse JSON representation of the `Boost`.
+    pub fn from_json(value: &Value) -> Result<Self> {
+        let invalid = |reason: &str| error::Boost::Invalid {
+            reason: reason.into(),
+        };
+
+        let threshold = value["threshold"]
+            .as_u64()
+            .and_then(|threshold| u8::try_from(threshold).ok())
+            .filter(|threshold| *threshold <= 100)
+            .ok_or_else(|| invalid("threshold"))?;
+        let until = match &value["until"] {
+            Value::Null => Until::Unplug,
+            Value::String(time) => Until::Time(
+                NaiveDateTime::parse_from_str(time, TIME_FORMAT)
+                    .map_err(|_| invalid("until"))?,
+            ),
+            _ => return Err(invalid("until")),
+        };
+
+        Ok(Self { threshold, until })
+    }
+}
+
+/// Tracker of the boost persisted by the `boost` subcommand.
+#[derive(Debug)]
+pub struct Tracker {
+    path: PathBuf,
+    /// Threshold used when no boost is active.
+    normal: u8,
+    boost: Option<Boost>,
+    /// Battery state of the previous check.
+    previous: Option<BatteryState>,
+}
+
+impl Tracker {
+    /// Create a new `Tracker` restoring the `normal` threshold
+    /// once the boost ends.
+    pub fn new(normal: u8) -> Self {
+        Self::with_path(normal, path())
+    }
+
+    const fn with_path(normal: u8, path: PathBuf) -> Self {
+        Self {
+            path,
+            normal,
+            boost: None,
+            previous: None,
+        }
+    }
+
+    /// Return the threshold which should be used right now.
+    ///
+    /// The persisted boost is reloaded and cleared once it ends,
+    /// i.e. once the time passes or the battery starts discharging.
+    pub fn threshold(&mut self, state: BatteryState, now: NaiveDateTime) -> u8 {
+        let boost = match load(&self.path) {
+            Ok(boost) => boost,
+            Err(e) => {
+                log::warn!("boost: {e}");
+
+                self.boost
+            }
+        };
+
+        if boost != self.boost {
+            if let Some(boost) = boost {
+                log::info!("boost: threshold raised to {}", describe(boost));
+            } else {
+                log::info!("boost: cancelled");
+            }
+        }
+
+        self.boost = boost.filter(|boost| {
+            let ended = match boost.until {
+                Until::Time(time) => now >= time,
+                Until::Unplug => {
+                    state == BatteryState::Discharging
+                        && self.previous.is_some_and(|previous| {
+                            previous != BatteryState::Discharging
+                        })
+                }
+            };
+
+            if ended {
+                log::info!(
+                    "boost: ended, threshold restored to {}%",
+                    self.normal
+                );
+
+                if let Err(e) = clear(&self.path) {
+                    log::warn!("boost: {e}");
+                }
+            }
+
+            !ended
+        });
+        self.previous = Some(state);
+
+        self.boost.map_or(self.normal, |boost| boost.threshold)
+    }
+
+    /// Change the threshold used when no boost is active.
+    pub const fn set_normal(&mut self, threshold: u8) {
+        self.normal = threshold;
+    }
+
+    /// Return the currently active `Boost`.
+    pub const fn active(&self) -> Option<Boost> {
+        self.boost
+    }
+}
+
+/// Return the directory of the persisted state.
+///
+/// `$XDG_STATE_HOME` is used if set, otherwise `~/.local/state`.
+pub fn state_dir() -> PathBuf {
+    env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(".local").join("state"))
+        })
+        .unwrap_or_else(env::temp_dir)
+        .join(DIR_NAME)
+}
+
+/// Return path of the boost state file.
+pub fn path() -> PathBuf {
+    state_dir().join(FILE_NAME)
+}
+
+/// Persist or cancel the boost requested on the command line.
+pub fn run(opts: &cli::Boost) -> Result<()> {
+    let path = path();
+
+    if opts.cancel {
+        if clear(&path)? {
+            println!("{}", locale::text("boost-cancelled"));
+        } else {
+            println!("{}", locale::text("boost-inactive"));
+        }
+
+        return Ok(());
+    }
+
+    let boost = Boost {
+        threshold: opts.threshold,
+        until: opts.until.map_or(Until::Unplug, Until::Time),
+    };
+
+    if let Until::Time(time) = boost.until {
+        if time <= Local::now().naive_local() {
+            return Err(error::Boost::Past {
+                time: time.format(TIME_FORMAT).to_string(),
+            });
+        }
+    }
+
+    save(&path, boost)?;
+
+    println!(
+        "{}",
+        locale::format("boost-raised", &[("boost", describe(boost).into())])
+    );
+
+    Ok(())
+}
+
+/// Return localized description of the `Boost`.
+pub fn describe(boost: Boost) -> String {
+    match boost.until {
+        Until::Time(time) => locale::format(
+            "boost-until-time",
+            &[
+                ("threshold", boost.threshold.into()),
+                ("time", time.format(TIME_FORMAT).to_string().into()),
+            ],
+        ),
+        Until::Unplug => locale::format(
+            "boost-until-unplug",
+            &[("threshold", boost.threshold.into())],
+        ),
+    }
+}
+
+/// Atomically write the `Boost` into the file at the path.
+fn save(path: &Path, boost: Boost) -> Result<()> {
+    common::write_atomic(path, &serde_json::to_vec_pretty(&boost.to_json())?)?;
+
+    log::debug!("boost: saved");
+
+    Ok(())
+}
+
+/// Read the `Boost` from the file at the path if it exists.
+fn load(path: &Path) -> Result<Option<Boost>> {
+    let data = match fs::read(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        data => data?,
+    };
+
+    Ok(Some(Boost::from_json(&serde_json::from_slice(&data)?)?))
+}
+
+/// Remove the file at the path.
+///
+/// Return whether the file existed.
+fn clear(path: &Path) -> Result<bool> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        result => result.map(|()| true).map_err(error::Boost::from),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process;
+
+    use super::*;
+
+    fn time(time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time, TIME_FORMAT).unwrap()
+    }
+
+    fn temporary_path(name: &str) -> PathBuf {
+        env::temp_dir()
+            .join(format!("rusty-battery-test-boost-{}", process::id()))
+            .join(name)
+    }
+
+    #[test]
+    fn test_save_load() {
+        let path = temporary_path("save-load.json");
+        let boost = Boost {
+            threshold: 100,
+            until: Until::Time(time("2026-10-20 06:30")),
+        };
+
+        save(&path, boost).unwrap();
+        let result = load(&path).unwrap();
+
+        assert_eq!(result, Some(boost));
+    }
+
+    #[test]
+    fn test_load_missing() {
+        let result = load(&temporary_path("missing.json")).unwrap();
+
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_boost_from_json_invalid() {
+        let result = Boost::from_json(&json!({"threshold": 101}));
+
+        assert!(matches!(result, Err(error::Boost::Invalid { .. })));
+    }
+
+    #[test]
+    fn test_tracker_threshold_until_time() {
+        let path = temporary_path("until-time.json");
+        let mut tracker = Tracker::with_path(80, path.clone());
+        save(
+            &path,
+            Boost {
+                threshold: 100,
+                until: Until::Time(time("2026-10-20 06:30")),
+            },
+        )
+        .unwrap();
+
+        let boosted =
+            tracker.threshold(BatteryState::Charging, time("2026-10-20 06:29"));
+        let restored =
+            tracker.threshold(BatteryState::Charging, time("2026-10-20 06:30"));
+
+        assert_eq!([boosted, restored], [100, 80]);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_tracker_threshold_until_unplug() {
+        let path = temporary_path("until-unplug.json");
+        let now = time("2026-10-18 12:00");
+        let mut tracker = Tracker::with_path(80, path.clone());
+        save(
+            &path,
+            Boost {
+                threshold: 95,
+                until: Until::Unplug,
+            },
+        )
+        .unwrap();
+
+        let result = [
+            BatteryState::Discharging,
+            BatteryState::Charging,
+            BatteryState::Charging,
+            BatteryState::Discharging,
+        ]
+        .map(|state| tracker.threshold(state, now));
+
+        assert_eq!(result, [95, 95, 95, 80]);
+        assert_eq!(tracker.active(), None);
+    }
+
+    #[test]
+    fn test_tracker_threshold_restarted() {
+        let path = temporary_path("restarted.json");
+        let now = time("2026-10-18 12:00");
+        let boost = Boost {
+            threshold: 100,
+            until: Until::Unplug,
+        };
+        save(&path, boost).unwrap();
+        Tracker::with_path(80, path.clone())
+            .threshold(BatteryState::Charging, now);
+
+        let mut tracker = Tracker::with_path(80, path);
+        let result = tracker.threshold(BatteryState::Discharging, now);
+
+        assert_eq!(result, 100);
+        assert_eq!(tracker.active(), Some(boost));
+    }
+
+    #[test]
+    fn test_describe() {
+        let result = describe(Boost {
+            threshold: 100,
+            until: Until::Time(time("2026-10-20 06:30")),
+        });
+
+        assert_eq!(result, "100% until 2026-10-20 06:30");
+    }
+} // tests
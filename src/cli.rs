@@ -1,6 +1,14 @@
-use clap::{Parser, Subcommand};
+use std::time::Duration;
 
-use crate::parser;
+use chrono::NaiveDateTime;
+use clap::{ArgGroup, Args, Parser, Subcommand};
+
+use crate::device::kde_connect;
+use crate::notification::{
+    Channel, EmailSecurity, Fallback, KDEConnectMissing, PlugKind, QuietMode,
+    QuietWindow, Urgency,
+};
+use crate::{logger, parser};
 
 /// Tool to help you care about your device's battery health.
 #[derive(Parser, Debug)]
@@ -11,6 +19,21 @@ pub struct Cli {
     #[clap(flatten)]
     pub verbose: clap_verbosity_flag::Verbosity,
 
+    /// Where the logs are written to
+    ///
+    /// The journald target attaches fields like `BATTERY_PERCENTAGE`
+    /// to the entries of the notify process, the standard error output
+    /// is used if the journal isn't available.
+    #[arg(long, global = true, value_enum, default_value_t = logger::Target::Stderr)]
+    pub log_target: logger::Target,
+
+    /// Language of the notifications and the output, e.g. `cs` or `cs_CZ`
+    ///
+    /// Defaults to the `LC_ALL`, `LC_MESSAGES` or `LANG` environment
+    /// variable, English is used if the language isn't supported.
+    #[arg(long, global = true, value_parser = parser::locale)]
+    pub locale: Option<String>,
+
     #[command(subcommand)]
     pub cmd: Command,
 }
@@ -18,84 +41,736 @@ pub struct Cli {
 #[derive(Subcommand, Debug, PartialEq, Eq)]
 pub enum Command {
     /// Notify whenever battery percentage exceeds the given threshold.
-    Notify {
-        /// Battery charge threshold
-        ///
-        /// Whenever the chosen battery device reaches this charge threshold
-        /// and will be charging, notifications will be sent, alerting that
-        /// the charger should be unplugged.
+    Notify(Box<Notify>),
+    /// List all available batteries of the current device.
+    Batteries(Batteries),
+    /// List all available KDE Connect devices.
+    KDEConnectDevices,
+    /// Show status of the running notify process.
+    Status,
+    /// Temporarily raise the threshold to charge the battery fully.
+    Boost(Boost),
+    /// Control the running notify process.
+    Ctl(Ctl),
+    /// Start notify with the user session via systemd or XDG autostart.
+    Install(Install),
+    /// Remove what the install subcommand created.
+    Uninstall(Uninstall),
+}
+
+#[derive(Args, Debug, PartialEq, Eq)]
+pub struct Batteries {
+    /// Also list batteries of reachable paired KDE Connect devices
+    #[arg(long)]
+    pub include_kde_connect: bool,
+}
+
+#[derive(Args, Debug, PartialEq, Eq)]
+pub struct Boost {
+    /// Local time until which the threshold is raised
+    ///
+    /// Format: `YYYY-MM-DD HH:MM` or `HH:MM` for the next occurrence
+    /// of the time. If this value is not present, the threshold is raised
+    /// until the charger is unplugged.
+    #[arg(long, value_name = "TIME", value_parser = parser::boost_until)]
+    pub until: Option<NaiveDateTime>,
+
+    /// Threshold used until the boost ends
+    ///
+    /// [minimum: 0] [maximum: 100]
+    #[arg(long, value_parser = parser::threshold, default_value_t = 100)]
+    pub threshold: u8,
+
+    /// Cancel the boost and restore the normal threshold right away
+    #[arg(long, conflicts_with_all = ["until", "threshold"])]
+    pub cancel: bool,
+}
+
+#[derive(Args, Debug, PartialEq, Eq)]
+#[command(group(
+    ArgGroup::new("kind").required(true).args(["systemd", "autostart"])
+))]
+pub struct Install {
+    /// Install a systemd user service
+    #[arg(long)]
+    pub systemd: bool,
+
+    /// Install an XDG autostart entry of the desktop session
+    #[arg(long)]
+    pub autostart: bool,
+
+    /// Enable the systemd service and (re)start it right away
+    #[arg(long, conflicts_with = "autostart")]
+    pub enable: bool,
+
+    /// Options of the installed notify process
+    ///
+    /// Example: `rusty-battery install --systemd -- --threshold 90 -v`
+    #[arg(last = true, value_name = "NOTIFY_OPTIONS")]
+    pub args: Vec<String>,
+}
+
+#[derive(Args, Debug, PartialEq, Eq)]
+pub struct Uninstall {
+    /// Remove only the systemd user service
+    #[arg(long)]
+    pub systemd: bool,
+
+    /// Remove only the XDG autostart entry
+    #[arg(long)]
+    pub autostart: bool,
+}
+
+#[derive(Args, Debug, PartialEq, Eq)]
+pub struct Ctl {
+    #[command(subcommand)]
+    pub command: CtlCommand,
+}
+
+#[derive(Subcommand, Debug, PartialEq, Eq)]
+pub enum CtlCommand {
+    /// Show status of the battery and the notification channels.
+    Status,
+    /// Suppress the alert for the given duration.
+    Snooze {
+        /// Duration of the snooze, e.g. `30m`, `1h30m` or `90s`
+        #[arg(value_parser = parser::duration)]
+        duration: Duration,
+    },
+    /// Suppress all alerts until resumed.
+    Pause,
+    /// Resume paused or snoozed alerts.
+    Resume,
+    /// Change the threshold until the notify process exits.
+    SetThreshold {
+        /// New battery charge threshold
         ///
         /// [minimum: 0] [maximum: 100]
-        #[arg(
-        short,
-        long,
-        value_parser = parser::threshold,
-        default_value_t = 80
-        )]
+        #[arg(value_parser = parser::threshold)]
         threshold: u8,
+    },
+    /// Refresh the battery and reload the boost right away.
+    Reload,
+    /// Send a test notification via every enabled channel.
+    TestNotify,
+}
 
-        /// Battery model name
-        ///
-        /// If this value is omitted and only battery device is found
-        /// for the current device, that one will be used.
-        ///
-        /// Otherwise, please use the `batteries` subcommand
-        /// to get a list of all battery devices to get the model of the
-        /// wanted battery device which should be monitored.
-        #[arg(short, long)]
-        model: Option<String>,
+#[derive(Args, Debug, PartialEq, Eq)]
+pub struct Notify {
+    /// Battery charge threshold
+    ///
+    /// Whenever the chosen battery device reaches this charge threshold
+    /// and will be charging, notifications will be sent, alerting that
+    /// the charger should be unplugged.
+    ///
+    /// [minimum: 0] [maximum: 100]
+    #[arg(
+    short,
+    long,
+    value_parser = parser::threshold,
+    default_value_t = 80
+    )]
+    pub threshold: u8,
 
-        /// Number of seconds to wait before refreshing battery device data
-        ///
-        /// After every battery device refresh, its data will be checked.
-        /// Notifications will be sent everytime they should be, based on the
-        /// new refreshed battery device data.
-        #[arg(long, default_value_t = 30)]
-        refresh_secs: u64,
+    /// Battery model name
+    ///
+    /// If this value is omitted and only battery device is found
+    /// for the current device, that one will be used.
+    ///
+    /// Otherwise, please use the `batteries` subcommand
+    /// to get a list of all battery devices to get the model of the
+    /// wanted battery device which should be monitored.
+    #[arg(short, long)]
+    pub model: Option<String>,
 
-        /// Notification summary
-        ///
-        /// Supported variables: THRESHOLD, CHARGE_STATE, MODEL, REFRESH_SECS
-        ///
-        /// Reference these variables in your summary like shell environment
-        /// variables with the '$' prefix.
-        #[arg(long, default_value_t = String::from("Charge limit warning"))]
-        summary: String,
+    /// Also search batteries of paired KDE Connect devices
+    ///
+    /// If no battery of the current device matches the `--model` value,
+    /// the KDE Connect device with that name is monitored instead,
+    /// so phones can be alerted about overcharging as well.
+    #[arg(long, requires = "model")]
+    pub include_kde_connect: bool,
 
-        /// Notification body
-        ///
-        /// Supported variables: THRESHOLD, CHARGE_STATE, MODEL, REFRESH_SECS
-        ///
-        /// Reference these variables in your body like shell environment
-        /// variables with the '$' prefix.
-        #[arg(
-            long,
-            default_value_t = String::from("Battery percentage reached the \
-            $THRESHOLD% threshold, please unplug your charger")
-        )]
-        body: String,
-
-        /// KDE Connect device names
-        ///
-        /// If this value is not present, KDE Connect will not be used.
-        ///
-        /// If this value is empty,
-        /// all of the KDE Connect devices will be pinged.
-        #[arg(long = "kde-connect", num_args = 0..=255)]
-        kde_connect_names: Option<Vec<String>>,
+    /// Number of seconds to wait before refreshing battery device data
+    ///
+    /// After every battery device refresh, its data will be checked.
+    /// Notifications will be sent everytime they should be, based on the
+    /// new refreshed battery device data.
+    #[arg(long, default_value_t = 30)]
+    pub refresh_secs: u64,
 
-        /// Disable desktop notifications
-        ///
-        /// Specify this flag if you don't want desktop notifications
-        /// to be shown whenever the chosen battery percentage exceeds the
-        /// given threshold.
-        #[arg(long)]
-        disable_desktop: bool,
-    },
-    /// List all available batteries of the current device.
-    Batteries,
-    /// List all available KDE Connect devices.
-    KDEConnectDevices,
+    /// Notification summary template
+    ///
+    /// Supported variables: `percentage`, `state`, `model`,
+    /// `serial_number`, `threshold`, `refresh_secs` and `urgency`.
+    ///
+    /// Templates use the Jinja syntax, e.g.
+    /// `{% if state == "Charging" %}{{ percentage }}%{% endif %}`.
+    /// Besides the built-in filters `pad(width, fill)` and `duration`
+    /// are available. Unknown variables are rejected at startup.
+    ///
+    /// Defaults to a summary in the language of `--locale`.
+    #[arg(long)]
+    pub summary: Option<String>,
+
+    /// Notification body template
+    ///
+    /// Supports the same variables and syntax as `--summary`.
+    ///
+    /// Defaults to a body in the language of `--locale`.
+    #[arg(long)]
+    pub body: Option<String>,
+
+    /// Notification urgency
+    ///
+    /// Notifiers which support some kind of priority levels map
+    /// this value to their closest equivalent.
+    #[arg(long, value_enum, default_value_t = Urgency::Critical)]
+    pub urgency: Urgency,
+
+    /// Ask the running notify process to exit and take over
+    ///
+    /// Only one notify process of the current user can run at a time.
+    /// Without this flag a second one refuses to start.
+    #[arg(long)]
+    pub replace: bool,
+
+    /// Delay suspend until a pending critical alert is delivered
+    ///
+    /// Holds a logind delay inhibitor lock while the system is awake,
+    /// so the alert isn't lost when the laptop is suspended while charging.
+    #[arg(long)]
+    pub inhibit_sleep: bool,
+
+    #[command(flatten)]
+    pub notifiers: Notifiers,
+}
+
+/// Options of all the supported notifiers.
+#[derive(Args, Debug, PartialEq, Eq)]
+pub struct Notifiers {
+    /// KDE Connect device selectors
+    ///
+    /// If this value is not present, KDE Connect will not be used.
+    ///
+    /// If this value is empty,
+    /// all of the KDE Connect devices will be pinged.
+    ///
+    /// Devices are selected by `NAME` (or `name:NAME`), `id:ID`,
+    /// `type:TYPE` (e.g. `smartphone` or its alias `phone`, `tablet`
+    /// or `desktop`) or `paired` for all paired devices. Types are only
+    /// known over D-Bus.
+    #[arg(
+        long = "kde-connect",
+        value_name = "SELECTOR",
+        num_args = 0..=255
+    )]
+    pub kde_connect_selectors: Option<Vec<kde_connect::Selector>>,
+
+    /// What happens when a device selected by its name or ID is unknown
+    ///
+    /// Every device is pinged once per alert, a device which becomes
+    /// reachable later is pinged as soon as it's back online.
+    #[arg(
+        long = "kde-connect-missing",
+        value_name = "POLICY",
+        value_enum,
+        default_value_t = KDEConnectMissing::Wait,
+        requires = "kde_connect_selectors"
+    )]
+    pub kde_connect_missing: KDEConnectMissing,
+
+    /// KDE Connect action performed on a device instead of the ping
+    ///
+    /// Format: `NAME=ACTION` where `ACTION` is one of `ping`, `ring`,
+    /// `share` or `sms:<NUMBER>`. Can be specified multiple times, also
+    /// for the same device. Devices ring only for critical alerts.
+    ///
+    /// Actions which a reachable device doesn't support fail at startup
+    /// over D-Bus, otherwise they fall back to the ping once performed.
+    #[arg(
+        long = "kde-connect-action",
+        value_name = "NAME=ACTION",
+        value_parser = parser::kde_connect_action,
+        requires = "kde_connect_selectors"
+    )]
+    pub kde_connect_actions: Vec<(String, kde_connect::Action)>,
+
+    /// Ping the notified KDE Connect devices once the alert is removed
+    #[arg(long = "kde-connect-resolved", requires = "kde_connect_selectors")]
+    pub kde_connect_resolved: bool,
+
+    /// Disable desktop notifications
+    ///
+    /// Specify this flag if you don't want desktop notifications
+    /// to be shown whenever the chosen battery percentage exceeds the
+    /// given threshold.
+    #[arg(long)]
+    pub disable_desktop: bool,
+
+    #[command(flatten)]
+    pub desktop: Desktop,
+
+    #[command(flatten)]
+    pub ntfy: Ntfy,
+
+    #[command(flatten)]
+    pub gotify: Gotify,
+
+    #[command(flatten)]
+    pub mqtt: Mqtt,
+
+    #[command(flatten)]
+    pub plug: Plug,
+
+    #[command(flatten)]
+    pub hook: Hook,
+
+    #[command(flatten)]
+    pub email: Email,
+
+    #[command(flatten)]
+    pub delivery: Delivery,
+
+    #[command(flatten)]
+    pub quiet: Quiet,
+}
+
+#[derive(Args, Debug, PartialEq, Eq, Clone)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Desktop {
+    /// Don't show action buttons on desktop notifications
+    ///
+    /// By default, notifications can be snoozed, dismissed until the battery
+    /// discharges below the threshold again, or the battery can be refreshed
+    /// right away once the charger was unplugged.
+    #[arg(
+        id = "desktop_disable_actions",
+        long = "desktop-disable-actions",
+        conflicts_with = "disable_desktop"
+    )]
+    pub disable_actions: bool,
+
+    /// Number of minutes the alert is suppressed for when snoozed
+    ///
+    /// At most a week, `10080`.
+    #[arg(
+        id = "snooze_mins",
+        value_name = "MINS",
+        long = "snooze-mins",
+        default_value_t = 15,
+        value_parser = parser::snooze_mins
+    )]
+    pub snooze_mins: u64,
+
+    /// Urgency of desktop notifications
+    ///
+    /// If this value is not present, `--urgency` is used.
+    #[arg(
+        id = "desktop_urgency",
+        value_name = "URGENCY",
+        long = "desktop-urgency",
+        value_enum
+    )]
+    pub urgency: Option<Urgency>,
+
+    /// Icon of desktop notifications
+    ///
+    /// Either an icon name of the current icon theme or an absolute path
+    /// to an image. The value `auto` selects an icon based on the battery
+    /// percentage and state, such as `battery-080-charging`.
+    #[arg(
+        id = "desktop_icon",
+        value_name = "ICON",
+        long = "desktop-icon",
+        default_value_t = String::from("battery")
+    )]
+    pub icon: String,
+
+    /// Number of seconds after which desktop notifications expire
+    ///
+    /// If this value is not present, notifications never expire.
+    #[arg(
+        id = "desktop_timeout_secs",
+        value_name = "SECS",
+        long = "desktop-timeout-secs"
+    )]
+    pub timeout_secs: Option<u32>,
+
+    /// Category hint of desktop notifications
+    ///
+    /// Example: `device`
+    #[arg(
+        id = "desktop_category",
+        value_name = "CATEGORY",
+        long = "desktop-category"
+    )]
+    pub category: Option<String>,
+
+    /// Sound played when a desktop notification is shown
+    ///
+    /// Either a themeable sound name or a path to a sound file.
+    #[arg(id = "desktop_sound", value_name = "SOUND", long = "desktop-sound")]
+    pub sound: Option<String>,
+
+    /// Hint that desktop notifications shouldn't be kept in the history
+    #[arg(
+        id = "desktop_transient",
+        long = "desktop-transient",
+        conflicts_with = "desktop_resident"
+    )]
+    pub transient: bool,
+
+    /// Hint that desktop notifications shouldn't be removed when an action
+    /// is invoked
+    #[arg(id = "desktop_resident", long = "desktop-resident")]
+    pub resident: bool,
+
+    /// Show battery percentage as a progress bar on desktop notifications
+    #[arg(id = "desktop_progress", long = "desktop-progress")]
+    pub progress: bool,
+}
+
+#[derive(Args, Debug, PartialEq, Eq, Clone)]
+pub struct Ntfy {
+    /// ntfy server URL
+    ///
+    /// If this value is not present, ntfy will not be used.
+    ///
+    /// Example: `https://ntfy.sh`
+    #[arg(
+        id = "ntfy_url",
+        value_name = "URL",
+        long = "ntfy-url",
+        requires = "ntfy_topic"
+    )]
+    pub url: Option<String>,
+
+    /// ntfy topic which notifications will be published to
+    #[arg(
+        id = "ntfy_topic",
+        value_name = "TOPIC",
+        long = "ntfy-topic",
+        requires = "ntfy_url"
+    )]
+    pub topic: Option<String>,
+
+    /// ntfy access token
+    #[arg(
+        id = "ntfy_token",
+        value_name = "TOKEN",
+        long = "ntfy-token",
+        requires = "ntfy_url"
+    )]
+    pub token: Option<String>,
+
+    /// ntfy tags attached to every notification
+    ///
+    /// Tags which match an emoji short code are shown as emojis.
+    #[arg(
+        id = "ntfy_tags",
+        value_name = "TAGS",
+        long = "ntfy-tags",
+        value_delimiter = ',',
+        requires = "ntfy_url"
+    )]
+    pub tags: Vec<String>,
+
+    /// Publish a "resolved" message to ntfy once the alert is removed
+    #[arg(id = "ntfy_resolved", long = "ntfy-resolved", requires = "ntfy_url")]
+    pub resolved: bool,
+}
+
+#[derive(Args, Debug, PartialEq, Eq, Clone)]
+pub struct Gotify {
+    /// Gotify server URL
+    ///
+    /// If this value is not present, Gotify will not be used.
+    #[arg(
+        id = "gotify_url",
+        value_name = "URL",
+        long = "gotify-url",
+        requires = "gotify_token"
+    )]
+    pub url: Option<String>,
+
+    /// Gotify application token
+    #[arg(
+        id = "gotify_token",
+        value_name = "TOKEN",
+        long = "gotify-token",
+        requires = "gotify_url"
+    )]
+    pub token: Option<String>,
+
+    /// Send a "resolved" message to Gotify once the alert is removed
+    #[arg(
+        id = "gotify_resolved",
+        long = "gotify-resolved",
+        requires = "gotify_url"
+    )]
+    pub resolved: bool,
+}
+
+#[derive(Args, Debug, PartialEq, Eq, Clone)]
+pub struct Mqtt {
+    /// MQTT broker host
+    ///
+    /// If this value is not present, MQTT will not be used.
+    ///
+    /// Battery state is published after every refresh and alerts are
+    /// published whenever they are raised or cleared.
+    #[arg(id = "mqtt_host", value_name = "HOST", long = "mqtt-host")]
+    pub host: Option<String>,
+
+    /// MQTT broker port
+    #[arg(
+        id = "mqtt_port",
+        value_name = "PORT",
+        long = "mqtt-port",
+        default_value_t = 1883
+    )]
+    pub port: u16,
+
+    /// MQTT username
+    #[arg(
+        id = "mqtt_username",
+        value_name = "USERNAME",
+        long = "mqtt-username",
+        requires = "mqtt_host"
+    )]
+    pub username: Option<String>,
+
+    /// MQTT password
+    #[arg(
+        id = "mqtt_password",
+        value_name = "PASSWORD",
+        long = "mqtt-password",
+        requires = "mqtt_username"
+    )]
+    pub password: Option<String>,
+
+    /// MQTT client identifier
+    #[arg(
+        id = "mqtt_client_id",
+        value_name = "CLIENT_ID",
+        long = "mqtt-client-id",
+        default_value_t = String::from("rusty-battery")
+    )]
+    pub client_id: String,
+
+    /// Prefix of every published MQTT topic
+    ///
+    /// Battery state is published to `<PREFIX>/state`, alerts to
+    /// `<PREFIX>/alert` and availability to `<PREFIX>/availability`.
+    #[arg(
+        id = "mqtt_topic_prefix",
+        value_name = "PREFIX",
+        long = "mqtt-topic-prefix",
+        default_value_t = String::from("rusty-battery")
+    )]
+    pub topic_prefix: String,
+
+    /// MQTT keep alive interval in seconds
+    ///
+    /// The broker considers the connection lost if nothing is published
+    /// for one and a half of this interval, so it must not be shorter than
+    /// the battery refresh interval. `0` disables the keep alive.
+    #[arg(
+        id = "mqtt_keep_alive",
+        value_name = "SECS",
+        long = "mqtt-keep-alive",
+        default_value_t = 60
+    )]
+    pub keep_alive: u16,
+
+    /// Publish Home Assistant MQTT discovery configuration
+    ///
+    /// The battery then shows up in Home Assistant automatically.
+    #[arg(
+        id = "mqtt_home_assistant",
+        long = "mqtt-home-assistant",
+        requires = "mqtt_host"
+    )]
+    pub home_assistant: bool,
+}
+
+#[derive(Args, Debug, PartialEq, Eq, Clone)]
+pub struct Plug {
+    /// Smart plug which powers the charger
+    ///
+    /// If this value is not present, no smart plug will be used.
+    ///
+    /// Once the threshold is reached the plug is switched off instead of
+    /// sending notifications, they are only sent when switching it fails.
+    /// The plug is switched back on when rusty-battery exits.
+    #[arg(
+        id = "plug",
+        value_name = "KIND",
+        long = "plug",
+        value_enum,
+        requires = "plug_host"
+    )]
+    pub kind: Option<PlugKind>,
+
+    /// Smart plug host
+    ///
+    /// HTTP based plugs also accept an URL.
+    #[arg(
+        id = "plug_host",
+        value_name = "HOST",
+        long = "plug-host",
+        requires = "plug"
+    )]
+    pub host: Option<String>,
+
+    /// Battery charge threshold under which the plug is switched back on
+    ///
+    /// [minimum: 0] [maximum: 100]
+    #[arg(
+        id = "plug_on_threshold",
+        value_name = "THRESHOLD",
+        long = "plug-on-threshold",
+        value_parser = parser::threshold,
+        default_value_t = 40
+    )]
+    pub on_threshold: u8,
+}
+
+#[derive(Args, Debug, PartialEq, Eq, Clone)]
+pub struct Hook {
+    /// Command executed whenever an alert is raised
+    ///
+    /// The command is executed via `sh -c`. Battery data is passed in
+    /// the `RB_EVENT`, `RB_PERCENTAGE`, `RB_STATE`, `RB_MODEL`,
+    /// `RB_THRESHOLD`, `RB_URGENCY`, `RB_SUMMARY` and `RB_BODY`
+    /// environment variables and also as a JSON object on stdin.
+    #[arg(id = "on_alert", value_name = "COMMAND", long = "on-alert")]
+    pub on_alert: Option<String>,
+
+    /// Command executed whenever an alert is cleared
+    ///
+    /// Receives the same data as the `--on-alert` command.
+    #[arg(id = "on_clear", value_name = "COMMAND", long = "on-clear")]
+    pub on_clear: Option<String>,
+
+    /// Command executed via an action button of the desktop notification
+    ///
+    /// Receives the same data as the `--on-alert` command.
+    #[arg(id = "on_action", value_name = "COMMAND", long = "on-action")]
+    pub on_action: Option<String>,
+
+    /// Label of the action button which executes the `--on-action` command
+    #[arg(
+        id = "on_action_label",
+        value_name = "LABEL",
+        long = "on-action-label",
+        default_value_t = String::from("Run hook"),
+        requires = "on_action"
+    )]
+    pub on_action_label: String,
+
+    /// Number of seconds after which a hook command is killed
+    #[arg(
+        id = "hook_timeout",
+        value_name = "SECS",
+        long = "hook-timeout",
+        default_value_t = 30
+    )]
+    pub timeout: u64,
+}
+
+#[derive(Args, Debug, PartialEq, Eq, Clone)]
+pub struct Email {
+    /// SMTP server host
+    ///
+    /// If this value is not present, emails will not be sent.
+    #[arg(
+        id = "email_server",
+        value_name = "HOST",
+        long = "email-server",
+        requires_all = ["email_from", "email_to"]
+    )]
+    pub server: Option<String>,
+
+    /// SMTP server port
+    ///
+    /// Defaults to the usual port of the chosen security.
+    #[arg(
+        id = "email_port",
+        value_name = "PORT",
+        long = "email-port",
+        requires = "email_server"
+    )]
+    pub port: Option<u16>,
+
+    /// How the connection to the SMTP server is secured
+    #[arg(
+        id = "email_security",
+        value_name = "SECURITY",
+        long = "email-security",
+        value_enum,
+        default_value_t = EmailSecurity::Starttls
+    )]
+    pub security: EmailSecurity,
+
+    /// SMTP username
+    #[arg(
+        id = "email_username",
+        value_name = "USERNAME",
+        long = "email-username",
+        requires = "email_server"
+    )]
+    pub username: Option<String>,
+
+    /// SMTP password
+    #[arg(
+        id = "email_password",
+        value_name = "PASSWORD",
+        long = "email-password",
+        requires = "email_username"
+    )]
+    pub password: Option<String>,
+
+    /// Sender of the emails
+    ///
+    /// Example: `rusty-battery <battery@example.com>`
+    #[arg(
+        id = "email_from",
+        value_name = "ADDRESS",
+        long = "email-from",
+        requires = "email_server"
+    )]
+    pub from: Option<String>,
+
+    /// Recipients of the emails
+    #[arg(
+        id = "email_to",
+        value_name = "ADDRESSES",
+        long = "email-to",
+        value_delimiter = ',',
+        requires = "email_server"
+    )]
+    pub to: Vec<String>,
+
+    /// Minimum number of seconds between two alert emails
+    ///
+    /// Alerts raised sooner after the last sent email are not emailed.
+    #[arg(
+        id = "email_interval_secs",
+        value_name = "SECS",
+        long = "email-interval-secs",
+        default_value_t = 3600
+    )]
+    pub interval_secs: u64,
+
+    /// Send a "resolved" email once the alert is removed
+    #[arg(
+        id = "email_resolved",
+        long = "email-resolved",
+        requires = "email_server"
+    )]
+    pub resolved: bool,
 }
 
 pub fn parse() -> Cli {
@@ -113,3 +788,123 @@ mod tests {
         Cli::command().debug_assert();
     }
 }
+
+/// Failure handling of the notification channels.
+///
+/// Channels are `desktop`, `kde-connect`, `ntfy`, `gotify`, `mqtt`,
+/// `hook` and `email`.
+#[derive(Args, Debug, PartialEq, Eq, Clone)]
+pub struct Delivery {
+    /// Number of retries after a channel fails to deliver an alert
+    #[arg(long, default_value_t = 2)]
+    pub retries: u32,
+
+    /// Number of retries of a single channel
+    ///
+    /// Overrides the `--retries` value for the channel,
+    /// can be specified multiple times.
+    #[arg(
+        long = "channel-retries",
+        value_name = "CHANNEL=RETRIES",
+        value_parser = parser::channel_retries
+    )]
+    pub channel_retries: Vec<(Channel, u32)>,
+
+    /// Number of milliseconds before the first retry
+    ///
+    /// The delay is doubled before every next retry. Retries of an alert
+    /// are given up once they would wait more than 15 seconds in total.
+    #[arg(long, value_name = "MILLISECONDS", default_value_t = 500)]
+    pub retry_backoff_ms: u64,
+
+    /// Number of consecutive failed alerts which disable a channel
+    ///
+    /// A disabled channel is probed again after `--circuit-cooldown-secs`,
+    /// `0` never disables a channel.
+    #[arg(long, value_name = "FAILURES", default_value_t = 5)]
+    pub circuit_failures: u32,
+
+    /// Number of seconds after which a disabled channel is probed again
+    ///
+    /// Cooldowns longer than a day are shortened to a day.
+    #[arg(long, value_name = "SECONDS", default_value_t = 300)]
+    pub circuit_cooldown_secs: u64,
+
+    /// Channel used when another channel fails to deliver an alert
+    ///
+    /// Format: `CHANNEL=FALLBACK[:critical]`, e.g.
+    /// `kde-connect=desktop:critical` shows a critical desktop notification
+    /// whenever KDE Connect fails. Fallbacks can be chained, the fallback
+    /// channel has to be enabled.
+    #[arg(
+        long = "fallback",
+        value_name = "CHANNEL=FALLBACK",
+        value_parser = parser::fallback
+    )]
+    pub fallbacks: Vec<(Channel, Fallback)>,
+
+    /// Minimum battery percentage change which sends an updated alert
+    ///
+    /// Identical alerts are never sent twice. Channels which can't replace
+    /// their notifications, e.g. ntfy or email, receive an updated alert
+    /// only once the battery percentage changes by this value since
+    /// the alert was sent, `0` disables updated alerts.
+    #[arg(long, value_name = "PERCENT", default_value_t = 5)]
+    pub update_change: u8,
+}
+
+/// Quiet hours during which alerts are suppressed or downgraded.
+#[derive(Args, Debug, PartialEq, Eq, Clone)]
+pub struct Quiet {
+    /// Weekly window of quiet hours in local time
+    ///
+    /// Format: `[DAYS@]HH:MM-HH:MM`, e.g. `mon-fri@22:00-07:00` or
+    /// `sat,sun@23:00-09:00`. A window which ends before it starts spans
+    /// midnight, a window without days applies every day. Can be specified
+    /// multiple times.
+    #[arg(
+        id = "quiet_hours",
+        value_name = "[DAYS@]HH:MM-HH:MM",
+        long = "quiet-hours"
+    )]
+    pub windows: Vec<QuietWindow>,
+
+    /// What happens to alerts during quiet hours
+    ///
+    /// Suppressed alerts are deferred and delivered once the quiet hours
+    /// end if the battery is still charging above the threshold, downgraded
+    /// alerts are sent with the low urgency.
+    #[arg(
+        id = "quiet_mode",
+        value_name = "MODE",
+        long = "quiet-mode",
+        value_enum,
+        default_value_t = QuietMode::Suppress
+    )]
+    pub mode: QuietMode,
+
+    /// What happens to alerts of a single channel during quiet hours
+    ///
+    /// Format: `CHANNEL=MODE`, e.g. `desktop=downgrade`. Overrides
+    /// the `--quiet-mode` value for the channel, can be specified multiple
+    /// times.
+    #[arg(
+        id = "quiet_channels",
+        value_name = "CHANNEL=MODE",
+        long = "quiet-channel",
+        value_parser = parser::quiet_channel
+    )]
+    pub channels: Vec<(Channel, QuietMode)>,
+
+    /// Defer desktop notifications while the desktop is in do-not-disturb
+    /// mode
+    ///
+    /// Only notification servers which expose the `Inhibited` property,
+    /// e.g. KDE Plasma, report the do-not-disturb mode.
+    #[arg(
+        id = "quiet_desktop_dnd",
+        long = "quiet-desktop-dnd",
+        conflicts_with = "disable_desktop"
+    )]
+    pub desktop_dnd: bool,
+}
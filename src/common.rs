@@ -1,11 +1,22 @@
-use std::collections::HashSet;
-use std::{fmt, hash, io, process};
-
-pub fn vec_to_set<T>(v: Vec<T>) -> HashSet<T>
-where
-    T: Eq + hash::Hash,
-{
-    v.into_iter().collect()
+use std::fs::{self, DirBuilder};
+use std::io::{Read, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, OpenOptionsExt};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{fmt, io, process, thread, time};
+
+/// How often a command with a timeout is checked for completion.
+const POLL_INTERVAL: time::Duration = time::Duration::from_millis(10);
+
+/// Additional input of a command executed via `command_with_input`.
+#[derive(Debug, Default)]
+pub struct Input<'a> {
+    /// Environment variables set for the command.
+    pub envs: Vec<(&'a str, String)>,
+    /// Data written into stdin of the command.
+    pub stdin: Option<&'a [u8]>,
+    /// Duration after which the command is killed.
+    pub timeout: Option<time::Duration>,
 }
 
 pub fn warn_on_err<T, E>(prefix: &str, result: Result<T, E>) -> Option<T>
@@ -32,22 +43,67 @@ where
     option.map_or_else(|| "None".into(), |value| format!("{value}"))
 }
 
-pub fn format_string_set(set: &HashSet<String>) -> String {
-    let size = set.iter().map(|s| s.len() + 1).sum();
-
-    let mut result = String::with_capacity(size);
+/// Return ID of the user running the current process.
+pub fn current_uid() -> u32 {
+    // SAFETY: getuid always succeeds and has no side effects
+    unsafe { libc::getuid() }
+}
 
-    let mut set_iter = set.iter();
+/// Create the directory if needed and make sure that only the current user
+/// can access it.
+///
+/// Return an error of the `PermissionDenied` kind otherwise.
+pub fn secure_dir(dir: &Path) -> io::Result<()> {
+    DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+
+    let metadata = fs::symlink_metadata(dir)?;
+
+    let reason = if !metadata.is_dir() {
+        format!("{} isn't a directory", dir.display())
+    } else if metadata.uid() != current_uid() {
+        format!("{} is owned by another user", dir.display())
+    } else if metadata.mode() & 0o077 != 0 {
+        format!("{} is accessible by other users", dir.display())
+    } else {
+        return Ok(());
+    };
+
+    Err(io::Error::new(io::ErrorKind::PermissionDenied, reason))
+}
 
-    if let Some(first) = set_iter.next() {
-        result.push_str(first);
+/// Atomically write the data into the file at the path.
+///
+/// The parent directory is created if missing and must be accessible only by the
+/// owner, see `secure_dir`. The data is written into a new uniquely named
+/// temporary file, which is never opened through a symbolic link, and then
+/// renamed over the path.
+pub fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        secure_dir(parent)?;
     }
 
-    for s in set_iter {
-        result.push_str(", ");
-        result.push_str(s);
+    let temporary = path.with_extension(format!(
+        "{}.{}.tmp",
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .custom_flags(libc::O_NOFOLLOW)
+        .open(&temporary)
+        .and_then(|mut file| file.write_all(data))
+        .and_then(|()| fs::rename(&temporary, path));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
     }
-
     result
 }
 
@@ -58,41 +114,110 @@ pub fn slice_to_string(slice: &[u8]) -> String {
 pub fn command(
     program: &str,
     args: &[&str],
+) -> Result<process::Output, io::Error> {
+    command_with_input(program, args, &Input::default())
+}
+
+/// Execute the program with the given arguments and `Input`.
+///
+/// Return an error of the `TimedOut` kind if the program was killed
+/// because it didn't finish within the timeout.
+pub fn command_with_input(
+    program: &str,
+    args: &[&str],
+    input: &Input,
 ) -> Result<process::Output, io::Error> {
     let mut command = process::Command::new(program);
 
-    command.args(args);
+    command
+        .args(args)
+        .envs(input.envs.iter().map(|(key, value)| (key, value)))
+        .stdin(if input.stdin.is_some() {
+            process::Stdio::piped()
+        } else {
+            process::Stdio::null()
+        })
+        .stdout(process::Stdio::piped())
+        .stderr(process::Stdio::piped());
 
     log::debug!("common/command: {:#?}", command);
 
-    command.output()
-}
+    let mut child = command.spawn()?;
 
-#[cfg(test)]
-mod tests {
-    use std::iter::FromIterator;
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
 
-    use crate::error;
+    if let (Some(mut stdin), Some(data)) = (child.stdin.take(), input.stdin) {
+        // the command doesn't have to read its stdin at all
+        if let Err(e) = stdin.write_all(data) {
+            log::debug!("common/command: failed to write stdin: {e}");
+        }
+    }
 
-    use super::*;
+    let status = match input.timeout {
+        None => child.wait()?,
+        Some(timeout) => wait_timeout(&mut child, timeout)?,
+    };
 
-    #[test]
-    fn test_vec_to_set() {
-        let v = vec![1, 2, 3];
+    Ok(process::Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+/// Read the whole pipe on a separate thread so that the child can't block.
+fn read_pipe<R>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>>
+where
+    R: Read + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut data = Vec::new();
+
+        if let Some(mut pipe) = pipe {
+            if let Err(e) = pipe.read_to_end(&mut data) {
+                log::debug!("common/command: failed to read pipe: {e}");
+            }
+        }
 
-        let result = vec_to_set(v);
+        data
+    })
+}
 
-        assert_eq!(HashSet::from_iter([1, 2, 3]), result);
+/// Wait for the child to exit, killing it once the timeout elapses.
+fn wait_timeout(
+    child: &mut process::Child,
+    timeout: time::Duration,
+) -> Result<process::ExitStatus, io::Error> {
+    let start = time::Instant::now();
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+
+        if start.elapsed() >= timeout {
+            child.kill()?;
+            child.wait()?;
+
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("killed after {} seconds", timeout.as_secs()),
+            ));
+        }
+
+        thread::sleep(POLL_INTERVAL);
     }
+}
 
-    #[test]
-    fn test_vec_to_set_empty() {
-        let v = vec![];
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+    use std::{env, process};
 
-        let result: HashSet<u8> = vec_to_set(v);
+    use crate::error;
 
-        assert_eq!(HashSet::with_capacity(0), result);
-    }
+    use super::*;
 
     #[test]
     fn test_warn_on_err_ok() {
@@ -134,31 +259,44 @@ mod tests {
     }
 
     #[test]
-    fn test_format_string_set() {
-        let set = HashSet::from(["1".into(), "2".into()]);
+    fn test_slice_to_string() {
+        let slice = [240, 159, 146, 150];
 
-        let result = format_string_set(&set);
+        let result = slice_to_string(&slice);
 
-        // set is unordered so we might get one of these 2 options
-        assert!(["1, 2".into(), "2, 1".into()].contains(&result));
+        assert_eq!("\u{1f496}", result);
     }
 
     #[test]
-    fn test_format_string_set_empty() {
-        let set = HashSet::new();
-
-        let result = format_string_set(&set);
-
-        assert_eq!(result, "");
+    fn test_command_with_input() {
+        let input = Input {
+            envs: vec![("RB_TEST", "env".into())],
+            stdin: Some(b"stdin"),
+            timeout: Some(time::Duration::from_secs(10)),
+        };
+
+        let result = command_with_input(
+            "sh",
+            &["-c", "echo $RB_TEST; cat; echo err >&2"],
+            &input,
+        )
+        .unwrap();
+
+        assert!(result.status.success());
+        assert_eq!(slice_to_string(&result.stdout), "env\nstdin");
+        assert_eq!(slice_to_string(&result.stderr), "err\n");
     }
 
     #[test]
-    fn test_slice_to_string() {
-        let slice = [240, 159, 146, 150];
+    fn test_command_with_input_timeout() {
+        let input = Input {
+            timeout: Some(time::Duration::from_millis(50)),
+            ..Input::default()
+        };
 
-        let result = slice_to_string(&slice);
+        let result = command_with_input("sleep", &["10"], &input);
 
-        assert_eq!("\u{1f496}", result);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::TimedOut);
     }
 
     #[test]
@@ -169,4 +307,39 @@ mod tests {
 
         assert_eq!("", result);
     }
+
+    #[test]
+    fn test_write_atomic() {
+        let dir = env::temp_dir()
+            .join(format!("rusty-battery-test-common-{}", process::id()));
+        let path = dir.join("nested/file.json");
+
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+        assert_eq!(
+            fs::metadata(path.parent().unwrap()).unwrap().mode() & 0o777,
+            0o700
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_atomic_insecure_dir() {
+        let dir = env::temp_dir().join(format!(
+            "rusty-battery-test-common-insecure-{}",
+            process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        let path = dir.join("file.json");
+
+        let result = write_atomic(&path, b"data");
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+        assert!(!path.exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
 }
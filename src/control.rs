@@ -0,0 +1,410 @@
+//! Control socket of the running `notify` process.
+//!
+//! The `notify` process listens on a Unix domain socket in the runtime
+//! directory, the `ctl` subcommand connects to it. Every connection carries
+//! a single request and a single response, both JSON objects on one line.
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use std::{mem, ptr, result, thread};
+
+use chrono::{DateTime, Local};
+use serde_json::{json, Value};
+
+use crate::event::Event;
+use crate::{cli, common, error, status};
+
+type Result<T> = result::Result<T, error::Control>;
+
+/// Result of a `Request`, either its data or the reason it failed.
+pub type Response = result::Result<Value, String>;
+
+/// Name of the control socket.
+const FILE_NAME: &str = "control.sock";
+
+/// Duration a client has to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// Duration the `notify` loop has to handle a request.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Request sent to the running `notify` process.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Request {
+    /// Return status of the battery and the notification channels.
+    Status,
+    /// Suppress the alert for the duration.
+    Snooze(Duration),
+    /// Suppress all alerts until resumed.
+    Pause,
+    /// Resume paused or snoozed alerts.
+    Resume,
+    /// Change the normal threshold until the process exits.
+    SetThreshold(u8),
+    /// Refresh the battery and reload the boost right away.
+    Reload,
+    /// Send a test notification via every enabled channel.
+    TestNotify,
+}
+
+impl Request {
+    /// Return identifier of the `Request` used by the protocol.
+    pub const fn identifier(self) -> &'static str {
+        match self {
+            Self::Status => "status",
+            Self::Snooze(_) => "snooze",
+            Self::Pause => "pause",
+            Self::Resume => "resume",
+            Self::SetThreshold(_) => "set-threshold",
+            Self::Reload => "reload",
+            Self::TestNotify => "test-notify",
+        }
+    }
+
+    /// Return JSON representation of the `Request`.
+    fn to_json(self) -> Value {
+        match self {
+            Self::Snooze(duration) => json!({
+                "command": self.identifier(),
+                "secs": duration.as_secs(),
+            }),
+            Self::SetThreshold(threshold) => json!({
+                "command": self.identifier(),
+                "threshold": threshold,
+            }),
+            _ => json!({ "command": self.identifier() }),
+        }
+    }
+
+    /// Parse JSON representation of the `Request`.
+    fn from_json(value: &Value) -> result::Result<Self, String> {
+        let request = match value["command"].as_str() {
+            Some("status") => Self::Status,
+            Some("snooze") => Self::Snooze(Duration::from_secs(
+                value["secs"].as_u64().ok_or("snooze needs secs")?,
+            )),
+            Some("pause") => Self::Pause,
+            Some("resume") => Self::Resume,
+            Some("set-threshold") => Self::SetThreshold(
+                value["threshold"]
+                    .as_u64()
+                    .and_then(|threshold| u8::try_from(threshold).ok())
+                    .filter(|threshold| *threshold <= 100)
+                    .ok_or("set-threshold needs threshold between 0 and 100")?,
+            ),
+            Some("reload") => Self::Reload,
+            Some("test-notify") => Self::TestNotify,
+            _ => return Err(format!("unknown command {}", value["command"])),
+        };
+
+        Ok(request)
+    }
+}
+
+/// Return end of a snooze for the `Duration` starting now.
+///
+/// Return `None` if the end can't be represented.
+pub fn snooze_end(duration: Duration) -> Option<DateTime<Local>> {
+    chrono::Duration::from_std(duration)
+        .ok()
+        .and_then(|duration| Local::now().checked_add_signed(duration))
+}
+
+impl From<&cli::CtlCommand> for Request {
+    fn from(command: &cli::CtlCommand) -> Self {
+        match command {
+            cli::CtlCommand::Status => Self::Status,
+            cli::CtlCommand::Snooze { duration } => Self::Snooze(*duration),
+            cli::CtlCommand::Pause => Self::Pause,
+            cli::CtlCommand::Resume => Self::Resume,
+            cli::CtlCommand::SetThreshold { threshold } => {
+                Self::SetThreshold(*threshold)
+            }
+            cli::CtlCommand::Reload => Self::Reload,
+            cli::CtlCommand::TestNotify => Self::TestNotify,
+        }
+    }
+}
+
+/// Return path of the control socket.
+pub fn path() -> PathBuf {
+    status::runtime_dir().join(FILE_NAME)
+}
+
+/// Listen on the control socket and forward the requests via the `Sender`.
+///
+/// The runtime directory has to be owned and accessible only by the current
+/// user, the socket itself is accessible only by the owner and requests
+/// of other users are rejected.
+pub fn listen(sender: mpsc::Sender<Event>) -> Result<()> {
+    let path = path();
+
+    if let Some(parent) = path.parent() {
+        common::secure_dir(parent)?;
+    }
+
+    remove_stale(&path)?;
+
+    let listener = UnixListener::bind(&path)?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+
+    log::info!("control: listening on {}", path.display());
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if let Err(e) = serve(stream, &sender) {
+                        log::warn!("control: {e}");
+                    }
+                }
+                Err(e) => log::warn!("control: connection failed: {e}"),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Remove the control socket of the terminating process.
+pub fn remove() {
+    match fs::remove_file(path()) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => {
+            log::warn!("control: failed to remove: {e}");
+        }
+        _ => log::debug!("control: removed"),
+    }
+}
+
+/// Send the `Request` to the running `notify` process.
+///
+/// Return data of the successful `Response`.
+pub fn send(request: Request) -> Result<Value> {
+    let mut stream = match UnixStream::connect(path()) {
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused
+            ) =>
+        {
+            return Err(error::Control::NotRunning);
+        }
+        stream => stream?,
+    };
+    stream.set_read_timeout(Some(RESPONSE_TIMEOUT + REQUEST_TIMEOUT))?;
+
+    write_line(&mut stream, &request.to_json())?;
+    let response = read_line(&mut stream)?;
+
+    log::debug!("control: {} response = {response}", request.identifier());
+
+    match (response.get("ok"), response.get("error")) {
+        (Some(data), _) => Ok(data.clone()),
+        (_, Some(Value::String(reason))) => Err(error::Control::Rejected {
+            reason: reason.clone(),
+        }),
+        _ => Err(error::Control::Invalid {
+            reason: "response without result".into(),
+        }),
+    }
+}
+
+/// Send the `ctl` subcommand to the running `notify` process
+/// and print its response.
+pub fn run(command: &cli::CtlCommand) -> Result<()> {
+    match send(Request::from(command))? {
+        Value::String(text) => println!("{text}"),
+        status => {
+            for line in status::format(&status) {
+                println!("{line}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle a single connection of a client.
+fn serve(mut stream: UnixStream, sender: &mpsc::Sender<Event>) -> Result<()> {
+    let (uid, peer_uid) = (common::current_uid(), peer_uid(&stream)?);
+
+    if peer_uid != uid {
+        write_line(&mut stream, &json!({ "error": "permission denied" }))?;
+
+        return Err(error::Control::Insecure {
+            reason: format!("request of user {peer_uid} rejected"),
+        });
+    }
+
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let response = match Request::from_json(&read_line(&mut stream)?) {
+        Ok(request) => {
+            log::debug!("control: {} received", request.identifier());
+
+            forward(sender, request)
+        }
+        Err(reason) => Err(reason),
+    };
+
+    let response = match response {
+        Ok(data) => json!({ "ok": data }),
+        Err(reason) => json!({ "error": reason }),
+    };
+
+    write_line(&mut stream, &response)
+}
+
+/// Forward the `Request` to the `notify` loop via the `Sender`
+/// and wait for its `Response`.
+pub fn forward(sender: &mpsc::Sender<Event>, request: Request) -> Response {
+    let (responder, receiver) = mpsc::channel();
+
+    sender
+        .send(Event::Control(request, responder))
+        .map_err(|_| "notify loop isn't running".to_owned())?;
+
+    receiver
+        .recv_timeout(RESPONSE_TIMEOUT)
+        .map_err(|e| e.to_string())?
+}
+
+/// Remove the socket at the path unless another process listens on it.
+fn remove_stale(path: &Path) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+
+    if UnixStream::connect(path).is_ok() {
+        return Err(error::Control::InUse {
+            path: path.display().to_string(),
+        });
+    }
+
+    log::debug!("control: removing stale socket");
+
+    Ok(fs::remove_file(path)?)
+}
+
+fn write_line(stream: &mut UnixStream, value: &Value) -> Result<()> {
+    let mut line = serde_json::to_vec(value)?;
+    line.push(b'\n');
+
+    Ok(stream.write_all(&line)?)
+}
+
+fn read_line(stream: &mut UnixStream) -> Result<Value> {
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+
+    Ok(serde_json::from_str(&line)?)
+}
+
+/// Return ID of the user connected via the `UnixStream`.
+#[allow(clippy::cast_possible_truncation)]
+fn peer_uid(stream: &UnixStream) -> Result<u32> {
+    let mut credentials = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut length = mem::size_of::<libc::ucred>() as libc::socklen_t;
+
+    // SAFETY: the pointer and the length describe the `credentials` buffer
+    // which outlives the call
+    let result = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            ptr::addr_of_mut!(credentials).cast(),
+            ptr::addr_of_mut!(length),
+        )
+    };
+
+    if result != 0 {
+        return Err(error::Control::from(io::Error::last_os_error()));
+    }
+
+    Ok(credentials.uid)
+}
+
+mod std_fmt_impls {
+    use std::fmt;
+
+    use super::Request;
+
+    impl fmt::Display for Request {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Snooze(duration) => {
+                    write!(f, "snooze {} seconds", duration.as_secs())
+                }
+                Self::SetThreshold(threshold) => {
+                    write!(f, "set-threshold {threshold}%")
+                }
+                _ => write!(f, "{}", self.identifier()),
+            }
+        }
+    }
+} // std_fmt_impls
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_json() {
+        let requests = [
+            Request::Status,
+            Request::Snooze(Duration::from_secs(90)),
+            Request::Pause,
+            Request::Resume,
+            Request::SetThreshold(85),
+            Request::Reload,
+            Request::TestNotify,
+        ];
+
+        let result = requests
+            .map(|request| Request::from_json(&request.to_json()).unwrap());
+
+        assert_eq!(result, requests);
+    }
+
+    #[test]
+    fn test_request_from_json_invalid() {
+        for value in [
+            json!({"command": "shutdown"}),
+            json!({"command": "snooze"}),
+            json!({"command": "set-threshold", "threshold": 101}),
+        ] {
+            let result = Request::from_json(&value);
+
+            assert!(result.is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_snooze_end() {
+        let now = Local::now();
+
+        let end = snooze_end(Duration::from_secs(90)).unwrap();
+        let too_long = snooze_end(Duration::from_secs(u64::MAX));
+
+        assert!(end >= now + chrono::Duration::seconds(90));
+        assert!(too_long.is_none());
+    }
+
+    #[test]
+    fn test_peer_uid() {
+        let (client, _server) = UnixStream::pair().unwrap();
+
+        let result = peer_uid(&client).unwrap();
+
+        assert_eq!(result, common::current_uid());
+    }
+} // tests
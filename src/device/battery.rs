@@ -1,12 +1,24 @@
 //! Battery device.
 use std::convert::TryFrom;
-use std::result;
+use std::path::PathBuf;
+use std::{env, result};
 
-use crate::error;
+use zbus::blocking::Connection;
+
+use crate::device::kde_connect::dbus;
+use crate::device::Listed;
+use crate::{error, locale};
 
 type Result<T> = result::Result<T, error::Battery>;
 type DeviceResult<T> = result::Result<T, error::BatteryDevice>;
 
+/// Environment variable with the path to the JSON file of a mock battery.
+///
+/// The file contains the `model`, `percentage` and `state` of the battery
+/// and is read again on every refresh. Used to run `notify` in tests
+/// without a real battery.
+pub const MOCK_VARIABLE: &str = "RUSTY_BATTERY_MOCK";
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum State {
     Charging,
@@ -21,7 +33,25 @@ pub struct Battery {
     pub model: String,
     pub serial_number: String,
 
-    battery: battery::Battery,
+    backend: Backend,
+}
+
+/// Source of the battery data.
+#[derive(Debug)]
+enum Backend {
+    /// Battery of the current device.
+    System(battery::Battery),
+    /// Battery of the KDE Connect device with the ID, read over D-Bus.
+    KDEConnect { connection: Connection, id: String },
+    /// Mock battery read from the JSON file at the path.
+    Mock(PathBuf),
+}
+
+impl State {
+    /// Return localized name of the `State`.
+    pub fn localized(self) -> String {
+        locale::format("battery-state", &[("state", self.to_string().into())])
+    }
 }
 
 impl Battery {
@@ -34,41 +64,106 @@ impl Battery {
             state: fetch::state(&battery),
             model: fetch::model(&battery)?,
             serial_number: fetch::serial_number(&battery)?,
-            battery,
+            backend: Backend::System(battery),
         })
     }
 
-    /// Update attributes to current battery values.
-    pub fn refresh(&mut self) -> Result<&mut Self> {
-        self.battery.refresh()?;
+    /// Construct a new `Battery` instance of the KDE Connect device
+    /// with the given name.
+    pub fn kde_connect(name: &str) -> Result<Self> {
+        let connection = dbus::session()?;
 
-        self.refresh_percentage();
-        self.refresh_state();
+        for id in dbus::devices(&connection, false, true)? {
+            if dbus::name(&connection, &id)? == name {
+                log::info!(
+                    "device/battery: KDE Connect device \"{name}\" found"
+                );
 
-        log::info!("device/battery: refreshed = {self}");
+                return Self::from_kde_connect(connection, id);
+            }
+        }
 
-        Ok(self)
+        log::error!("device/battery: KDE Connect device \"{name}\" not found");
+
+        Err(error::Battery::NotFound {
+            model: error::Model(Some(name.to_owned())),
+        })
     }
 
-    /// Refresh and return battery percentage.
-    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
-    fn refresh_percentage(&mut self) -> u8 {
-        let percentage = fetch::percentage(&self.battery);
-        self.percentage = percentage;
+    /// Construct a new `Battery` instance of the KDE Connect device
+    /// with the given ID.
+    fn from_kde_connect(connection: Connection, id: String) -> Result<Self> {
+        let model = dbus::name(&connection, &id)?;
+        let (percentage, state) = fetch::kde_connect(&connection, &id)?;
 
-        log::debug!("device/battery: refreshed percentage = {percentage}%");
+        let device = Self {
+            percentage,
+            state,
+            model,
+            serial_number: id.clone(),
+            backend: Backend::KDEConnect { connection, id },
+        };
 
-        percentage
+        log::info!("device/battery: {device} created from KDE Connect");
+
+        Ok(device)
+    }
+
+    /// Construct a new `Battery` instance of the mock battery
+    /// described by the JSON file at the path.
+    fn mock(path: PathBuf) -> Result<Self> {
+        let (model, percentage, state) = fetch::mock(&path)?;
+
+        let device = Self {
+            percentage,
+            state,
+            model,
+            serial_number: "mock".into(),
+            backend: Backend::Mock(path),
+        };
+
+        log::info!("device/battery: {device} created from mock");
+
+        Ok(device)
     }
 
-    /// Refresh and return `State`.
-    fn refresh_state(&mut self) -> State {
-        let state = fetch::state(&self.battery);
+    /// Update attributes to current battery values.
+    ///
+    /// An unreachable KDE Connect device keeps its last percentage
+    /// but its `State` becomes `Unknown` until it's reachable again.
+    pub fn refresh(&mut self) -> Result<&mut Self> {
+        let last_percentage = self.percentage;
+        let (percentage, state) = match &mut self.backend {
+            Backend::System(battery) => {
+                battery.refresh()?;
+
+                (fetch::percentage(battery), fetch::state(battery))
+            }
+            Backend::KDEConnect { connection, id } => {
+                fetch::kde_connect(connection, id).unwrap_or_else(|e| {
+                    log::warn!(
+                        "device/battery: KDE Connect device {id} \
+                        unavailable: {e}"
+                    );
+
+                    (last_percentage, State::Unknown)
+                })
+            }
+            Backend::Mock(path) => {
+                let (_, percentage, state) = fetch::mock(path)?;
+
+                (percentage, state)
+            }
+        };
+
+        self.percentage = percentage;
         self.state = state;
 
+        log::debug!("device/battery: refreshed percentage = {percentage}%");
         log::debug!("device/battery: refreshed state = {state}");
+        log::info!("device/battery: refreshed = {self}");
 
-        state
+        Ok(self)
     }
 }
 
@@ -83,7 +178,7 @@ impl TryFrom<battery::Battery> for Battery {
             state: fetch::state(&battery),
             model: fetch::model(&battery)?,
             serial_number: fetch::serial_number(&battery)?,
-            battery,
+            backend: Backend::System(battery),
         };
 
         log::info!(
@@ -99,6 +194,14 @@ impl TryFrom<Option<&str>> for Battery {
     type Error = error::Battery;
 
     fn try_from(value: Option<&str>) -> result::Result<Self, Self::Error> {
+        if let Some(path) = env::var_os(MOCK_VARIABLE) {
+            log::warn!(
+                "device/battery: using mock battery from {MOCK_VARIABLE}"
+            );
+
+            return Self::mock(PathBuf::from(path));
+        }
+
         match value {
             None => {
                 log::info!(
@@ -127,6 +230,25 @@ pub(super) fn all() -> Result<Vec<Battery>> {
         .collect()
 }
 
+/// Return a `Vec` of `Battery` instances of reachable paired
+/// KDE Connect devices.
+///
+/// Devices without the battery plugin are skipped.
+pub(super) fn kde_connect(connection: &Connection) -> Result<Vec<Battery>> {
+    Ok(dbus::devices(connection, true, true)?
+        .into_iter()
+        .filter_map(|id| {
+            Battery::from_kde_connect(connection.clone(), id)
+                .map_err(|e| {
+                    log::debug!(
+                        "device/battery: skipping KDE Connect device: {e}"
+                    );
+                })
+                .ok()
+        })
+        .collect())
+}
+
 /// Return `Iterator` over all available `battery::Battery` devices.
 fn iterator() -> Result<
     impl Iterator<Item = result::Result<battery::Battery, battery::Error>>,
@@ -201,8 +323,27 @@ fn find(model: &str) -> Result<battery::Battery> {
     })
 }
 
+impl Listed for Battery {
+    fn describe(&self) -> String {
+        locale::format(
+            "device-battery",
+            &[
+                ("model", self.model.as_str().into()),
+                ("serial_number", self.serial_number.as_str().into()),
+                ("percentage", self.percentage.into()),
+                ("state", self.state.localized().into()),
+            ],
+        )
+    }
+}
+
 mod fetch {
-    use super::{error, DeviceResult, State};
+    use std::fs;
+    use std::path::Path;
+
+    use zbus::blocking::Connection;
+
+    use super::{dbus, error, DeviceResult, Result, State};
 
     /// Fetch battery percentage of the given `battery::Battery` device.
     #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
@@ -256,6 +397,59 @@ mod fetch {
 
         Ok(serial_number)
     }
+
+    /// Fetch battery percentage and `State` of the KDE Connect device
+    /// with the given ID.
+    ///
+    /// KDE Connect reports negative charge when it's not known yet.
+    pub fn kde_connect(
+        connection: &Connection,
+        id: &str,
+    ) -> Result<(u8, State)> {
+        let (charge, charging) = dbus::battery(connection, id)?;
+
+        let state = match (charge, charging) {
+            (..=-1, _) => State::Unknown,
+            (_, true) => State::Charging,
+            (_, false) => State::Discharging,
+        };
+        let percentage = u8::try_from(charge.clamp(0, 100)).unwrap_or_default();
+
+        log::trace!(
+            "device/battery: fetched KDE Connect {id} \
+            percentage = {percentage}%, state = {state}"
+        );
+
+        Ok((percentage, state))
+    }
+
+    /// Fetch model, percentage and `State` of the mock battery
+    /// from the JSON file at the path.
+    pub fn mock(path: &Path) -> Result<(String, u8, State)> {
+        let invalid = |reason: String| error::Battery::Mock { reason };
+
+        let data = fs::read(path).map_err(|e| invalid(e.to_string()))?;
+        let value: serde_json::Value = serde_json::from_slice(&data)
+            .map_err(|e| invalid(e.to_string()))?;
+
+        let model = value["model"].as_str().unwrap_or("mock").to_owned();
+        let percentage = value["percentage"]
+            .as_u64()
+            .and_then(|percentage| u8::try_from(percentage.min(100)).ok())
+            .ok_or_else(|| invalid("missing percentage".into()))?;
+        let state = match value["state"].as_str() {
+            Some("Charging") => State::Charging,
+            Some("Discharging") => State::Discharging,
+            _ => State::Unknown,
+        };
+
+        log::trace!(
+            "device/battery: fetched mock percentage = {percentage}%, \
+            state = {state}"
+        );
+
+        Ok((model, percentage, state))
+    }
 } // fetch
 
 mod std_fmt_impls {
@@ -288,6 +482,7 @@ mod std_fmt_impls {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::{self, KDEConnectBattery, KDEConnectDevice};
 
     #[test]
     fn test_battery_state_charging_display() {
@@ -315,4 +510,87 @@ mod tests {
 
         assert_eq!(display, "Unknown");
     }
-}
+
+    #[test]
+    fn test_kde_connect_batteries() {
+        let mut unreachable =
+            KDEConnectDevice::phone("c", "Tablet", Some((40, false)));
+        unreachable.reachable = false;
+        let (connection, _service) = testing::kde_connect_service(vec![
+            KDEConnectDevice::phone("a", "Pixel 7", Some((85, true))),
+            KDEConnectDevice::phone("b", "Watch", None),
+            unreachable,
+        ]);
+
+        let batteries = kde_connect(&connection).unwrap();
+
+        assert_eq!(batteries.len(), 1);
+        assert_eq!(batteries[0].model, "Pixel 7");
+        assert_eq!(batteries[0].serial_number, "a");
+        assert_eq!(batteries[0].percentage, 85);
+        assert_eq!(batteries[0].state, State::Charging);
+    }
+
+    #[test]
+    fn test_kde_connect_battery_unknown_charge() {
+        let (connection, _service) =
+            testing::kde_connect_service(vec![KDEConnectDevice::phone(
+                "a",
+                "Pixel 7",
+                Some((-1, false)),
+            )]);
+
+        let battery =
+            Battery::from_kde_connect(connection, "a".into()).unwrap();
+
+        assert_eq!(battery.percentage, 0);
+        assert_eq!(battery.state, State::Unknown);
+    }
+
+    #[test]
+    fn test_kde_connect_battery_refresh() {
+        let (connection, service) =
+            testing::kde_connect_service(vec![KDEConnectDevice::phone(
+                "a",
+                "Pixel 7",
+                Some((70, false)),
+            )]);
+        let mut battery =
+            Battery::from_kde_connect(connection, "a".into()).unwrap();
+        {
+            let interface = service
+                .object_server()
+                .interface::<_, KDEConnectBattery>(dbus::battery_path("a"))
+                .unwrap();
+            let mut plugin = interface.get_mut();
+            plugin.charge = 90;
+            plugin.charging = true;
+        }
+
+        battery.refresh().unwrap();
+
+        assert_eq!(battery.percentage, 90);
+        assert_eq!(battery.state, State::Charging);
+    }
+
+    #[test]
+    fn test_kde_connect_battery_refresh_unavailable() {
+        let (connection, service) =
+            testing::kde_connect_service(vec![KDEConnectDevice::phone(
+                "a",
+                "Pixel 7",
+                Some((70, true)),
+            )]);
+        let mut battery =
+            Battery::from_kde_connect(connection, "a".into()).unwrap();
+        service
+            .object_server()
+            .remove::<KDEConnectBattery, _>(dbus::battery_path("a"))
+            .unwrap();
+
+        battery.refresh().unwrap();
+
+        assert_eq!(battery.percentage, 70);
+        assert_eq!(battery.state, State::Unknown);
+    }
+} // tests
@@ -0,0 +1,169 @@
+//! KDE Connect D-Bus client.
+use std::result;
+
+use zbus::blocking::{Connection, Proxy, ProxyBuilder};
+use zbus::{CacheProperties, DBusError};
+
+use crate::error;
+
+type Result<T> = result::Result<T, error::KDEConnect>;
+
+/// Well-known name of the KDE Connect daemon.
+const SERVICE: &str = "org.kde.kdeconnect";
+/// Object path of the KDE Connect daemon.
+const DAEMON_PATH: &str = "/modules/kdeconnect";
+/// Interface of the KDE Connect daemon.
+const DAEMON_INTERFACE: &str = "org.kde.kdeconnect.daemon";
+/// Interface of a KDE Connect device.
+const DEVICE_INTERFACE: &str = "org.kde.kdeconnect.device";
+/// Interface of the battery plugin of a KDE Connect device.
+const BATTERY_INTERFACE: &str = "org.kde.kdeconnect.device.battery";
+/// Interface of the ping plugin of a KDE Connect device.
+const PING_INTERFACE: &str = "org.kde.kdeconnect.device.ping";
+/// Interface of the find my phone plugin of a KDE Connect device.
+const FIND_MY_PHONE_INTERFACE: &str = "org.kde.kdeconnect.device.findmyphone";
+/// Interface of the share plugin of a KDE Connect device.
+const SHARE_INTERFACE: &str = "org.kde.kdeconnect.device.share";
+
+/// Names of D-Bus errors reporting that the device doesn't provide
+/// the plugin needed for an action.
+const UNSUPPORTED_ERRORS: [&str; 3] = [
+    "org.freedesktop.DBus.Error.UnknownObject",
+    "org.freedesktop.DBus.Error.UnknownInterface",
+    "org.freedesktop.DBus.Error.UnknownMethod",
+];
+
+/// Return a new connection to the session bus.
+pub fn session() -> Result<Connection> {
+    Ok(Connection::session()?)
+}
+
+/// Return IDs of the devices known to the KDE Connect daemon.
+pub fn devices(
+    connection: &Connection,
+    only_reachable: bool,
+    only_paired: bool,
+) -> Result<Vec<String>> {
+    let ids: Vec<String> = proxy(connection, DAEMON_PATH, DAEMON_INTERFACE)?
+        .call("devices", &(only_reachable, only_paired))?;
+
+    log::debug!("device/kde_connect/dbus: devices = {ids:?}");
+
+    Ok(ids)
+}
+
+/// Return name of the device with the given ID.
+pub fn name(connection: &Connection, id: &str) -> Result<String> {
+    let name = proxy(connection, &device_path(id), DEVICE_INTERFACE)?
+        .get_property("name")?;
+
+    log::trace!("device/kde_connect/dbus: {id} name = \"{name}\"");
+
+    Ok(name)
+}
+
+/// Return type of the device with the given ID, e.g. `phone`.
+pub fn kind(connection: &Connection, id: &str) -> Result<String> {
+    let kind = proxy(connection, &device_path(id), DEVICE_INTERFACE)?
+        .get_property("type")?;
+
+    log::trace!("device/kde_connect/dbus: {id} type = {kind}");
+
+    Ok(kind)
+}
+
+/// Return plugins supported by the device with the given ID,
+/// e.g. `kdeconnect_ping`.
+pub fn supported_plugins(
+    connection: &Connection,
+    id: &str,
+) -> Result<Vec<String>> {
+    let plugins = proxy(connection, &device_path(id), DEVICE_INTERFACE)?
+        .get_property("supportedPlugins")?;
+
+    log::trace!("device/kde_connect/dbus: {id} plugins = {plugins:?}");
+
+    Ok(plugins)
+}
+
+/// Return charge and charging state of the device with the given ID.
+pub fn battery(connection: &Connection, id: &str) -> Result<(i32, bool)> {
+    let path = battery_path(id);
+    let proxy = proxy(connection, &path, BATTERY_INTERFACE)?;
+    let charge = proxy.get_property("charge")?;
+    let charging = proxy.get_property("isCharging")?;
+
+    log::trace!(
+        "device/kde_connect/dbus: {id} charge = {charge}%, \
+        charging = {charging}"
+    );
+
+    Ok((charge, charging))
+}
+
+/// Send a ping with the message to the device with the given ID.
+pub fn ping(connection: &Connection, id: &str, message: &str) -> Result<()> {
+    let path = plugin_path(id, "ping");
+    Ok(proxy(connection, &path, PING_INTERFACE)?
+        .call::<_, _, ()>("sendPing", &(message,))?)
+}
+
+/// Make the device with the given ID ring.
+pub fn ring(connection: &Connection, id: &str) -> Result<()> {
+    let path = plugin_path(id, "findmyphone");
+    Ok(proxy(connection, &path, FIND_MY_PHONE_INTERFACE)?
+        .call::<_, _, ()>("ring", &())?)
+}
+
+/// Share the text with the device with the given ID.
+pub fn share_text(connection: &Connection, id: &str, text: &str) -> Result<()> {
+    let path = plugin_path(id, "share");
+    Ok(proxy(connection, &path, SHARE_INTERFACE)?
+        .call::<_, _, ()>("shareText", &(text,))?)
+}
+
+/// Return whether the error reports that the device doesn't provide
+/// the plugin needed for an action.
+pub fn unsupported(error: &zbus::Error) -> bool {
+    match error {
+        zbus::Error::MethodError(name, ..) => {
+            UNSUPPORTED_ERRORS.contains(&name.as_str())
+        }
+        zbus::Error::FDO(error) => {
+            UNSUPPORTED_ERRORS.contains(&error.name().as_str())
+        }
+        _ => false,
+    }
+}
+
+/// Return object path of the device with the given ID.
+pub fn device_path(id: &str) -> String {
+    format!("{DAEMON_PATH}/devices/{id}")
+}
+
+/// Return object path of the battery plugin of the device with the given ID.
+pub fn battery_path(id: &str) -> String {
+    plugin_path(id, "battery")
+}
+
+/// Return object path of the plugin of the device with the given ID.
+pub fn plugin_path(id: &str, plugin: &str) -> String {
+    format!("{}/{plugin}", device_path(id))
+}
+
+/// Return uncached `Proxy` for the interface at the given path.
+///
+/// Property caching is disabled so that fresh values are fetched on every
+/// refresh and no match rules are needed on peer-to-peer connections.
+fn proxy<'a>(
+    connection: &Connection,
+    path: &'a str,
+    interface: &'a str,
+) -> Result<Proxy<'a>> {
+    Ok(ProxyBuilder::new_bare(connection)
+        .destination(SERVICE)?
+        .path(path)?
+        .interface(interface)?
+        .cache_properties(CacheProperties::No)
+        .build()?)
+}
@@ -0,0 +1,855 @@
+//! KDE Connect device
+use std::collections::HashMap;
+use std::result;
+use std::str::FromStr;
+
+use zbus::blocking::Connection;
+
+use crate::device::Listed;
+use crate::{common, error, locale};
+
+pub mod dbus;
+mod parse;
+
+type Result<T> = result::Result<T, error::KDEConnect>;
+
+/// Name of the KDE Connect CLI program.
+const CLI: &str = "kdeconnect-cli";
+
+/// Parts of kdeconnect-cli stderr reporting that the device doesn't
+/// provide the plugin needed for an action.
+const UNSUPPORTED_ERRORS: [&str; 2] =
+    ["No such object path", "No such interface"];
+
+/// KDE Connect device representation.
+#[derive(Clone, Debug)]
+pub struct KDEConnect {
+    /// ID of the device
+    id: String,
+    /// Name of the device
+    name: String,
+    /// Whether the device is currently reachable
+    reachable: bool,
+    /// Whether the device is paired
+    paired: bool,
+    /// Type of the device, e.g. `smartphone`, known only over D-Bus
+    kind: Option<String>,
+}
+
+/// Selector of the KDE Connect devices which should be notified.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Selector {
+    /// Device with the name, `NAME` or `name:NAME`.
+    Name(String),
+    /// Device with the ID, `id:ID`.
+    Id(String),
+    /// Devices of the type reported by KDE Connect, e.g. `type:smartphone`,
+    /// `type:phone` is an alias of it.
+    Type(String),
+    /// All paired devices, `paired`.
+    Paired,
+}
+
+/// Client of the KDE Connect daemon.
+#[derive(Clone, Debug)]
+pub enum Client {
+    /// Connection to the session bus.
+    DBus(Connection),
+    /// KDE Connect CLI program, used when D-Bus isn't available.
+    Cli(String),
+}
+
+/// Action which can be performed on a KDE Connect device.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Send a ping with the message via the `ping-msg` option.
+    Ping,
+    /// Make the device ring via the `ring` option.
+    Ring,
+    /// Share the message as text via the `share-text` option.
+    Share,
+    /// Send the message as SMS to the destination via the `send-sms` option.
+    Sms { destination: String },
+}
+
+impl Client {
+    /// Construct a new `Client`.
+    ///
+    /// D-Bus is used if the KDE Connect daemon responds on the session bus,
+    /// otherwise the KDE Connect CLI is used.
+    pub fn new() -> Self {
+        match dbus::session().and_then(|connection| {
+            dbus::devices(&connection, false, false)?;
+
+            Ok(connection)
+        }) {
+            Ok(connection) => {
+                log::debug!("device/kde_connect: using D-Bus");
+
+                Self::DBus(connection)
+            }
+            Err(e) => {
+                log::warn!(
+                    "device/kde_connect: D-Bus unavailable, \
+                    falling back to CLI: {e}"
+                );
+
+                Self::Cli(CLI.into())
+            }
+        }
+    }
+}
+
+impl Action {
+    /// Return name of the KDE Connect plugin performing the `Action`.
+    pub const fn plugin(&self) -> &'static str {
+        match self {
+            Self::Ping => "kdeconnect_ping",
+            Self::Ring => "kdeconnect_findmyphone",
+            Self::Share => "kdeconnect_share",
+            Self::Sms { .. } => "kdeconnect_sms",
+        }
+    }
+}
+
+impl KDEConnect {
+    /// Return `error::KDEConnect::Unsupported` if the device doesn't
+    /// provide the plugin of any of the `Action`s.
+    ///
+    /// Plugins are known only for reachable devices over D-Bus, the CLI
+    /// reports unsupported actions only once they are performed.
+    pub fn check_actions(
+        &self,
+        client: &Client,
+        actions: &[Action],
+    ) -> Result<()> {
+        let Client::DBus(connection) = client else {
+            return Ok(());
+        };
+
+        if !self.reachable {
+            log::debug!(
+                "device/kde_connect: actions of {self} not checked, \
+                device isn't reachable"
+            );
+
+            return Ok(());
+        }
+
+        let plugins = dbus::supported_plugins(connection, &self.id)?;
+        let unsupported = actions
+            .iter()
+            .find(|action| !plugins.iter().any(|p| p == action.plugin()));
+
+        if let Some(action) = unsupported {
+            return Err(error::KDEConnect::Unsupported {
+                device: self.name.clone(),
+                action: action.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Ping the given `KDEConnect` with the message.
+    pub fn ping(&self, client: &Client, message: &str) -> Result<()> {
+        self.perform(client, &Action::Ping, message)
+    }
+
+    /// Perform the `Action` with the given message on this `KDEConnect`.
+    ///
+    /// Return `error::KDEConnect::Unsupported` if the device doesn't
+    /// support the `Action`.
+    pub fn perform(
+        &self,
+        client: &Client,
+        action: &Action,
+        message: &str,
+    ) -> Result<()> {
+        log::trace!("device/kde_connect: performing {action} on {}", self.id);
+
+        match client {
+            Client::DBus(connection) => {
+                self.perform_dbus(connection, action, message)?;
+            }
+            Client::Cli(program) => {
+                self.perform_cli(program, action, message)?;
+            }
+        }
+
+        log::debug!("device/kde_connect: {action} performed on {self}");
+
+        Ok(())
+    }
+
+    /// Perform the `Action` over D-Bus.
+    ///
+    /// SMS is sent via the KDE Connect CLI as its D-Bus method expects
+    /// variant lists of addresses and attachments.
+    fn perform_dbus(
+        &self,
+        connection: &Connection,
+        action: &Action,
+        message: &str,
+    ) -> Result<()> {
+        let result = match action {
+            Action::Ping => dbus::ping(connection, &self.id, message),
+            Action::Ring => dbus::ring(connection, &self.id),
+            Action::Share => dbus::share_text(connection, &self.id, message),
+            Action::Sms { .. } => {
+                return self.perform_cli(CLI, action, message)
+            }
+        };
+
+        match result {
+            Err(error::KDEConnect::DBus(e)) if dbus::unsupported(&e) => {
+                Err(error::KDEConnect::Unsupported {
+                    device: self.name.clone(),
+                    action: action.to_string(),
+                })
+            }
+            result => result,
+        }
+    }
+
+    /// Perform the `Action` via the KDE Connect CLI program.
+    fn perform_cli(
+        &self,
+        program: &str,
+        action: &Action,
+        message: &str,
+    ) -> Result<()> {
+        let mut args = vec!["--device", self.id.as_str()];
+        match action {
+            Action::Ping => args.extend(["--ping-msg", message]),
+            Action::Ring => args.push("--ring"),
+            Action::Share => args.extend(["--share-text", message]),
+            Action::Sms { destination } => args.extend([
+                "--send-sms",
+                message,
+                "--destination",
+                destination,
+            ]),
+        }
+
+        let result = run(program, &args);
+        let stderr = match &result {
+            Ok((_, stderr)) | Err(error::KDEConnect::Exit { stderr, .. }) => {
+                stderr.as_str()
+            }
+            Err(_) => "",
+        };
+
+        if unsupported(stderr) {
+            return Err(error::KDEConnect::Unsupported {
+                device: self.name.clone(),
+                action: action.to_string(),
+            });
+        }
+
+        result.map(|_| ())
+    }
+
+    /// Return ID of the device.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Return name of the device.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Selector {
+    /// Return whether the `KDEConnect` is selected.
+    pub fn matches(&self, device: &KDEConnect) -> bool {
+        match self {
+            Self::Name(name) => device.name == *name,
+            Self::Id(id) => device.id == *id,
+            Self::Type(kind) => device.kind.as_ref() == Some(kind),
+            Self::Paired => device.paired,
+        }
+    }
+
+    /// Return whether the `Selector` selects a single specific device.
+    pub const fn specific(&self) -> bool {
+        matches!(self, Self::Name(_) | Self::Id(_))
+    }
+}
+
+impl FromStr for Selector {
+    type Err = String;
+
+    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+        let selector = match s.split_once(':') {
+            _ if s == "paired" => Self::Paired,
+            Some(("id", id)) => Self::Id(id.into()),
+            Some(("type", kind)) => match kind.to_lowercase().as_str() {
+                "phone" => Self::Type("smartphone".into()),
+                kind => Self::Type(kind.into()),
+            },
+            Some(("name", name)) => Self::Name(name.into()),
+            _ => Self::Name(s.into()),
+        };
+
+        match &selector {
+            Self::Name(value) | Self::Id(value) | Self::Type(value)
+                if value.is_empty() =>
+            {
+                Err(format!(
+                    "invalid selector \"{s}\", expected one of \
+                    NAME, name:NAME, id:ID, type:TYPE, paired"
+                ))
+            }
+            _ => Ok(selector),
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "ping" => Ok(Self::Ping),
+            None if s == "ring" => Ok(Self::Ring),
+            None if s == "share" => Ok(Self::Share),
+            Some(("sms", destination)) if !destination.is_empty() => {
+                Ok(Self::Sms {
+                    destination: destination.into(),
+                })
+            }
+            _ => Err(format!(
+                "unknown action \"{s}\", expected one of \
+                ping, ring, share, sms:<NUMBER>"
+            )),
+        }
+    }
+}
+
+impl Listed for KDEConnect {
+    fn describe(&self) -> String {
+        locale::format(
+            "device-kde-connect",
+            &[
+                ("name", self.name.as_str().into()),
+                ("id", self.id.as_str().into()),
+                ("kind", self.kind.as_deref().unwrap_or("unknown").into()),
+                ("reachable", self.reachable.to_string().into()),
+                ("paired", self.paired.to_string().into()),
+            ],
+        )
+    }
+}
+
+impl TryFrom<&str> for KDEConnect {
+    type Error = error::KDEConnectDevice;
+
+    /// Parse a line of the `id-name-only` KDE Connect CLI output.
+    fn try_from(value: &str) -> result::Result<Self, Self::Error> {
+        parse::id_name_only(value)
+    }
+}
+
+pub mod map {
+    use zbus::blocking::Connection;
+
+    use super::{dbus, list, parse, Client, HashMap, KDEConnect, Result};
+
+    /// Return a mapping between ID and its `KDEConnect` instance.
+    ///
+    /// `KDEConnect`s are collected via the `devices` D-Bus method or the
+    /// verbose `list-devices` KDE Connect CLI option.
+    pub fn all(client: &Client) -> Result<HashMap<String, KDEConnect>> {
+        match client {
+            Client::DBus(connection) => collect(connection, false),
+            Client::Cli(program) => {
+                Ok(into_map(parse::verbose(&list::all(program)?)?))
+            }
+        }
+    }
+
+    /// Return a mapping between ID and its `KDEConnect` instance.
+    ///
+    /// Only reachable paired `KDEConnect`s are collected via the `devices`
+    /// D-Bus method or the `list-available` KDE Connect CLI option.
+    pub fn available(client: &Client) -> Result<HashMap<String, KDEConnect>> {
+        match client {
+            Client::DBus(connection) => collect(connection, true),
+            Client::Cli(program) => Ok(into_map(
+                list::available(program)?
+                    .lines()
+                    .map(|line| {
+                        let mut device = parse::id_name_only(line)?;
+                        device.reachable = true;
+                        device.paired = true;
+
+                        Ok(device)
+                    })
+                    .collect::<Result<Vec<KDEConnect>>>()?,
+            )),
+        }
+    }
+
+    /// Return a mapping between ID and its `KDEConnect` instance.
+    ///
+    /// Data is collected over D-Bus.
+    fn collect(
+        connection: &Connection,
+        only_available: bool,
+    ) -> Result<HashMap<String, KDEConnect>> {
+        let reachable = dbus::devices(connection, true, false)?;
+        let paired = dbus::devices(connection, false, true)?;
+
+        Ok(into_map(
+            dbus::devices(connection, only_available, only_available)?
+                .into_iter()
+                .map(|id| {
+                    Ok(KDEConnect {
+                        name: dbus::name(connection, &id)?,
+                        reachable: reachable.contains(&id),
+                        paired: paired.contains(&id),
+                        kind: Some(dbus::kind(connection, &id)?),
+                        id,
+                    })
+                })
+                .collect::<Result<Vec<KDEConnect>>>()?,
+        ))
+    }
+
+    /// Return a mapping between ID and its `KDEConnect` instance.
+    fn into_map(devices: Vec<KDEConnect>) -> HashMap<String, KDEConnect> {
+        devices
+            .into_iter()
+            .map(|device| {
+                log::debug!("device/kde_connect: created {device}");
+
+                (device.id.clone(), device)
+            })
+            .collect()
+    }
+} // map
+
+mod list {
+    use super::{execute, Result};
+
+    /// Return stdout of the verbose `list-devices` KDE Connect CLI option.
+    pub fn all(program: &str) -> Result<String> {
+        log::debug!("device/kde_connect: listing all");
+
+        execute(program, &["--list-devices"])
+    }
+
+    /// Return stdout of the `list-available` KDE Connect CLI option.
+    pub fn available(program: &str) -> Result<String> {
+        log::debug!("device/kde_connect: listing all available");
+
+        execute(program, &["--list-available", "--id-name-only"])
+    }
+} // list
+
+/// Execute KDE Connect CLI command with the given arguments.
+///
+/// Warn if any data is passed into stderr.
+/// Return stdout data.
+fn execute(program: &str, args: &[&str]) -> Result<String> {
+    let (stdout, _) = run(program, args)?;
+
+    Ok(stdout)
+}
+
+/// Execute KDE Connect CLI command with the given arguments.
+///
+/// Warn if any data is passed into stderr.
+/// Return stdout and stderr data, or an error with the stderr data
+/// if the command failed.
+fn run(program: &str, args: &[&str]) -> Result<(String, String)> {
+    let output = common::command(program, args)?;
+
+    let stderr = common::slice_to_string(output.stderr.as_slice());
+    if !stderr.is_empty() {
+        log::warn!("kdeconnect/cli: stderr = {}", &stderr.trim());
+    }
+
+    let stdout = common::slice_to_string(output.stdout.as_slice());
+    if !stdout.is_empty() {
+        log::trace!("kdeconnect/cli: stdout = {}", &stdout.trim());
+    }
+
+    if !output.status.success() {
+        return Err(error::KDEConnect::Exit {
+            status: output.status,
+            stderr: stderr.trim().into(),
+        });
+    }
+
+    Ok((stdout, stderr))
+}
+
+/// Return whether the stderr of KDE Connect CLI reports that
+/// the device doesn't support the requested action.
+fn unsupported(stderr: &str) -> bool {
+    UNSUPPORTED_ERRORS
+        .iter()
+        .any(|error| stderr.contains(error))
+}
+
+mod std_fmt_impls {
+    use std::fmt;
+
+    use super::{common, Action, KDEConnect, Selector};
+
+    impl fmt::Display for Action {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Ping => write!(f, "ping"),
+                Self::Ring => write!(f, "ring"),
+                Self::Share => write!(f, "share"),
+                Self::Sms { destination } => write!(f, "sms:{destination}"),
+            }
+        }
+    }
+
+    impl fmt::Display for Selector {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Name(name) => write!(f, "{name}"),
+                Self::Id(id) => write!(f, "id:{id}"),
+                Self::Type(kind) => write!(f, "type:{kind}"),
+                Self::Paired => write!(f, "paired"),
+            }
+        }
+    }
+
+    impl fmt::Display for KDEConnect {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "KDE Connect Device: name = {}, id = {}, type = {}, \
+                reachable = {}, paired = {}",
+                self.name,
+                self.id,
+                common::format_option(self.kind.as_ref()),
+                self.reachable,
+                self.paired,
+            )
+        }
+    }
+} // std_fmt_impls
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, KDEConnectDevice, KDEConnectPing};
+
+    /// Stand-in KDE Connect CLI printing outputs of the fixture corpus.
+    const FAKE_CLI: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/kdeconnect-cli/bin/kdeconnect-cli"
+    );
+
+    #[test]
+    fn test_action_from_str() {
+        let actions = [
+            ("ping", Action::Ping),
+            ("ring", Action::Ring),
+            ("share", Action::Share),
+            (
+                "sms:+420123456789",
+                Action::Sms {
+                    destination: "+420123456789".into(),
+                },
+            ),
+        ];
+
+        for (value, expected) in actions {
+            let result = Action::from_str(value);
+
+            assert_eq!(result, Ok(expected.clone()));
+            assert_eq!(expected.to_string(), value);
+        }
+    }
+
+    #[test]
+    fn test_action_from_str_invalid() {
+        for value in ["", "sms", "sms:", "call"] {
+            let result = Action::from_str(value);
+
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn test_selector_from_str() {
+        let selectors = [
+            ("Lukas's Pixel 7", Selector::Name("Lukas's Pixel 7".into())),
+            ("name:paired", Selector::Name("paired".into())),
+            ("id:0123abcd", Selector::Id("0123abcd".into())),
+            ("type:Phone", Selector::Type("smartphone".into())),
+            ("type:smartphone", Selector::Type("smartphone".into())),
+            ("type:Tablet", Selector::Type("tablet".into())),
+            ("paired", Selector::Paired),
+        ];
+
+        for (value, expected) in selectors {
+            let result = Selector::from_str(value);
+
+            assert_eq!(result, Ok(expected));
+        }
+    }
+
+    #[test]
+    fn test_selector_from_str_invalid() {
+        for value in ["", "id:", "type:", "name:"] {
+            let result = Selector::from_str(value);
+
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn test_selector_matches() {
+        let device = KDEConnect {
+            id: "0123abcd".into(),
+            name: "Lukas's Pixel 7".into(),
+            reachable: true,
+            paired: true,
+            kind: Some("smartphone".into()),
+        };
+        let selectors = [
+            (Selector::Name("Lukas's Pixel 7".into()), true),
+            (Selector::Name("Tablet".into()), false),
+            (Selector::Id("0123abcd".into()), true),
+            (Selector::Type("smartphone".into()), true),
+            (Selector::Type("tablet".into()), false),
+            (Selector::Paired, true),
+        ];
+
+        for (selector, expected) in selectors {
+            let result = selector.matches(&device);
+
+            assert_eq!(result, expected, "{selector}");
+        }
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let stderr = "error: No such object path \
+        '/modules/kdeconnect/devices/abc/findmyphone'";
+
+        let result = unsupported(stderr);
+
+        assert!(result);
+    }
+
+    #[test]
+    fn test_unsupported_other_error() {
+        let result = unsupported("error: Couldn't find device");
+
+        assert!(!result);
+    }
+
+    #[test]
+    fn test_map_all_dbus() {
+        let mut unpaired = KDEConnectDevice::phone("b", "Living room TV", None);
+        unpaired.paired = false;
+        let (connection, _service) = testing::kde_connect_service(vec![
+            KDEConnectDevice::phone("a", "Lukas's Pixel 7", None),
+            unpaired,
+        ]);
+
+        let devices = map::all(&Client::DBus(connection)).unwrap();
+
+        let phone = &devices["a"];
+        assert_eq!(phone.name, "Lukas's Pixel 7");
+        assert_eq!(phone.kind.as_deref(), Some("smartphone"));
+        assert!(phone.reachable && phone.paired);
+        let tv = &devices["b"];
+        assert!(tv.reachable && !tv.paired);
+    }
+
+    #[test]
+    fn test_map_available_dbus() {
+        let mut unreachable = KDEConnectDevice::phone("b", "Tablet", None);
+        unreachable.reachable = false;
+        let (connection, _service) = testing::kde_connect_service(vec![
+            KDEConnectDevice::phone("a", "Lukas's Pixel 7", None),
+            unreachable,
+        ]);
+
+        let devices = map::available(&Client::DBus(connection)).unwrap();
+
+        assert_eq!(devices.len(), 1);
+        assert!(devices.contains_key("a"));
+    }
+
+    #[test]
+    fn test_map_all_same_name() {
+        let (connection, _service) = testing::kde_connect_service(vec![
+            KDEConnectDevice::phone("a", "Pixel 7", None),
+            KDEConnectDevice::phone("b", "Pixel 7", None),
+        ]);
+
+        let devices = map::all(&Client::DBus(connection)).unwrap();
+
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices["a"].name, devices["b"].name);
+    }
+
+    #[test]
+    fn test_perform_dbus_ping() {
+        let (connection, service) =
+            testing::kde_connect_service(vec![KDEConnectDevice::phone(
+                "a",
+                "Lukas's Pixel 7",
+                None,
+            )]);
+        let client = Client::DBus(connection);
+        let device = map::all(&client).unwrap().remove("a");
+
+        let result = device.unwrap().ping(&client, "unplug");
+
+        assert!(result.is_ok());
+        let ping = service
+            .object_server()
+            .interface::<_, KDEConnectPing>(dbus::plugin_path("a", "ping"))
+            .unwrap();
+        assert_eq!(ping.get().messages, ["unplug"]);
+    }
+
+    #[test]
+    fn test_perform_dbus_unsupported() {
+        let (connection, _service) =
+            testing::kde_connect_service(vec![KDEConnectDevice::phone(
+                "a",
+                "Lukas's Pixel 7",
+                None,
+            )]);
+        let client = Client::DBus(connection);
+        let device = map::all(&client).unwrap().remove("a");
+
+        let result = device.unwrap().perform(&client, &Action::Ring, "unplug");
+
+        assert!(matches!(result, Err(error::KDEConnect::Unsupported { .. })));
+    }
+
+    #[test]
+    fn test_check_actions_dbus() {
+        let (connection, _service) =
+            testing::kde_connect_service(vec![KDEConnectDevice::phone(
+                "a",
+                "Lukas's Pixel 7",
+                None,
+            )]);
+        let client = Client::DBus(connection);
+        let device = map::all(&client).unwrap().remove("a");
+        let device = device.unwrap();
+
+        let supported =
+            device.check_actions(&client, &[Action::Ping, Action::Share]);
+        let unsupported =
+            device.check_actions(&client, &[Action::Share, Action::Ring]);
+
+        assert!(supported.is_ok());
+        assert!(matches!(
+            unsupported,
+            Err(error::KDEConnect::Unsupported { action, .. })
+                if action == "ring"
+        ));
+    }
+
+    #[test]
+    fn test_check_actions_unreachable() {
+        let mut tablet = KDEConnectDevice::phone("b", "Tablet", None);
+        tablet.reachable = false;
+        let (connection, _service) = testing::kde_connect_service(vec![tablet]);
+        let client = Client::DBus(connection);
+        let device = map::all(&client).unwrap().remove("b").unwrap();
+
+        let result = device.check_actions(&client, &[Action::Ring]);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_check_actions_cli() {
+        let client = Client::Cli(testing::kde_connect_cli("exit 1"));
+        let device = KDEConnect::try_from("a phone").unwrap();
+
+        let result = device.check_actions(&client, &[Action::Ring]);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_map_all_cli() {
+        let client = Client::Cli(FAKE_CLI.into());
+
+        let devices = map::all(&client).unwrap();
+
+        let phone = &devices["9f2b7d4e_6a13_4c85_b0e9_3d7c1a5f8e26"];
+        assert!(phone.reachable && phone.paired);
+        let tablet = &devices["4e8c0a6f_d2b5_4971_a3c8_0f6e9b2d7a14"];
+        assert!(!tablet.reachable && tablet.paired);
+        assert!(phone.kind.is_none());
+    }
+
+    #[test]
+    fn test_map_available_cli() {
+        let client = Client::Cli(FAKE_CLI.into());
+
+        let devices = map::available(&client).unwrap();
+
+        assert_eq!(devices.len(), 1);
+        assert!(devices["9f2b7d4e_6a13_4c85_b0e9_3d7c1a5f8e26"].reachable);
+    }
+
+    #[test]
+    fn test_perform_cli_ping() {
+        let program = testing::kde_connect_cli(
+            r#"test "$*" = "--device a --ping-msg unplug" \
+                || echo "No such interface: $*" >&2"#,
+        );
+        let device = KDEConnect::try_from("a phone").unwrap();
+
+        let result = device.ping(&Client::Cli(program), "unplug");
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_perform_cli_unsupported() {
+        let program = testing::kde_connect_cli(
+            "echo \"error: No such object path\" >&2; exit 1",
+        );
+        let device = KDEConnect::try_from("a phone").unwrap();
+
+        let result = device.perform(&Client::Cli(program), &Action::Ring, "");
+
+        assert!(matches!(result, Err(error::KDEConnect::Unsupported { .. })));
+    }
+
+    #[test]
+    fn test_perform_cli_failed() {
+        let program =
+            testing::kde_connect_cli("echo \"error: not paired\" >&2; exit 1");
+        let device = KDEConnect::try_from("a phone").unwrap();
+
+        let result = device.perform(&Client::Cli(program), &Action::Ring, "");
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "CLI failed: exit status: 1: error: not paired"
+        );
+    }
+
+    #[test]
+    fn test_map_all_cli_failed() {
+        let client = Client::Cli(testing::kde_connect_cli("exit 1"));
+
+        let result = map::all(&client);
+
+        assert!(matches!(result, Err(error::KDEConnect::Exit { .. })));
+    }
+} // tests
@@ -0,0 +1,253 @@
+//! Parsers of the KDE Connect CLI output.
+use std::result;
+
+use super::KDEConnect;
+use crate::error;
+
+type Result<T> = result::Result<T, error::KDEConnectDevice>;
+
+/// Prefix of device lines in the verbose `list-devices` output.
+const DEVICE_PREFIX: &str = "- ";
+
+/// Statuses of the verbose `list-devices` output
+/// with their reachable and paired flags.
+const STATUSES: [(&str, bool, bool); 3] = [
+    ("(paired and reachable)", true, true),
+    ("(reachable)", true, false),
+    ("(paired)", false, true),
+];
+
+/// Parse a line of the `id-name-only` output.
+///
+/// The line consists of the ID followed by the full name,
+/// e.g. `0123abcd Lukas's Pixel 7`. Flags of the device are unknown
+/// and are left unset.
+pub fn id_name_only(line: &str) -> Result<KDEConnect> {
+    let line = line.trim();
+
+    let (id, name) = line.split_once(' ').unwrap_or((line, ""));
+    if id.is_empty() {
+        return Err(error::KDEConnectDevice::ID);
+    }
+    log::trace!("device/kde_connect/parse: id = {id}");
+
+    let name = name.trim_start();
+    if name.is_empty() {
+        return Err(error::KDEConnectDevice::Name);
+    }
+    log::trace!("device/kde_connect/parse: name = {name}");
+
+    Ok(KDEConnect {
+        id: id.into(),
+        name: name.into(),
+        reachable: false,
+        paired: false,
+        kind: None,
+    })
+}
+
+/// Parse the verbose `list-devices` output.
+///
+/// Every device is on its own line,
+/// e.g. `- Lukas's Pixel 7: 0123abcd (paired and reachable)`.
+/// Other lines, like the `2 devices found` summary, are skipped.
+pub fn verbose(output: &str) -> Result<Vec<KDEConnect>> {
+    output
+        .lines()
+        .filter_map(|line| line.trim_end().strip_prefix(DEVICE_PREFIX))
+        .map(verbose_line)
+        .collect()
+}
+
+/// Parse a device line of the verbose `list-devices` output
+/// without its prefix.
+///
+/// Names can contain `: ` so the ID is searched from the end.
+fn verbose_line(line: &str) -> Result<KDEConnect> {
+    let (rest, reachable, paired) = STATUSES
+        .iter()
+        .find_map(|(status, reachable, paired)| {
+            line.strip_suffix(status)
+                .map(|rest| (rest.trim_end(), *reachable, *paired))
+        })
+        .unwrap_or((line, false, false));
+
+    let (name, id) = rest
+        .rsplit_once(": ")
+        .ok_or_else(|| error::KDEConnectDevice::Line { line: line.into() })?;
+    if id.is_empty() || id.contains(char::is_whitespace) {
+        return Err(error::KDEConnectDevice::ID);
+    }
+    if name.is_empty() {
+        return Err(error::KDEConnectDevice::Name);
+    }
+    log::trace!(
+        "device/kde_connect/parse: id = {id}, name = {name}, \
+        reachable = {reachable}, paired = {paired}"
+    );
+
+    Ok(KDEConnect {
+        id: id.into(),
+        name: name.into(),
+        reachable,
+        paired,
+        kind: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verbose `list-devices` outputs of the fixture corpus.
+    const VERBOSE_FIXTURES: [(&str, &str); 4] = [
+        (
+            "1.3",
+            include_str!(
+                "../../../tests/fixtures/kdeconnect-cli/1.3/list-devices.txt"
+            ),
+        ),
+        (
+            "1.4",
+            include_str!(
+                "../../../tests/fixtures/kdeconnect-cli/1.4/list-devices.txt"
+            ),
+        ),
+        (
+            "21.12",
+            include_str!(
+                "../../../tests/fixtures/kdeconnect-cli/21.12/list-devices.txt"
+            ),
+        ),
+        (
+            "23.08",
+            include_str!(
+                "../../../tests/fixtures/kdeconnect-cli/23.08/list-devices.txt"
+            ),
+        ),
+    ];
+
+    /// `id-name-only` outputs of the fixture corpus.
+    const ID_NAME_ONLY_FIXTURES: [(&str, &str); 2] = [
+        (
+            "1.4",
+            include_str!(
+                "../../../tests/fixtures/kdeconnect-cli/1.4/\
+                list-devices-id-name-only.txt"
+            ),
+        ),
+        (
+            "23.08",
+            include_str!(
+                "../../../tests/fixtures/kdeconnect-cli/23.08/\
+                list-devices-id-name-only.txt"
+            ),
+        ),
+    ];
+
+    fn find<'a>(devices: &'a [KDEConnect], name: &str) -> &'a KDEConnect {
+        devices
+            .iter()
+            .find(|device| device.name == name)
+            .unwrap_or_else(|| panic!("device \"{name}\" not parsed"))
+    }
+
+    #[test]
+    fn test_id_name_only() {
+        let result = id_name_only("0123abcd Lukas's Pixel 7").unwrap();
+
+        assert_eq!(result.id, "0123abcd");
+        assert_eq!(result.name, "Lukas's Pixel 7");
+    }
+
+    #[test]
+    fn test_id_name_only_invalid() {
+        for line in ["", "  ", "0123abcd", "0123abcd  "] {
+            let result = id_name_only(line);
+
+            assert!(result.is_err(), "{line:?} parsed");
+        }
+    }
+
+    #[test]
+    fn test_id_name_only_fixtures() {
+        for (version, output) in ID_NAME_ONLY_FIXTURES {
+            let devices = output
+                .lines()
+                .map(id_name_only)
+                .collect::<Result<Vec<KDEConnect>>>()
+                .unwrap_or_else(|e| panic!("{version}: {e}"));
+
+            assert_eq!(devices.len(), 3, "{version}");
+            find(&devices, "Lukas's Pixel 7");
+            find(&devices, "Work: Galaxy Tab S8");
+            find(&devices, "Living room TV");
+        }
+    }
+
+    #[test]
+    fn test_verbose() {
+        let output = "- Lukas's Pixel 7: 0123abcd (paired and reachable)\n\
+            - Living room TV: 4567ef01 (reachable)\n\
+            - Old Phone: 89abcdef (paired)\n\
+            - Forgotten: 13579bdf \n\
+            4 devices found\n";
+
+        let result = verbose(output).unwrap();
+
+        let flags = result
+            .iter()
+            .map(|device| {
+                (device.name.as_str(), device.reachable, device.paired)
+            })
+            .collect::<Vec<(&str, bool, bool)>>();
+        assert_eq!(
+            flags,
+            [
+                ("Lukas's Pixel 7", true, true),
+                ("Living room TV", true, false),
+                ("Old Phone", false, true),
+                ("Forgotten", false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_verbose_name_with_colon() {
+        let result = verbose("- Work: Galaxy Tab: 0123abcd (paired)").unwrap();
+
+        assert_eq!(result[0].name, "Work: Galaxy Tab");
+        assert_eq!(result[0].id, "0123abcd");
+    }
+
+    #[test]
+    fn test_verbose_empty() {
+        let result = verbose("0 devices found\n").unwrap();
+
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_verbose_invalid() {
+        for output in ["- Lukas's Pixel 7", "- : 0123abcd", "- Pixel: "] {
+            let result = verbose(output);
+
+            assert!(result.is_err(), "{output:?} parsed");
+        }
+    }
+
+    #[test]
+    fn test_verbose_fixtures() {
+        for (version, output) in VERBOSE_FIXTURES {
+            let devices =
+                verbose(output).unwrap_or_else(|e| panic!("{version}: {e}"));
+
+            let phone = find(&devices, "Lukas's Pixel 7");
+            assert!(phone.reachable && phone.paired, "{version}");
+            let tablet = find(&devices, "Work: Galaxy Tab S8");
+            assert!(!tablet.reachable && tablet.paired, "{version}");
+            let tv = find(&devices, "Living room TV");
+            assert!(tv.reachable && !tv.paired, "{version}");
+        }
+    }
+} // tests
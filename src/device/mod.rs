@@ -1,7 +1,6 @@
 pub use self::battery::{Battery, State as BatteryState};
 pub use self::kde_connect::KDEConnect;
-use crate::{common, error};
-use std::fmt::Display;
+use crate::{common, error, locale};
 
 pub mod battery;
 pub mod kde_connect;
@@ -10,6 +9,7 @@ pub mod kde_connect;
 pub enum Type {
     Battery,
     KDEConnect,
+    KDEConnectBattery,
 }
 
 impl Type {
@@ -22,25 +22,46 @@ impl Type {
             Self::Battery => print_devices(self, &battery::all()?),
             Self::KDEConnect => print_devices(
                 self,
-                &kde_connect::map::all()?
+                &kde_connect::map::all(&kde_connect::Client::new())?
                     .into_values()
                     .collect::<Vec<KDEConnect>>(),
             ),
+            Self::KDEConnectBattery => print_devices(
+                self,
+                &battery::kde_connect(&kde_connect::dbus::session()?)?,
+            ),
         };
 
         Ok(())
     }
 }
 
+/// Device which can be printed in the localized device listing.
+trait Listed {
+    /// Return localized description of the device.
+    fn describe(&self) -> String;
+}
+
 fn print_devices<D>(title: Type, slice: &[D])
 where
-    D: Display,
+    D: Listed,
 {
+    let heading = match title {
+        Type::Battery => "devices-battery",
+        Type::KDEConnect => "devices-kde-connect",
+        Type::KDEConnectBattery => "devices-kde-connect-battery",
+    };
+
     println!();
-    println!("{title} Devices");
+    println!(
+        "{}",
+        locale::format(heading, &[("count", slice.len().into())])
+    );
     println!();
 
-    common::print_slice(slice);
+    common::print_slice(
+        &slice.iter().map(Listed::describe).collect::<Vec<String>>(),
+    );
 }
 
 mod std_fmt_impls {
@@ -53,6 +74,7 @@ mod std_fmt_impls {
             match self {
                 Self::Battery => write!(f, "Battery"),
                 Self::KDEConnect => write!(f, "KDE Connect"),
+                Self::KDEConnectBattery => write!(f, "KDE Connect Battery"),
             }
         }
     }
@@ -79,4 +101,13 @@ mod tests {
 
         assert_eq!(display, "KDE Connect");
     }
+
+    #[test]
+    fn test_type_kdeconnect_battery_display() {
+        let state = Type::KDEConnectBattery;
+
+        let display = format!("{state}");
+
+        assert_eq!(display, "KDE Connect Battery");
+    }
 } // tests
@@ -1,5 +1,6 @@
 use std::fmt::Debug;
 use std::io;
+use std::process;
 use std::result;
 use std::sync::mpsc;
 
@@ -15,10 +16,24 @@ pub type Result<T> = result::Result<T, Error>;
 pub enum Error {
     #[error("Battery: {}", .0)]
     Battery(#[from] Battery),
+    #[error("Boost: {}", .0)]
+    Boost(#[from] Boost),
+    #[error("Control: {}", .0)]
+    Control(#[from] Control),
+    #[error("Install: {}", .0)]
+    Install(#[from] Install),
+    #[error("Instance: {}", .0)]
+    Instance(#[from] Instance),
     #[error("KDE Connect: {}", .0)]
     KDEConnect(#[from] KDEConnect),
     #[error("Notification: {}", .0)]
     Notification(#[from] Notification),
+    #[error("Service: {}", .0)]
+    Service(#[from] Service),
+    #[error("Sleep: {}", .0)]
+    Sleep(#[from] Sleep),
+    #[error("Status: {}", .0)]
+    Status(#[from] Status),
     #[error("System: {}", .0)]
     System(#[from] System),
 }
@@ -34,6 +49,10 @@ pub enum Battery {
     NotFound { model: Model },
     #[error("device: {}", .0)]
     Device(#[from] BatteryDevice),
+    #[error("KDE Connect: {}", .0)]
+    KDEConnect(#[from] KDEConnect),
+    #[error("mock: {reason}")]
+    Mock { reason: String },
 }
 
 #[derive(Error, Debug)]
@@ -44,30 +63,188 @@ pub enum BatteryDevice {
     SerialNumber,
 }
 
+#[derive(Error, Debug)]
+pub enum Boost {
+    #[error("file: {}", .0)]
+    Io(#[from] io::Error),
+    #[error("JSON: {}", .0)]
+    Json(#[from] serde_json::Error),
+    #[error("invalid state: {reason}")]
+    Invalid { reason: String },
+    #[error("{time} is in the past")]
+    Past { time: String },
+}
+
+#[derive(Error, Debug)]
+pub enum Control {
+    #[error("socket: {}", .0)]
+    Io(#[from] io::Error),
+    #[error("JSON: {}", .0)]
+    Json(#[from] serde_json::Error),
+    #[error("insecure: {reason}")]
+    Insecure { reason: String },
+    #[error("socket {path} is used by another process")]
+    InUse { path: String },
+    #[error("invalid response: {reason}")]
+    Invalid { reason: String },
+    #[error("rusty-battery notify isn't running")]
+    NotRunning,
+    #[error("request rejected: {reason}")]
+    Rejected { reason: String },
+}
+
+#[derive(Error, Debug)]
+pub enum Install {
+    #[error("file: {}", .0)]
+    Io(#[from] io::Error),
+    #[error("invalid notify options: {reason}")]
+    Args { reason: String },
+    #[error("neither XDG_CONFIG_HOME nor HOME is set")]
+    ConfigDir,
+    #[error("systemctl {command}: {reason}")]
+    Systemctl { command: String, reason: String },
+}
+
+#[derive(Error, Debug)]
+pub enum Instance {
+    #[error("lock file: {}", .0)]
+    Io(#[from] io::Error),
+    #[error(
+        "rusty-battery notify is already running: pid = {pid}, \
+        use --replace to take over"
+    )]
+    Running { pid: String },
+    #[error("pid {pid} didn't exit within {secs} seconds")]
+    Timeout { pid: String, secs: u64 },
+    #[error("pid {pid} in the lock file isn't a rusty-battery process")]
+    Foreign { pid: String },
+}
+
 #[derive(Error, Debug)]
 pub enum Notification {
     #[error("configuration failure: {kind}")]
     Config { kind: String },
     #[error("desktop: {}", .0)]
     Desktop(#[from] notify_rust::error::Error),
+    #[error("HTTP: {}", .0)]
+    Http(#[from] Box<ureq::Error>),
+    #[error("MQTT: {}", .0)]
+    Mqtt(#[from] Mqtt),
+    #[error("plug: {}", .0)]
+    Plug(#[from] Plug),
+    #[error("hook: {}", .0)]
+    Hook(#[from] Hook),
+    #[error("email: {}", .0)]
+    Email(#[from] Email),
+}
+
+#[derive(Error, Debug)]
+pub enum Email {
+    #[error("invalid address: {}", .0)]
+    Address(#[from] lettre::address::AddressError),
+    #[error("message: {}", .0)]
+    Message(#[from] lettre::error::Error),
+    #[error("SMTP: {}", .0)]
+    Smtp(#[from] lettre::transport::smtp::Error),
+}
+
+#[derive(Error, Debug)]
+pub enum Hook {
+    #[error("failed to execute: {}", .0)]
+    Io(#[from] io::Error),
+    #[error("\"{command}\" failed: {status}")]
+    Exit {
+        command: String,
+        status: process::ExitStatus,
+    },
+    #[error("\"{command}\" killed after {secs} seconds")]
+    Timeout { command: String, secs: u64 },
+}
+
+#[derive(Error, Debug)]
+pub enum Mqtt {
+    #[error("connection: {}", .0)]
+    Io(#[from] io::Error),
+    #[error("connection refused: return code = {code}")]
+    Refused { code: u8 },
+    #[error("unexpected packet: type = {kind:#04x}")]
+    UnexpectedPacket { kind: u8 },
+}
+
+#[derive(Error, Debug)]
+pub enum Plug {
+    #[error("HTTP: {}", .0)]
+    Http(#[from] Box<ureq::Error>),
+    #[error("connection: {}", .0)]
+    Io(#[from] io::Error),
+    #[error("JSON: {}", .0)]
+    Json(#[from] serde_json::Error),
+    #[error("unexpected response: {response}")]
+    Response { response: String },
+    #[error("response of {length} bytes is too long")]
+    ResponseLength { length: u32 },
+    #[error("plug didn't report expected state: on = {expected}")]
+    Verification { expected: bool },
+    #[error("battery is still charging after the plug was switched off")]
+    StillCharging,
 }
 
 #[derive(Error, Debug)]
 pub enum KDEConnect {
     #[error("CLI: {}", .0)]
     Cli(#[from] io::Error),
+    #[error("CLI failed: {status}: {stderr}")]
+    Exit {
+        status: process::ExitStatus,
+        stderr: String,
+    },
+    #[error("D-Bus: {}", .0)]
+    DBus(#[from] zbus::Error),
     #[error("device: {}", .0)]
     Device(#[from] KDEConnectDevice),
+    #[error("device \"{device}\" doesn't support {action}")]
+    Unsupported { device: String, action: String },
+    #[error("{}", format_devices(.0))]
+    Devices(Vec<(String, Self)>),
+    #[error(
+        "selector \"{selector}\" needs D-Bus, KDE Connect CLI doesn't \
+        report device types"
+    )]
+    CliType { selector: String },
 }
 
 #[derive(Error, Debug)]
 pub enum KDEConnectDevice {
-    #[error("not found: name = {name}")]
-    NotFound { name: String },
     #[error("failed to retrieve id")]
     ID,
     #[error("failed to retrieve name")]
     Name,
+    #[error("no known device selected by \"{selector}\"")]
+    Unknown { selector: String },
+    #[error("failed to parse line \"{line}\"")]
+    Line { line: String },
+}
+
+#[derive(Error, Debug)]
+pub enum Service {
+    #[error("D-Bus: {}", .0)]
+    DBus(#[from] zbus::Error),
+}
+
+#[derive(Error, Debug)]
+pub enum Sleep {
+    #[error("D-Bus: {}", .0)]
+    DBus(#[from] zbus::Error),
+}
+
+#[derive(Error, Debug)]
+pub enum Status {
+    #[error("file: {}", .0)]
+    Io(#[from] io::Error),
+    #[error("JSON: {}", .0)]
+    Json(#[from] serde_json::Error),
+    #[error("rusty-battery notify isn't running")]
+    NotRunning,
 }
 
 #[derive(Error, Debug)]
@@ -78,6 +255,15 @@ pub enum System {
     RecvTimeout(#[from] mpsc::RecvTimeoutError),
 }
 
+/// Return the errors of the KDE Connect devices by their name.
+fn format_devices(errors: &[(String, KDEConnect)]) -> String {
+    errors
+        .iter()
+        .map(|(device, e)| format!("device \"{device}\": {e}"))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
 pub fn handle(e: Error) -> ! {
     let mut cmd = cli::Cli::command();
 
@@ -2,18 +2,45 @@ use std::sync::mpsc;
 use std::time;
 use std::{process, result};
 
+use chrono::Local;
+
+use crate::control::{self, Request};
 use crate::device::BatteryState;
-use crate::notification::{Message, Notifier};
-use crate::{device, error};
+use crate::notification::{Action, Message, Notifier, Urgency};
+use crate::service::Service;
+use crate::sleep::{self, Inhibitor};
+use crate::systemd::{self, Systemd};
+use crate::{boost, common, device, error, locale, status};
 
 type Result<T> = result::Result<T, error::Error>;
 
+/// Event which `loop_` reacts to besides the battery refresh.
+#[derive(Debug)]
+pub enum Event {
+    /// Current process should terminate.
+    Shutdown,
+    /// Action was invoked on a notification.
+    Action(Action),
+    /// Request was received via the control socket,
+    /// its `Response` is sent back via the `Sender`.
+    Control(Request, mpsc::Sender<control::Response>),
+    /// System is about to suspend, it's delayed until the `Inhibitor`
+    /// is dropped.
+    Suspend(Option<Inhibitor>),
+    /// System resumed from a suspend.
+    Resume,
+}
+
 /// Loop infinitely processing battery charge threshold events.
+#[allow(clippy::too_many_arguments)]
 pub fn loop_(
-    shutdown_receiver: &mpsc::Receiver<()>,
+    receiver: &mpsc::Receiver<Event>,
     mut battery_device: device::Battery,
     mut message: Message,
     mut notifier: Notifier,
+    mut boost: boost::Tracker,
+    mut service: Option<Service>,
+    mut systemd: Systemd,
     refresh_secs: u64,
 ) -> Result<()> {
     log::info!(
@@ -23,32 +50,52 @@ pub fn loop_(
     let refresh_duration = time::Duration::from_secs(refresh_secs);
 
     loop {
-        if battery_device.percentage >= notifier.threshold
-            && battery_device.state == BatteryState::Charging
-        {
+        let threshold =
+            boost.threshold(battery_device.state, Local::now().naive_local());
+
+        if threshold != notifier.threshold {
+            notifier.set_threshold(threshold, &message);
+            message.set_threshold(threshold);
+        }
+
+        notifier.update(&message);
+
+        if alert_pending(&battery_device, &notifier) {
             notifier.notify(&message);
         } else {
             notifier.remove();
         }
 
+        common::warn_on_err(
+            "event",
+            status::write(&battery_device, &mut notifier, boost.active()),
+        );
+
+        if let Some(service) = &mut service {
+            service.update(&battery_device, &notifier, &message);
+        }
+
+        systemd.update(&battery_device, &notifier);
+
         wait_and_update(
-            shutdown_receiver,
+            receiver,
             &mut battery_device,
             &mut message,
             &mut notifier,
-            refresh_duration,
+            &mut boost,
+            systemd.timeout(refresh_duration),
         )?;
     }
 }
 
 /// Register signal handler for SIGINT, SIGTERM and SIGHUP.
 ///
-/// The handling thread sends a value to a channel via the given `Sender`.
-pub fn set_handler(shutdown_sender: mpsc::Sender<()>) -> Result<()> {
+/// The handling thread sends `Event::Shutdown` via the given `Sender`.
+pub fn set_handler(sender: mpsc::Sender<Event>) -> Result<()> {
     ctrlc::set_handler(move || {
         log::info!("event: got signal, exiting...");
 
-        shutdown_sender.send(()).unwrap_or_else(|e| {
+        sender.send(Event::Shutdown).unwrap_or_else(|e| {
             log::error!("event: {e}");
 
             process::exit(1);
@@ -61,25 +108,104 @@ pub fn set_handler(shutdown_sender: mpsc::Sender<()>) -> Result<()> {
 
 /// Wait on the given `Receiver` and update relevant structs.
 ///
-/// If `Receiver` receives a value within the given `Duration`
+/// If `Receiver` receives `Event::Shutdown` within the given `Duration`
 /// handle the process shutdown.
 ///
+/// If `Receiver` receives `Event::Action` within the given `Duration`
+/// handle the action and perform the updates right away.
+///
+/// If `Receiver` receives `Event::Control` within the given `Duration`
+/// handle the request and send back its response.
+///
+/// If `Receiver` receives `Event::Suspend` within the given `Duration`
+/// deliver a pending critical alert before the `Inhibitor` is released.
+///
+/// If `Receiver` receives `Event::Resume` within the given `Duration`
+/// perform the updates right away.
+///
 /// If the `Receiver` times out perform the updates.
 ///
+/// The timeout may be shorter than the refresh interval,
+/// see `Systemd::timeout`.
+///
+/// If the system was suspended while waiting the updates are performed
+/// before the received event is handled.
+///
 /// If the other half of the `Receiver` channel gets disconnected return error.
 fn wait_and_update(
-    shutdown_receiver: &mpsc::Receiver<()>,
+    receiver: &mpsc::Receiver<Event>,
     battery_device: &mut device::Battery,
     message: &mut Message,
     notifier: &mut Notifier,
-    refresh_duration: time::Duration,
+    boost: &mut boost::Tracker,
+    timeout: time::Duration,
 ) -> Result<()> {
-    match shutdown_receiver.recv_timeout(refresh_duration) {
-        Ok(_) => {
+    let clock = sleep::Clock::now();
+    let event = receiver.recv_timeout(timeout);
+
+    // the timeout doesn't elapse while the system is suspended
+    if let Some(suspended) = clock.suspended(sleep::Clock::now()) {
+        log::info!(
+            "event: resumed after {} seconds of suspend",
+            suspended.as_secs()
+        );
+
+        battery_device.refresh()?;
+        message.update(battery_device);
+    }
+
+    match event {
+        Ok(Event::Shutdown) => {
             handle_shutdown(notifier);
 
             Ok(())
         }
+        Ok(Event::Action(action)) => {
+            log::info!("event: {action} action invoked");
+
+            notifier.handle_action(action, message);
+
+            battery_device.refresh()?;
+            message.update(battery_device);
+
+            Ok(())
+        }
+        Ok(Event::Control(request, responder)) => {
+            log::info!("event: {request} requested");
+
+            let response =
+                control(request, battery_device, message, notifier, boost);
+
+            // the client may have disconnected in the meantime
+            responder.send(response).ok();
+
+            Ok(())
+        }
+        Ok(Event::Suspend(inhibitor)) => {
+            battery_device.refresh()?;
+            message.update(battery_device);
+
+            if message.urgency == Urgency::Critical
+                && alert_pending(battery_device, notifier)
+            {
+                log::info!("event: delivering alert before suspend");
+
+                notifier.notify(message);
+            }
+
+            // the suspend proceeds once the lock is released
+            drop(inhibitor);
+
+            Ok(())
+        }
+        Ok(Event::Resume) => {
+            log::info!("event: refreshing after resume");
+
+            battery_device.refresh()?;
+            message.update(battery_device);
+
+            Ok(())
+        }
         Err(e) => match e {
             mpsc::RecvTimeoutError::Timeout => {
                 log::trace!("event: {e}");
@@ -98,9 +224,127 @@ fn wait_and_update(
     }
 }
 
+/// Return whether the alert should be shown for the `Battery`.
+fn alert_pending(
+    battery_device: &device::Battery,
+    notifier: &Notifier,
+) -> bool {
+    battery_device.percentage >= notifier.threshold
+        && battery_device.state == BatteryState::Charging
+}
+
+/// Handle `Request` received via the control socket.
+///
+/// Changes of the threshold take effect at the start of the next iteration.
+fn control(
+    request: Request,
+    battery_device: &mut device::Battery,
+    message: &mut Message,
+    notifier: &mut Notifier,
+    boost: &mut boost::Tracker,
+) -> control::Response {
+    let text = match request {
+        Request::Status => {
+            return Ok(status::collect(
+                battery_device,
+                notifier,
+                boost.active(),
+            ));
+        }
+        Request::Snooze(duration) => {
+            let until = control::snooze_end(duration)
+                .ok_or("snooze duration is too long")?;
+
+            notifier.snooze_for(duration)?;
+
+            locale::format(
+                "control-snoozed",
+                &[(
+                    "until",
+                    until.format(boost::TIME_FORMAT).to_string().into(),
+                )],
+            )
+        }
+        Request::Pause => {
+            notifier.pause();
+
+            locale::text("control-paused")
+        }
+        Request::Resume => {
+            notifier.resume();
+
+            locale::text("control-resumed")
+        }
+        Request::SetThreshold(threshold) => {
+            notifier.check_threshold(threshold)?;
+            boost.set_normal(threshold);
+
+            boost.active().map_or_else(
+                || {
+                    locale::format(
+                        "control-threshold",
+                        &[("threshold", threshold.into())],
+                    )
+                },
+                |active| {
+                    locale::format(
+                        "control-threshold-boost",
+                        &[
+                            ("threshold", threshold.into()),
+                            ("boost", boost::describe(active).into()),
+                        ],
+                    )
+                },
+            )
+        }
+        Request::Reload => {
+            battery_device.refresh().map_err(|e| e.to_string())?;
+            message.update(battery_device);
+
+            let threshold = boost
+                .threshold(battery_device.state, Local::now().naive_local());
+
+            locale::format(
+                "control-reloaded",
+                &[
+                    ("percentage", battery_device.percentage.into()),
+                    ("state", battery_device.state.localized().into()),
+                    ("threshold", threshold.into()),
+                ],
+            )
+        }
+        Request::TestNotify => {
+            let results = notifier.test(message);
+
+            if results.is_empty() {
+                return Err("no notification channel is enabled".into());
+            }
+
+            results
+                .into_iter()
+                .map(|(channel, delivered)| {
+                    locale::format(
+                        "control-test",
+                        &[
+                            ("channel", channel.to_string().into()),
+                            ("delivered", delivered.to_string().into()),
+                        ],
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n")
+        }
+    };
+
+    Ok(text.into())
+}
+
 /// Handle shutdown by removing notifications and terminating current process.
 fn handle_shutdown(notifier: &mut Notifier) {
-    notifier.remove();
+    systemd::stopping();
+    notifier.shutdown();
+    status::remove();
+    control::remove();
 
     log::debug!("event: terminating current process");
 
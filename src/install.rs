@@ -0,0 +1,576 @@
+//! Start of the `notify` process with the user session.
+//!
+//! `install` writes a systemd user unit or an XDG autostart entry running
+//! the current executable with the given `notify` options, `uninstall`
+//! disables the unit and removes the files again.
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::{env, iter, result};
+
+use clap::Parser;
+
+use crate::{cli, common, error, locale};
+
+type Result<T> = result::Result<T, error::Install>;
+
+const SYSTEMCTL: &str = "systemctl";
+/// Name of the systemd user unit.
+const UNIT_NAME: &str = "rusty-battery.service";
+/// Name of the XDG autostart entry.
+const ENTRY_NAME: &str = "rusty-battery.desktop";
+
+const DESCRIPTION: &str = "Notify when battery reaches a threshold";
+const DOCUMENTATION: &str = "https://github.com/kucera-lukas/rusty-battery";
+
+/// Way the `notify` process is started with the user session.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Kind {
+    Systemd,
+    Autostart,
+}
+
+impl Kind {
+    /// Return path of the installed file within the config directory.
+    fn path(self, config_dir: &Path) -> PathBuf {
+        match self {
+            Self::Systemd => config_dir.join("systemd/user").join(UNIT_NAME),
+            Self::Autostart => config_dir.join("autostart").join(ENTRY_NAME),
+        }
+    }
+
+    /// Return content of the installed file running the executable
+    /// with the `notify` options.
+    fn render(self, exe: &str, args: &[String]) -> String {
+        match self {
+            Self::Systemd => format!(
+                "[Unit]\n\
+                Description={DESCRIPTION}\n\
+                Documentation={DOCUMENTATION}\n\
+                \n\
+                [Service]\n\
+                Type=notify\n\
+                ExecStart={}\n\
+                Restart=on-failure\n\
+                WatchdogSec=90\n\
+                \n\
+                [Install]\n\
+                WantedBy=default.target\n",
+                command_line(
+                    exe,
+                    &["--log-target", "journald", "notify"],
+                    args,
+                    quote_systemd,
+                ),
+            ),
+            Self::Autostart => format!(
+                "[Desktop Entry]\n\
+                Type=Application\n\
+                Name=rusty-battery\n\
+                Comment={DESCRIPTION}\n\
+                Exec={}\n\
+                Terminal=false\n\
+                NoDisplay=true\n\
+                X-GNOME-Autostart-enabled=true\n",
+                command_line(exe, &["notify"], args, quote_desktop),
+            ),
+        }
+    }
+}
+
+/// Install the `notify` process with the options of the `cli::Install`.
+pub fn install(opts: &cli::Install) -> Result<()> {
+    validate(&opts.args)?;
+
+    let kind = if opts.systemd {
+        Kind::Systemd
+    } else {
+        Kind::Autostart
+    };
+    let exe = env::current_exe()?;
+
+    let path = install_at(
+        &config_dir()?,
+        SYSTEMCTL,
+        kind,
+        &exe.to_string_lossy(),
+        &opts.args,
+        opts.enable,
+    )?;
+
+    println!(
+        "{}",
+        locale::format(
+            "install-installed",
+            &[("path", path.display().to_string().into())],
+        )
+    );
+
+    if opts.enable {
+        println!(
+            "{}",
+            locale::format("install-enabled", &[("unit", UNIT_NAME.into())])
+        );
+    }
+
+    Ok(())
+}
+
+/// Remove the files selected by the `cli::Uninstall`, all of them
+/// if none is selected.
+pub fn uninstall(opts: &cli::Uninstall) -> Result<()> {
+    let kinds = match (opts.systemd, opts.autostart) {
+        (true, false) => vec![Kind::Systemd],
+        (false, true) => vec![Kind::Autostart],
+        _ => vec![Kind::Systemd, Kind::Autostart],
+    };
+
+    let removed = uninstall_at(&config_dir()?, SYSTEMCTL, &kinds)?;
+
+    if removed.is_empty() {
+        println!("{}", locale::text("uninstall-nothing"));
+    }
+
+    for path in removed {
+        println!(
+            "{}",
+            locale::format(
+                "uninstall-removed",
+                &[("path", path.display().to_string().into())],
+            )
+        );
+    }
+
+    Ok(())
+}
+
+/// Write the file of the `Kind` and return its path.
+///
+/// The file is accessible only by the owner as the `notify` options may
+/// contain secrets, e.g. `--email-password`.
+///
+/// systemd is asked to reload its units, the unit is also enabled
+/// and restarted if `enable` is set.
+fn install_at(
+    config_dir: &Path,
+    systemctl: &str,
+    kind: Kind,
+    exe: &str,
+    args: &[String],
+    enable: bool,
+) -> Result<PathBuf> {
+    let path = kind.path(config_dir);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(&path)?;
+    // the mode applies only to a newly created file
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(kind.render(exe, args).as_bytes())?;
+
+    log::info!("install: {} written", path.display());
+
+    if kind == Kind::Systemd {
+        common::warn_on_err("install", run(systemctl, &["daemon-reload"]));
+
+        if enable {
+            run(systemctl, &["enable", UNIT_NAME])?;
+            run(systemctl, &["restart", UNIT_NAME])?;
+        }
+    }
+
+    Ok(path)
+}
+
+/// Remove the installed files of the `Kind`s and return their paths.
+///
+/// The systemd unit is stopped and disabled before it's removed.
+fn uninstall_at(
+    config_dir: &Path,
+    systemctl: &str,
+    kinds: &[Kind],
+) -> Result<Vec<PathBuf>> {
+    let mut removed = vec![];
+
+    for &kind in kinds {
+        let path = kind.path(config_dir);
+
+        if !path.exists() {
+            log::debug!("install: {} doesn't exist", path.display());
+
+            continue;
+        }
+
+        if kind == Kind::Systemd {
+            common::warn_on_err(
+                "install",
+                run(systemctl, &["disable", "--now", UNIT_NAME]),
+            );
+        }
+
+        fs::remove_file(&path)?;
+
+        log::info!("install: {} removed", path.display());
+
+        if kind == Kind::Systemd {
+            common::warn_on_err("install", run(systemctl, &["daemon-reload"]));
+        }
+
+        removed.push(path);
+    }
+
+    Ok(removed)
+}
+
+/// Return the directory of the user configuration.
+///
+/// `$XDG_CONFIG_HOME` is used if set, otherwise `~/.config`.
+fn config_dir() -> Result<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME").map(|home| PathBuf::from(home).join(".config"))
+        })
+        .ok_or(error::Install::ConfigDir)
+}
+
+/// Check that `notify` accepts the options.
+fn validate(args: &[String]) -> Result<()> {
+    cli::Cli::try_parse_from(
+        ["rusty-battery", "notify"]
+            .into_iter()
+            .chain(args.iter().map(String::as_str)),
+    )
+    .map(|_| ())
+    .map_err(|e| error::Install::Args {
+        reason: e
+            .to_string()
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches("error: ")
+            .into(),
+    })
+}
+
+/// Run `systemctl` of the user service manager with the arguments.
+fn run(systemctl: &str, args: &[&str]) -> Result<()> {
+    let command = args.join(" ");
+    let output = common::command(systemctl, &[&["--user"], args].concat())
+        .map_err(|e| error::Install::Systemctl {
+            command: command.clone(),
+            reason: e.to_string(),
+        })?;
+
+    if output.status.success() {
+        log::debug!("install: systemctl {command} succeeded");
+
+        Ok(())
+    } else {
+        Err(error::Install::Systemctl {
+            command,
+            reason: common::slice_to_string(&output.stderr).trim().into(),
+        })
+    }
+}
+
+/// Return command line of the executable, the fixed arguments
+/// and the options, each quoted by the `quote` function.
+fn command_line(
+    exe: &str,
+    fixed: &[&str],
+    args: &[String],
+    quote: fn(&str) -> String,
+) -> String {
+    iter::once(exe)
+        .chain(fixed.iter().copied())
+        .chain(args.iter().map(String::as_str))
+        .map(quote)
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Return whether the word needs no quoting on a command line.
+fn is_plain(word: &str) -> bool {
+    !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=,@+".contains(c))
+}
+
+/// Quote the word for the `ExecStart` value of a systemd unit.
+///
+/// `%` specifiers and `$` variables are escaped as well.
+fn quote_systemd(word: &str) -> String {
+    let quoted = if is_plain(word) {
+        word.to_owned()
+    } else {
+        format!(
+            "\"{}\"",
+            word.replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+        )
+    };
+
+    quoted.replace('%', "%%").replace('$', "$$")
+}
+
+/// Quote the word for the `Exec` value of a desktop entry.
+///
+/// The value is unescaped as a string before it's split into arguments,
+/// so the backslashes of the quoting are escaped again.
+fn quote_desktop(word: &str) -> String {
+    let quoted = if is_plain(word) {
+        word.to_owned()
+    } else {
+        let mut quoted = String::from('"');
+
+        for c in word.chars() {
+            if matches!(c, '"' | '`' | '$' | '\\') {
+                quoted.push('\\');
+            }
+
+            quoted.push(c);
+        }
+
+        quoted.push('"');
+        quoted
+    };
+
+    quoted
+        .replace('%', "%%")
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::MetadataExt;
+    use std::process;
+
+    use crate::testing;
+
+    use super::*;
+
+    const EXE: &str = "/usr/bin/rusty-battery";
+
+    fn temporary_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir()
+            .join(format!("rusty-battery-test-install-{}", process::id()))
+            .join(name);
+        fs::remove_dir_all(&dir).ok();
+
+        dir
+    }
+
+    /// Return stand-in `systemctl` appending its arguments to a log file
+    /// in the directory.
+    fn create_systemctl(dir: &Path) -> String {
+        fs::create_dir_all(dir).unwrap();
+
+        testing::systemctl(&format!(
+            "echo \"$@\" >> {}",
+            dir.join("systemctl.log").display()
+        ))
+    }
+
+    fn systemctl_log(dir: &Path) -> String {
+        fs::read_to_string(dir.join("systemctl.log")).unwrap_or_default()
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|&arg| arg.to_owned()).collect()
+    }
+
+    #[test]
+    fn test_validate() {
+        let result = validate(&args(&["--threshold", "90", "-v"]));
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_invalid() {
+        let result = validate(&args(&["--threshold", "101"]));
+
+        assert!(matches!(
+            result,
+            Err(error::Install::Args { reason }) if reason.contains("101")
+        ));
+    }
+
+    #[test]
+    fn test_kind_render_systemd() {
+        let unit = Kind::Systemd.render(
+            EXE,
+            &args(&[
+                "--threshold",
+                "90",
+                "--on-alert",
+                "echo \"$RB_SUMMARY\" 100%",
+            ]),
+        );
+
+        assert!(unit.contains("Type=notify\n"));
+        assert!(unit.contains(
+            "ExecStart=/usr/bin/rusty-battery --log-target journald notify \
+            --threshold 90 --on-alert \"echo \\\"$$RB_SUMMARY\\\" 100%%\"\n"
+        ));
+    }
+
+    #[test]
+    fn test_kind_render_autostart() {
+        let entry = Kind::Autostart.render(
+            EXE,
+            &args(&[
+                "--threshold",
+                "90",
+                "--on-alert",
+                "echo \"$RB_SUMMARY\" 100%",
+            ]),
+        );
+
+        assert!(entry.starts_with("[Desktop Entry]\n"));
+        assert!(entry.contains(
+            "Exec=/usr/bin/rusty-battery notify --threshold 90 \
+            --on-alert \"echo \\\\\"\\\\$RB_SUMMARY\\\\\" 100%%\"\n"
+        ));
+    }
+
+    #[test]
+    fn test_quote_systemd() {
+        assert_eq!(quote_systemd("--threshold=90"), "--threshold=90");
+        assert_eq!(quote_systemd(""), "\"\"");
+        assert_eq!(quote_systemd("a b"), "\"a b\"");
+        assert_eq!(quote_systemd("a\\b\nc"), "\"a\\\\b\\nc\"");
+    }
+
+    #[test]
+    fn test_quote_desktop() {
+        assert_eq!(quote_desktop("--threshold=90"), "--threshold=90");
+        assert_eq!(quote_desktop(""), "\"\"");
+        assert_eq!(quote_desktop("a b"), "\"a b\"");
+        assert_eq!(quote_desktop("a`b"), "\"a\\\\`b\"");
+    }
+
+    #[test]
+    fn test_install_at_systemd() {
+        let dir = temporary_dir("systemd");
+        let systemctl = create_systemctl(&dir);
+
+        let path = install_at(&dir, &systemctl, Kind::Systemd, EXE, &[], true)
+            .unwrap();
+
+        assert_eq!(path, dir.join("systemd/user/rusty-battery.service"));
+        assert_eq!(fs::metadata(&path).unwrap().mode() & 0o777, 0o600);
+        assert!(fs::read_to_string(&path).unwrap().contains(
+            "ExecStart=/usr/bin/rusty-battery --log-target journald notify\n"
+        ));
+        assert_eq!(
+            systemctl_log(&dir),
+            "--user daemon-reload\n\
+            --user enable rusty-battery.service\n\
+            --user restart rusty-battery.service\n"
+        );
+    }
+
+    #[test]
+    fn test_install_at_autostart() {
+        let dir = temporary_dir("autostart");
+        let systemctl = create_systemctl(&dir);
+
+        let path =
+            install_at(&dir, &systemctl, Kind::Autostart, EXE, &[], false)
+                .unwrap();
+
+        assert_eq!(path, dir.join("autostart/rusty-battery.desktop"));
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .contains("Exec=/usr/bin/rusty-battery notify\n"));
+        assert_eq!(systemctl_log(&dir), "");
+    }
+
+    #[test]
+    fn test_install_at_existing() {
+        let dir = temporary_dir("existing");
+        let systemctl = create_systemctl(&dir);
+        let path = Kind::Autostart.path(&dir);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(
+            &path,
+            "previous entry, much longer than the new one ".repeat(100),
+        )
+        .unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        install_at(&dir, &systemctl, Kind::Autostart, EXE, &[], false).unwrap();
+
+        assert_eq!(fs::metadata(&path).unwrap().mode() & 0o777, 0o600);
+        assert!(!fs::read_to_string(&path).unwrap().contains("previous"));
+    }
+
+    #[test]
+    fn test_install_at_enable_failed() {
+        let dir = temporary_dir("enable-failed");
+        let systemctl = testing::systemctl("echo 'no bus' >&2; exit 1");
+
+        let installed =
+            install_at(&dir, &systemctl, Kind::Systemd, EXE, &[], false);
+        let enabled =
+            install_at(&dir, &systemctl, Kind::Systemd, EXE, &[], true);
+
+        assert!(installed.is_ok());
+        assert!(matches!(
+            enabled,
+            Err(error::Install::Systemctl { command, reason })
+                if command == "enable rusty-battery.service"
+                && reason == "no bus"
+        ));
+    }
+
+    #[test]
+    fn test_uninstall_at() {
+        let dir = temporary_dir("uninstall");
+        let systemctl = create_systemctl(&dir);
+        let kinds = [Kind::Systemd, Kind::Autostart];
+        for kind in kinds {
+            install_at(&dir, &systemctl, kind, EXE, &[], false).unwrap();
+        }
+
+        let removed = uninstall_at(&dir, &systemctl, &kinds).unwrap();
+
+        assert_eq!(
+            removed,
+            [
+                dir.join("systemd/user/rusty-battery.service"),
+                dir.join("autostart/rusty-battery.desktop"),
+            ]
+        );
+        assert!(removed.iter().all(|path| !path.exists()));
+        assert!(systemctl_log(&dir)
+            .ends_with("--user disable --now rusty-battery.service\n--user daemon-reload\n"));
+    }
+
+    #[test]
+    fn test_uninstall_at_not_installed() {
+        let dir = temporary_dir("not-installed");
+        let systemctl = create_systemctl(&dir);
+
+        let removed =
+            uninstall_at(&dir, &systemctl, &[Kind::Systemd, Kind::Autostart])
+                .unwrap();
+
+        assert!(removed.is_empty());
+        assert_eq!(systemctl_log(&dir), "");
+    }
+} // tests
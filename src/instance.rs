@@ -0,0 +1,267 @@
+//! Single running instance of the `notify` process.
+//!
+//! The `notify` process holds an exclusive lock of a file in the runtime
+//! directory for its whole lifetime and writes its PID into it, so a second
+//! process can detect it and ask it to exit. The file is never removed,
+//! otherwise a replacing process could lock a file which is already unlinked.
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use std::{process, result, thread};
+
+use crate::{common, error, status};
+
+type Result<T> = result::Result<T, error::Instance>;
+
+/// Name of the lock file.
+const FILE_NAME: &str = "notify.lock";
+
+/// Duration the replaced process has to exit.
+const REPLACE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Interval of the checks whether the replaced process exited.
+const REPLACE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Exclusive lock of the running `notify` process.
+///
+/// The lock is released once dropped or once the process exits.
+#[derive(Debug)]
+pub struct Lock {
+    /// Locked file, closing it releases the lock.
+    _file: File,
+}
+
+impl Lock {
+    /// Acquire the lock of the current user.
+    ///
+    /// If another process holds the lock it's asked to exit when `replace`
+    /// is set, so it removes its notifications, otherwise
+    /// `error::Instance::Running` is returned.
+    pub fn acquire(replace: bool) -> Result<Self> {
+        Self::acquire_at(&path(), replace)
+    }
+
+    /// The directory of the lock file has to be owned and accessible only
+    /// by the current user and the lock file can't be a symbolic link.
+    fn acquire_at(path: &Path, replace: bool) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            common::secure_dir(parent)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .mode(0o600)
+            .custom_flags(libc::O_NOFOLLOW)
+            .open(path)?;
+
+        if !try_lock(&file)? {
+            let pid = read_pid(&mut file);
+
+            match pid {
+                Some(pid) if replace => take_over(&file, pid)?,
+                _ => {
+                    return Err(error::Instance::Running {
+                        pid: common::format_option(pid),
+                    });
+                }
+            }
+        }
+
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(process::id().to_string().as_bytes())?;
+
+        log::debug!("instance: lock acquired");
+
+        Ok(Self { _file: file })
+    }
+}
+
+/// Return path of the lock file.
+pub fn path() -> PathBuf {
+    status::runtime_dir().join(FILE_NAME)
+}
+
+/// Ask the process with the PID to exit and wait until it releases the lock
+/// of the file.
+///
+/// Only a `notify` process of the same program is signaled.
+fn take_over(file: &File, pid: i32) -> Result<()> {
+    if pid <= 0 || !same_program(pid) {
+        return Err(error::Instance::Foreign {
+            pid: pid.to_string(),
+        });
+    }
+
+    log::info!("instance: asking pid {pid} to exit");
+
+    // SAFETY: kill has no memory safety requirements
+    if unsafe { libc::kill(pid, libc::SIGTERM) } != 0 {
+        log::warn!("instance: pid {pid}: {}", io::Error::last_os_error());
+    }
+
+    let deadline = Instant::now() + REPLACE_TIMEOUT;
+
+    while !try_lock(file)? {
+        if Instant::now() >= deadline {
+            return Err(error::Instance::Timeout {
+                pid: pid.to_string(),
+                secs: REPLACE_TIMEOUT.as_secs(),
+            });
+        }
+
+        thread::sleep(REPLACE_INTERVAL);
+    }
+
+    log::info!("instance: pid {pid} replaced");
+
+    Ok(())
+}
+
+/// Try to lock the file exclusively without blocking.
+///
+/// Return whether the lock was acquired.
+fn try_lock(file: &File) -> Result<bool> {
+    // SAFETY: flock has no memory safety requirements and the descriptor
+    // stays open for the duration of the call
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) }
+        == 0
+    {
+        return Ok(true);
+    }
+
+    match io::Error::last_os_error() {
+        e if e.kind() == io::ErrorKind::WouldBlock => Ok(false),
+        e => Err(error::Instance::from(e)),
+    }
+}
+
+/// Return whether the process with the PID runs the same program as the
+/// current process.
+fn same_program(pid: i32) -> bool {
+    let name = |process: &str| {
+        fs::read_to_string(format!("/proc/{process}/comm")).ok()
+    };
+
+    matches!(
+        (name(&pid.to_string()), name("self")),
+        (Some(other), Some(current)) if other == current
+    )
+}
+
+/// Read PID of the process holding the lock of the file.
+fn read_pid(file: &mut File) -> Option<i32> {
+    let mut content = String::new();
+
+    file.read_to_string(&mut content).ok()?;
+
+    content.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+
+    fn temporary_path(name: &str) -> PathBuf {
+        env::temp_dir()
+            .join(format!("rusty-battery-test-instance-{}", process::id()))
+            .join(name)
+    }
+
+    #[test]
+    fn test_lock_acquire() {
+        let path = temporary_path("acquire.lock");
+
+        let _lock = Lock::acquire_at(&path, false).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            process::id().to_string()
+        );
+    }
+
+    #[test]
+    fn test_lock_acquire_running() {
+        let path = temporary_path("running.lock");
+        let _lock = Lock::acquire_at(&path, false).unwrap();
+
+        let result = Lock::acquire_at(&path, false);
+
+        assert!(matches!(
+            result,
+            Err(error::Instance::Running { pid }) if pid == process::id().to_string()
+        ));
+    }
+
+    #[test]
+    fn test_lock_acquire_symlink() {
+        let path = temporary_path("symlink.lock");
+        let target = temporary_path("target");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&target, "content").unwrap();
+        std::os::unix::fs::symlink(&target, &path).unwrap();
+
+        let result = Lock::acquire_at(&path, false);
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "content");
+    }
+
+    #[test]
+    fn test_lock_acquire_insecure_dir() {
+        let path = temporary_path("insecure/insecure.lock");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::set_permissions(
+            path.parent().unwrap(),
+            fs::Permissions::from_mode(0o777),
+        )
+        .unwrap();
+
+        let result = Lock::acquire_at(&path, false);
+
+        assert!(matches!(
+            result,
+            Err(error::Instance::Io(e))
+                if e.kind() == io::ErrorKind::PermissionDenied
+        ));
+    }
+
+    #[test]
+    fn test_take_over_invalid_pid() {
+        let path = temporary_path("invalid-pid.lock");
+        let _lock = Lock::acquire_at(&path, false).unwrap();
+        let file = File::open(&path).unwrap();
+
+        for pid in [0, -1] {
+            let result = take_over(&file, pid);
+
+            assert!(matches!(result, Err(error::Instance::Foreign { .. })));
+        }
+    }
+
+    #[test]
+    fn test_same_program() {
+        let current = i32::try_from(process::id()).unwrap();
+
+        assert!(same_program(current));
+        assert!(!same_program(1));
+    }
+
+    #[test]
+    fn test_lock_acquire_released() {
+        let path = temporary_path("released.lock");
+        drop(Lock::acquire_at(&path, false).unwrap());
+
+        let result = Lock::acquire_at(&path, false);
+
+        assert!(result.is_ok());
+    }
+} // tests
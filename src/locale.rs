@@ -0,0 +1,294 @@
+//! Localization of the built-in notification messages and CLI output.
+//!
+//! Messages are looked up in the Fluent catalogs bundled from `locales/`.
+use std::env;
+use std::sync::OnceLock;
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use unic_langid::LanguageIdentifier;
+
+/// Bundled catalogs by their language.
+const CATALOGS: [(&str, &str); 2] = [
+    ("en", include_str!("../locales/en.ftl")),
+    ("cs", include_str!("../locales/cs.ftl")),
+];
+
+/// Language used when no bundled catalog matches the requested one.
+const DEFAULT_LANGUAGE: &str = "en";
+
+/// Environment variables selecting the language in the order of precedence.
+const VARIABLES: [&str; 3] = ["LC_ALL", "LC_MESSAGES", "LANG"];
+
+/// Group separator of the Czech numbers, a no-break space.
+const CS_GROUP_SEPARATOR: char = '\u{a0}';
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// Catalog of the messages in a single language.
+pub struct Locale {
+    language: &'static str,
+    bundle: FluentBundle<FluentResource>,
+}
+
+impl Locale {
+    /// Create a new `Locale` with the bundled catalog of the language.
+    ///
+    /// Unsupported languages fall back to English.
+    pub fn new(language: &str) -> Self {
+        let (language, catalog) = CATALOGS
+            .into_iter()
+            .find(|(bundled, _)| *bundled == language)
+            .unwrap_or(CATALOGS[0]);
+
+        let identifier: LanguageIdentifier =
+            language.parse().expect("bundled language is valid");
+        let mut bundle = FluentBundle::new_concurrent(vec![identifier]);
+        bundle.set_use_isolating(false);
+        bundle.set_formatter(Some(match language {
+            "cs" => format_number::<_, ',', CS_GROUP_SEPARATOR>,
+            _ => format_number::<_, '.', ','>,
+        }));
+        bundle
+            .add_resource(
+                FluentResource::try_new(catalog.to_owned())
+                    .expect("bundled catalog is valid"),
+            )
+            .expect("bundled catalog has unique messages");
+
+        Self { language, bundle }
+    }
+
+    /// Return language of the `Locale`.
+    pub const fn language(&self) -> &'static str {
+        self.language
+    }
+
+    /// Return the message with the ID formatted with the arguments.
+    ///
+    /// The ID itself is returned if the catalog doesn't contain it.
+    pub fn format(&self, id: &str, args: &[(&str, FluentValue)]) -> String {
+        let Some(pattern) = self
+            .bundle
+            .get_message(id)
+            .and_then(|message| message.value())
+        else {
+            log::warn!("locale: message \"{id}\" not found");
+
+            return id.into();
+        };
+
+        let args = args.iter().cloned().collect::<FluentArgs>();
+        let mut errors = vec![];
+        let text =
+            self.bundle
+                .format_pattern(pattern, Some(&args), &mut errors);
+
+        for error in errors {
+            log::warn!("locale: message \"{id}\": {error}");
+        }
+
+        text.into_owned()
+    }
+}
+
+/// Initialize the global `Locale`.
+///
+/// `language` has precedence over the environment variables.
+pub fn init(language: Option<&str>) {
+    let language = language
+        .map(ToOwned::to_owned)
+        .or_else(|| {
+            VARIABLES.into_iter().find_map(|name| {
+                env::var(name).ok().filter(|value| !value.is_empty())
+            })
+        })
+        .and_then(|locale| language_of(&locale))
+        .unwrap_or(DEFAULT_LANGUAGE);
+
+    let locale = LOCALE.get_or_init(|| Locale::new(language));
+
+    log::debug!("locale: using {}", locale.language());
+}
+
+/// Return the global `Locale`, English if it wasn't initialized.
+pub fn get() -> &'static Locale {
+    LOCALE.get_or_init(|| Locale::new(DEFAULT_LANGUAGE))
+}
+
+/// Return the message with the ID from the global `Locale`.
+pub fn text(id: &str) -> String {
+    get().format(id, &[])
+}
+
+/// Return the message with the ID from the global `Locale`
+/// formatted with the arguments.
+pub fn format(id: &str, args: &[(&str, FluentValue)]) -> String {
+    get().format(id, args)
+}
+
+/// Return the bundled language of the POSIX locale or language tag,
+/// e.g. `cs_CZ.UTF-8` or `cs-CZ`.
+pub fn language_of(locale: &str) -> Option<&'static str> {
+    let language = locale
+        .split(['_', '-', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    CATALOGS
+        .into_iter()
+        .map(|(bundled, _)| bundled)
+        .find(|bundled| *bundled == language)
+}
+
+/// Return the languages of all the bundled catalogs.
+pub fn languages() -> Vec<&'static str> {
+    CATALOGS.into_iter().map(|(language, _)| language).collect()
+}
+
+/// Format the number with the decimal and group separators.
+fn format_number<M, const DECIMAL: char, const GROUP: char>(
+    value: &FluentValue,
+    _memoizer: &M,
+) -> Option<String> {
+    let FluentValue::Number(number) = value else {
+        return None;
+    };
+
+    let formatted = number.as_string();
+    let (integer, fraction) = formatted
+        .split_once('.')
+        .map_or((&*formatted, None), |(integer, fraction)| {
+            (integer, Some(fraction))
+        });
+    let (sign, digits) = integer
+        .strip_prefix('-')
+        .map_or(("", integer), |digits| ("-", digits));
+
+    let mut grouped = String::from(sign);
+
+    for (index, digit) in digits.chars().enumerate() {
+        if number.options.use_grouping
+            && index > 0
+            && (digits.len() - index) % 3 == 0
+        {
+            grouped.push(GROUP);
+        }
+
+        grouped.push(digit);
+    }
+
+    if let Some(fraction) = fraction {
+        grouped.push(DECIMAL);
+        grouped.push_str(fraction);
+    }
+
+    Some(grouped)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use fluent_syntax::ast::Entry;
+    use fluent_syntax::parser;
+
+    use super::*;
+
+    /// Return IDs of all the messages in the catalog.
+    fn message_ids(catalog: &str) -> BTreeSet<&str> {
+        parser::parse(catalog)
+            .expect("catalog is valid")
+            .body
+            .into_iter()
+            .filter_map(|entry| match entry {
+                Entry::Message(message) => Some(message.id.name),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_catalogs_complete() {
+        let expected = message_ids(CATALOGS[0].1);
+
+        for (language, catalog) in CATALOGS {
+            let result = message_ids(catalog);
+
+            assert_eq!(result, expected, "catalog \"{language}\"");
+        }
+    }
+
+    #[test]
+    fn test_locale_format_plural() {
+        let locale = Locale::new("cs");
+
+        let result = [0, 1, 3, 5].map(|count| {
+            locale.format("devices-battery", &[("count", count.into())])
+        });
+
+        assert_eq!(
+            result,
+            [
+                "Nebyly nalezeny žádné baterie",
+                "Nalezena 1 baterie",
+                "Nalezeny 3 baterie",
+                "Nalezeno 5 baterií",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_locale_format_number() {
+        let value = FluentValue::from("12345.5".parse::<f64>().unwrap());
+
+        let result = ["en", "cs"].map(|language| {
+            Locale::new(language)
+                .format("email-model", &[("model", value.clone())])
+        });
+
+        assert_eq!(result, ["Model: 12,345.5", "Model: 12\u{a0}345,5"]);
+    }
+
+    #[test]
+    fn test_locale_format_select() {
+        let locale = Locale::new("cs");
+
+        let result = [true, false].map(|delivered| {
+            locale.format(
+                "control-test",
+                &[
+                    ("channel", "desktop".into()),
+                    ("delivered", delivered.to_string().into()),
+                ],
+            )
+        });
+
+        assert_eq!(result, ["desktop: doručeno", "desktop: selhalo"]);
+    }
+
+    #[test]
+    fn test_locale_format_missing() {
+        let locale = Locale::new("en");
+
+        let result = locale.format("missing-message", &[]);
+
+        assert_eq!(result, "missing-message");
+    }
+
+    #[test]
+    fn test_locale_new_unsupported() {
+        let locale = Locale::new("de");
+
+        assert_eq!(locale.language(), "en");
+    }
+
+    #[test]
+    fn test_language_of() {
+        let result =
+            ["cs_CZ.UTF-8", "cs-CZ", "en_GB", "de_DE", "C"].map(language_of);
+
+        assert_eq!(result, [Some("cs"), Some("cs"), Some("en"), None, None]);
+    }
+} // tests
@@ -1,18 +1,61 @@
-use std::io::Write;
+use std::io::{self, Write};
+use std::os::unix::net::UnixDatagram;
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
 
 use chrono::Local;
 use env_logger::Builder;
-use log::LevelFilter;
+use log::{Level, LevelFilter, Log, Metadata, Record};
 
-/// Initialize `env_logger`.
-pub fn init(verbose: &clap_verbosity_flag::Verbosity) {
+/// Path of the socket of the native journald protocol.
+const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+/// Identifier of the journal entries.
+const SYSLOG_IDENTIFIER: &str = "rusty-battery";
+
+/// Extra fields attached to every journal entry.
+static FIELDS: Mutex<Vec<(&str, String)>> = Mutex::new(Vec::new());
+
+/// Where the log records are written to.
+#[derive(clap::ValueEnum, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Target {
+    /// Standard error output.
+    Stderr,
+    /// systemd journal with structured fields, e.g. `BATTERY_PERCENTAGE`.
+    Journald,
+}
+
+/// Initialize the logger of the `Target`.
+///
+/// If the journal isn't available, `env_logger` is used instead.
+pub fn init(verbose: &clap_verbosity_flag::Verbosity, target: Target) {
     let level_filter = verbose.log_level_filter();
 
-    create_builder(level_filter).init();
+    match target {
+        Target::Stderr => create_builder(level_filter).init(),
+        Target::Journald => match Journal::new(JOURNAL_SOCKET, level_filter) {
+            Ok(journal) => {
+                log::set_boxed_logger(Box::new(journal))
+                    .expect("logger should be initialized only once");
+                log::set_max_level(level_filter);
+            }
+            Err(e) => {
+                create_builder(level_filter).init();
+
+                log::warn!("logger: journal not available: {e}");
+            }
+        },
+    }
 
     log::debug!("logger: initialized with RUST_LOG={level_filter}");
 }
 
+/// Replace the extra fields attached to every journal entry.
+///
+/// Field names must consist of uppercase letters, digits and underscores.
+pub fn set_fields(fields: Vec<(&'static str, String)>) {
+    *FIELDS.lock().unwrap_or_else(PoisonError::into_inner) = fields;
+}
+
 /// Return `env_logger::Builder`
 fn create_builder(level_filter: LevelFilter) -> Builder {
     let mut builder = Builder::new();
@@ -32,8 +75,108 @@ fn create_builder(level_filter: LevelFilter) -> Builder {
     builder
 }
 
+/// Logger sending the records to journald via its native protocol.
+///
+/// Records which can't be sent are written to the standard error output.
+#[derive(Debug)]
+struct Journal {
+    socket: UnixDatagram,
+    level_filter: LevelFilter,
+}
+
+impl Journal {
+    fn new(
+        path: impl AsRef<Path>,
+        level_filter: LevelFilter,
+    ) -> io::Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(path)?;
+
+        Ok(Self {
+            socket,
+            level_filter,
+        })
+    }
+}
+
+impl Log for Journal {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level_filter
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let entry = entry(
+            record,
+            &FIELDS.lock().unwrap_or_else(PoisonError::into_inner),
+        );
+
+        if let Err(e) = self.socket.send(&entry) {
+            eprintln!("[{}] - {} ({e})", record.level(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Return journal entry of the `Record` with the extra fields.
+fn entry(record: &Record, fields: &[(&str, String)]) -> Vec<u8> {
+    let mut entry = Vec::new();
+
+    append_field(&mut entry, "MESSAGE", &record.args().to_string());
+    append_field(&mut entry, "PRIORITY", priority(record.level()));
+    append_field(&mut entry, "SYSLOG_IDENTIFIER", SYSLOG_IDENTIFIER);
+    append_field(&mut entry, "TARGET", record.target());
+
+    if let Some(file) = record.file() {
+        append_field(&mut entry, "CODE_FILE", file);
+    }
+
+    if let Some(line) = record.line() {
+        append_field(&mut entry, "CODE_LINE", &line.to_string());
+    }
+
+    for (name, value) in fields {
+        append_field(&mut entry, name, value);
+    }
+
+    entry
+}
+
+/// Append the field to the journal entry.
+///
+/// Values containing a newline are serialized with their length.
+fn append_field(entry: &mut Vec<u8>, name: &str, value: &str) {
+    entry.extend_from_slice(name.as_bytes());
+
+    if value.contains('\n') {
+        entry.push(b'\n');
+        entry.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        entry.push(b'=');
+    }
+
+    entry.extend_from_slice(value.as_bytes());
+    entry.push(b'\n');
+}
+
+/// Return syslog priority of the `Level`.
+const fn priority(level: Level) -> &'static str {
+    match level {
+        Level::Error => "3",
+        Level::Warn => "4",
+        Level::Info => "6",
+        Level::Debug | Level::Trace => "7",
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::{env, fs, process};
+
     use super::*;
 
     fn init_log() {
@@ -44,8 +187,8 @@ mod tests {
     #[should_panic(expected = "Builder::init should not be called \
         after logger initialized: SetLoggerError(())")]
     fn test_init_logger_initialized() {
-        init(&clap_verbosity_flag::Verbosity::new(0, 0));
-        init(&clap_verbosity_flag::Verbosity::new(0, 0));
+        init(&clap_verbosity_flag::Verbosity::new(0, 0), Target::Stderr);
+        init(&clap_verbosity_flag::Verbosity::new(0, 0), Target::Stderr);
     }
 
     #[test]
@@ -90,4 +233,80 @@ mod tests {
     fn test_create_builder_trace() {
         assert_create_builder(LevelFilter::Trace);
     }
+
+    #[test]
+    fn test_append_field() {
+        let mut entry = Vec::new();
+
+        append_field(&mut entry, "BATTERY_PERCENTAGE", "85");
+
+        assert_eq!(entry, b"BATTERY_PERCENTAGE=85\n");
+    }
+
+    #[test]
+    fn test_append_field_multiline() {
+        let mut entry = Vec::new();
+
+        append_field(&mut entry, "MESSAGE", "a\nb");
+
+        assert_eq!(entry, b"MESSAGE\n\x03\0\0\0\0\0\0\0a\nb\n");
+    }
+
+    #[test]
+    fn test_entry() {
+        let fields = [("BATTERY_PERCENTAGE", "85".to_owned())];
+
+        let entry = entry(
+            &Record::builder()
+                .args(format_args!("test-message"))
+                .level(Level::Warn)
+                .target("rusty_battery::event")
+                .build(),
+            &fields,
+        );
+
+        assert_eq!(
+            entry,
+            b"MESSAGE=test-message\n\
+            PRIORITY=4\n\
+            SYSLOG_IDENTIFIER=rusty-battery\n\
+            TARGET=rusty_battery::event\n\
+            BATTERY_PERCENTAGE=85\n"
+        );
+    }
+
+    #[test]
+    fn test_journal_log() {
+        let path = env::temp_dir()
+            .join(format!("rusty-battery-test-journal-{}.sock", process::id()));
+        fs::remove_file(&path).ok();
+        let server = UnixDatagram::bind(&path).unwrap();
+        let journal = Journal::new(&path, LevelFilter::Info).unwrap();
+        let mut buf = [0; 1024];
+
+        journal.log(
+            &Record::builder()
+                .args(format_args!("test-debug"))
+                .level(Level::Debug)
+                .build(),
+        );
+        journal.log(
+            &Record::builder()
+                .args(format_args!("test-info"))
+                .level(Level::Info)
+                .build(),
+        );
+        let size = server.recv(&mut buf).unwrap();
+
+        assert!(buf[..size].starts_with(b"MESSAGE=test-info\nPRIORITY=6\n"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_journal_new_missing() {
+        let result =
+            Journal::new("/nonexistent/journal.sock", LevelFilter::Info);
+
+        assert!(result.is_err());
+    }
 }
@@ -17,15 +17,26 @@
 
 use std::process;
 
+mod boost;
 mod cli;
 mod common;
+mod control;
 mod device;
 mod error;
 mod event;
+mod install;
+mod instance;
+mod locale;
 mod logger;
 mod notification;
 mod notify;
 mod parser;
+mod service;
+mod sleep;
+mod status;
+mod systemd;
+#[cfg(test)]
+mod testing;
 
 fn main() -> ! {
     process::exit(match run_app() {
@@ -37,35 +48,31 @@ fn main() -> ! {
 fn run_app() -> error::Result<()> {
     let opts = cli::parse();
 
-    logger::init(&opts.verbose);
+    logger::init(&opts.verbose, opts.log_target);
+    locale::init(opts.locale.as_deref());
 
     match opts.cmd {
-        cli::Command::Notify {
-            threshold,
-            model,
-            refresh_secs,
-            summary,
-            body,
-            kde_connect_names,
-            disable_desktop,
-        } => notify::notify(
-            threshold,
-            model.as_deref(),
-            refresh_secs,
-            summary,
-            body,
-            kde_connect_names,
-            disable_desktop,
-        )?,
-        cli::Command::Batteries => batteries()?,
+        cli::Command::Notify(opts) => notify::notify(*opts)?,
+        cli::Command::Batteries(opts) => batteries(&opts)?,
         cli::Command::KDEConnectDevices => kde_connect_devices()?,
+        cli::Command::Status => status::print()?,
+        cli::Command::Boost(opts) => boost::run(&opts)?,
+        cli::Command::Ctl(opts) => control::run(&opts.command)?,
+        cli::Command::Install(opts) => install::install(&opts)?,
+        cli::Command::Uninstall(opts) => install::uninstall(&opts)?,
     }
 
     Ok(())
 }
 
-fn batteries() -> error::Result<()> {
-    device::Type::Battery.print()
+fn batteries(opts: &cli::Batteries) -> error::Result<()> {
+    device::Type::Battery.print()?;
+
+    if opts.include_kde_connect {
+        device::Type::KDEConnectBattery.print()?;
+    }
+
+    Ok(())
 }
 
 fn kde_connect_devices() -> error::Result<()> {
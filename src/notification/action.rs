@@ -0,0 +1,80 @@
+use std::str::FromStr;
+
+/// Action invoked by the user on a notification.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Action {
+    /// Suppress the alert for a while.
+    Snooze,
+    /// Suppress the alert until the battery discharges below the threshold.
+    Dismiss,
+    /// Charger was unplugged, battery should be refreshed right away.
+    Unplugged,
+    /// Execute the `--on-action` hook command.
+    Hook,
+}
+
+impl Action {
+    /// Return identifier of the `Action` used by the notification server.
+    pub const fn identifier(self) -> &'static str {
+        match self {
+            Self::Snooze => "snooze",
+            Self::Dismiss => "dismiss",
+            Self::Unplugged => "unplugged",
+            Self::Hook => "hook",
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "snooze" => Ok(Self::Snooze),
+            "dismiss" => Ok(Self::Dismiss),
+            "unplugged" => Ok(Self::Unplugged),
+            "hook" => Ok(Self::Hook),
+            _ => Err(format!("unknown action: {s}")),
+        }
+    }
+}
+
+mod std_fmt_impls {
+    use std::fmt;
+
+    use super::Action;
+
+    impl fmt::Display for Action {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.identifier())
+        }
+    }
+} // std_fmt_impls
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_from_identifier() {
+        let actions = [
+            Action::Snooze,
+            Action::Dismiss,
+            Action::Unplugged,
+            Action::Hook,
+        ];
+
+        for action in actions {
+            let result = Action::from_str(action.identifier());
+
+            assert_eq!(result, Ok(action));
+        }
+    }
+
+    #[test]
+    fn test_action_from_unknown_identifier() {
+        let result = Action::from_str("__closed");
+
+        assert!(result.is_err());
+    }
+} // tests
@@ -0,0 +1,458 @@
+use std::fmt::Display;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Longest time the retries of all channels may wait for in total while
+/// delivering an alert, kept well below the interval of the systemd
+/// watchdog pings so that the blocked event loop doesn't miss them.
+pub const RETRY_BUDGET: Duration = Duration::from_secs(15);
+
+/// Longest duration an open circuit stays open for, longer cooldowns
+/// are shortened to it.
+const MAX_CIRCUIT_COOLDOWN: Duration = Duration::from_hours(24);
+
+/// Notification channel which can fail to deliver an alert.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Channel {
+    Desktop,
+    KDEConnect,
+    Ntfy,
+    Gotify,
+    Mqtt,
+    Hook,
+    Email,
+}
+
+impl Channel {
+    /// All channels in the order alerts are delivered in.
+    pub const ALL: [Self; 7] = [
+        Self::Desktop,
+        Self::KDEConnect,
+        Self::Ntfy,
+        Self::Gotify,
+        Self::Mqtt,
+        Self::Hook,
+        Self::Email,
+    ];
+
+    /// Return identifier of the `Channel` used on the command line.
+    pub const fn identifier(self) -> &'static str {
+        match self {
+            Self::Desktop => "desktop",
+            Self::KDEConnect => "kde-connect",
+            Self::Ntfy => "ntfy",
+            Self::Gotify => "gotify",
+            Self::Mqtt => "mqtt",
+            Self::Hook => "hook",
+            Self::Email => "email",
+        }
+    }
+
+    /// Return whether the `Channel` replaces its alert in place.
+    ///
+    /// Such channels are updated on every change of the alert,
+    /// the others only once the change is material.
+    pub const fn replaces(self) -> bool {
+        matches!(self, Self::Desktop)
+    }
+}
+
+impl FromStr for Channel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|channel| channel.identifier() == s)
+            .ok_or_else(|| {
+                format!(
+                    "unknown channel \"{s}\", expected one of {}",
+                    Self::ALL.map(Self::identifier).join(", ")
+                )
+            })
+    }
+}
+
+/// Channel used when another one fails to deliver an alert.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Fallback {
+    pub channel: Channel,
+    /// Whether the alert is escalated to the critical urgency.
+    pub critical: bool,
+}
+
+impl FromStr for Fallback {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (channel, critical) = s
+            .strip_suffix(":critical")
+            .map_or((s, false), |channel| (channel, true));
+
+        Ok(Self {
+            channel: channel.parse()?,
+            critical,
+        })
+    }
+}
+
+/// Retry and circuit breaker policy of a channel.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Policy {
+    /// Number of retries after a failed attempt.
+    pub retries: u32,
+    /// Delay before the first retry, doubled for every next one.
+    pub backoff: Duration,
+    /// Number of consecutive failed deliveries which open the circuit,
+    /// `0` keeps the circuit always closed.
+    pub circuit_failures: u32,
+    /// Duration after which an open circuit is probed again.
+    pub circuit_cooldown: Duration,
+}
+
+/// State of the circuit breaker of a channel.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Circuit {
+    /// Alerts are delivered.
+    Closed,
+    /// Channel keeps failing, alerts are skipped until the time.
+    Open { until: Instant },
+    /// Cooldown is over, the next delivery is a single probe.
+    HalfOpen,
+}
+
+/// Delivery statistics and circuit breaker of a channel.
+#[derive(Debug)]
+pub struct Health {
+    policy: Policy,
+    circuit: Circuit,
+    /// Number of all delivery attempts including retries.
+    pub attempts: u64,
+    /// Number of all failed delivery attempts.
+    pub failures: u64,
+    /// Number of deliveries in a row which failed even after retries.
+    pub consecutive: u32,
+    /// Time after which failed attempts aren't retried anymore.
+    deadline: Option<Instant>,
+}
+
+impl Health {
+    /// Create a new `Health` instance with a closed circuit.
+    pub const fn new(policy: Policy) -> Self {
+        Self {
+            policy,
+            circuit: Circuit::Closed,
+            attempts: 0,
+            failures: 0,
+            consecutive: 0,
+            deadline: None,
+        }
+    }
+
+    /// Stop retrying failed attempts once the `deadline` passes.
+    pub const fn limit_retries(&mut self, deadline: Instant) {
+        self.deadline = Some(deadline);
+    }
+
+    /// Return the current `Circuit` state.
+    pub fn circuit(&mut self) -> Circuit {
+        if let Circuit::Open { until } = self.circuit {
+            if Instant::now() >= until {
+                self.circuit = Circuit::HalfOpen;
+            }
+        }
+
+        self.circuit
+    }
+
+    /// Deliver an alert via `send`, retrying with exponential backoff.
+    ///
+    /// Deliveries are skipped while the circuit is open and only
+    /// a single attempt is made while it's half-open. Retries which
+    /// would wait past the deadline set by `limit_retries` are given up.
+    ///
+    /// Return whether the alert was delivered.
+    pub fn deliver<E, F>(&mut self, channel: Channel, mut send: F) -> bool
+    where
+        E: Display,
+        F: FnMut() -> Result<(), E>,
+    {
+        let attempts = match self.circuit() {
+            Circuit::Open { .. } => {
+                log::debug!("notification/{channel}: circuit open, skipped");
+
+                return false;
+            }
+            Circuit::HalfOpen => {
+                log::info!("notification/{channel}: re-probing");
+
+                1
+            }
+            Circuit::Closed => self.policy.retries + 1,
+        };
+        let mut backoff = self.policy.backoff;
+
+        for attempt in 1..=attempts {
+            self.attempts += 1;
+
+            match send() {
+                Ok(()) => {
+                    self.succeeded(channel);
+
+                    return true;
+                }
+                Err(e) => {
+                    self.failures += 1;
+
+                    log::warn!(
+                        "notification/{channel}: attempt {attempt}/{attempts} \
+                        failed: {e}, failures = {}",
+                        self.failures,
+                    );
+
+                    if attempt < attempts {
+                        if self.exceeds_deadline(backoff) {
+                            log::warn!(
+                                "notification/{channel}: retry deadline \
+                                reached, remaining retries skipped"
+                            );
+
+                            break;
+                        }
+
+                        thread::sleep(backoff);
+                        backoff = backoff.saturating_mul(2);
+                    }
+                }
+            }
+        }
+
+        self.failed(channel);
+
+        false
+    }
+
+    /// Reset the consecutive failures and close the circuit.
+    fn succeeded(&mut self, channel: Channel) {
+        if self.circuit != Circuit::Closed {
+            log::info!("notification/{channel}: circuit closed");
+        }
+
+        self.circuit = Circuit::Closed;
+        self.consecutive = 0;
+    }
+
+    /// Count the failed delivery and open the circuit if needed.
+    /// Return whether waiting for the `backoff` ends past the deadline.
+    fn exceeds_deadline(&self, backoff: Duration) -> bool {
+        self.deadline.is_some_and(|deadline| {
+            Instant::now()
+                .checked_add(backoff)
+                .is_none_or(|end| end > deadline)
+        })
+    }
+
+    fn failed(&mut self, channel: Channel) {
+        self.consecutive += 1;
+
+        let tripped = self.policy.circuit_failures > 0
+            && self.consecutive >= self.policy.circuit_failures;
+
+        if self.circuit == Circuit::HalfOpen || tripped {
+            let cooldown =
+                self.policy.circuit_cooldown.min(MAX_CIRCUIT_COOLDOWN);
+
+            self.circuit = Circuit::Open {
+                until: Instant::now() + cooldown,
+            };
+
+            log::warn!(
+                "notification/{channel}: circuit opened after {} consecutive \
+                failures, re-probing in {} seconds",
+                self.consecutive,
+                cooldown.as_secs(),
+            );
+        }
+    }
+}
+
+mod std_fmt_impls {
+    use std::fmt;
+
+    use super::{Channel, Circuit, Fallback};
+
+    impl fmt::Display for Channel {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.identifier())
+        }
+    }
+
+    impl fmt::Display for Fallback {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            if self.critical {
+                write!(f, "{}:critical", self.channel)
+            } else {
+                write!(f, "{}", self.channel)
+            }
+        }
+    }
+
+    impl fmt::Display for Circuit {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Closed => write!(f, "closed"),
+                Self::Open { .. } => write!(f, "open"),
+                Self::HalfOpen => write!(f, "half-open"),
+            }
+        }
+    }
+} // std_fmt_impls
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_health(retries: u32, circuit_failures: u32) -> Health {
+        Health::new(Policy {
+            retries,
+            backoff: Duration::ZERO,
+            circuit_failures,
+            circuit_cooldown: Duration::from_mins(1),
+        })
+    }
+
+    #[test]
+    fn test_channel_from_identifier() {
+        for channel in Channel::ALL {
+            let result = Channel::from_str(channel.identifier());
+
+            assert_eq!(result, Ok(channel));
+        }
+    }
+
+    #[test]
+    fn test_fallback_from_str() {
+        let result = Fallback::from_str("desktop:critical");
+
+        assert_eq!(
+            result,
+            Ok(Fallback {
+                channel: Channel::Desktop,
+                critical: true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_fallback_from_str_invalid() {
+        let result = Fallback::from_str("pager:critical");
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_health_deliver_retries() {
+        let mut health = create_health(2, 0);
+        let mut calls = 0;
+
+        let result = health.deliver(Channel::Ntfy, || {
+            calls += 1;
+
+            if calls < 3 {
+                Err("unavailable")
+            } else {
+                Ok(())
+            }
+        });
+
+        assert!(result);
+        assert_eq!(health.attempts, 3);
+        assert_eq!(health.failures, 2);
+        assert_eq!(health.consecutive, 0);
+    }
+
+    #[test]
+    fn test_health_deliver_failed() {
+        let mut health = create_health(1, 0);
+
+        let result = health.deliver(Channel::Ntfy, || Err("unavailable"));
+
+        assert!(!result);
+        assert_eq!(health.attempts, 2);
+        assert_eq!(health.consecutive, 1);
+        assert_eq!(health.circuit(), Circuit::Closed);
+    }
+
+    #[test]
+    fn test_health_deliver_deadline() {
+        let mut health = Health::new(Policy {
+            backoff: Duration::MAX,
+            ..create_health(3, 0).policy
+        });
+        health.limit_retries(Instant::now() + RETRY_BUDGET);
+        let mut calls = 0;
+
+        let result = health.deliver(Channel::Ntfy, || {
+            calls += 1;
+
+            Err("unavailable")
+        });
+
+        assert!(!result);
+        assert_eq!(calls, 1);
+        assert_eq!(health.consecutive, 1);
+    }
+
+    #[test]
+    fn test_health_circuit_opened() {
+        let mut health = create_health(0, 2);
+        let mut calls = 0;
+
+        for _ in 0..3 {
+            health.deliver(Channel::Email, || {
+                calls += 1;
+
+                Err("unavailable")
+            });
+        }
+
+        assert_eq!(calls, 2);
+        assert!(matches!(health.circuit(), Circuit::Open { .. }));
+    }
+
+    #[test]
+    fn test_health_circuit_opened_long_cooldown() {
+        let mut health = Health::new(Policy {
+            circuit_cooldown: Duration::MAX,
+            ..create_health(0, 1).policy
+        });
+
+        let result = health.deliver(Channel::Email, || Err("unavailable"));
+
+        assert!(!result);
+        assert!(matches!(health.circuit(), Circuit::Open { .. }));
+    }
+
+    #[test]
+    fn test_health_circuit_reprobed() {
+        let mut health = create_health(3, 1);
+        health.circuit = Circuit::Open {
+            until: Instant::now(),
+        };
+        let mut calls = 0;
+
+        let failed = health.deliver(Channel::Email, || {
+            calls += 1;
+
+            Err("unavailable")
+        });
+        health.circuit = Circuit::HalfOpen;
+        let delivered = health.deliver(Channel::Email, || Ok::<(), &str>(()));
+
+        assert!(!failed);
+        assert_eq!(calls, 1);
+        assert!(delivered);
+        assert_eq!(health.circuit(), Circuit::Closed);
+    }
+} // tests
@@ -1,30 +1,154 @@
-use std::result;
+use std::sync::mpsc;
+use std::{result, thread};
 
-use notify_rust::{Notification, NotificationHandle, Timeout, Urgency};
+use notify_rust::{
+    ActionResponse, CloseReason, Hint, Notification, NotificationHandle,
+    Timeout, Urgency,
+};
+use zbus::blocking::{Connection, Proxy, ProxyBuilder};
+use zbus::CacheProperties;
 
-use crate::error;
-use crate::notification::{Message, PlatformNotifier};
+use crate::device::BatteryState;
+use crate::event::Event;
+use crate::notification::{self, Action, Message, PlatformNotifier};
+use crate::{cli, common, error, locale};
 
 const APP_NAME: &str = "rusty-battery";
-const ICON: &str = "battery";
+
+/// Icon name which selects an icon based on the battery percentage.
+const DYNAMIC_ICON: &str = "auto";
+
+/// Name of the hint which shows a progress bar on the notification.
+const PROGRESS_HINT: &str = "value";
+
+/// Server capabilities which the notifier adapts to.
+const ACTIONS_CAPABILITY: &str = "actions";
+const BODY_MARKUP_CAPABILITY: &str = "body-markup";
+const PERSISTENCE_CAPABILITY: &str = "persistence";
+
+/// Well-known name and interface of the notification server.
+const SERVER: &str = "org.freedesktop.Notifications";
+/// Object path of the notification server.
+const SERVER_PATH: &str = "/org/freedesktop/Notifications";
+/// Property of the notification server reporting do-not-disturb mode.
+const INHIBITED_PROPERTY: &str = "Inhibited";
 
 type Result<T> = result::Result<T, error::Notification>;
 
 #[derive(Debug)]
 pub struct Notifier {
     handle: Option<NotificationHandle>,
+    /// Action buttons shown on the notification with their labels.
+    actions: Vec<(Action, String)>,
+    /// Sender of the invoked actions.
+    sender: mpsc::Sender<Event>,
+    appearance: Appearance,
+
+    /// Sender of notification ids whose listening thread terminated,
+    /// along with the reason if the server closed the notification.
+    finished_sender: mpsc::Sender<(u32, Option<CloseReason>)>,
+    finished_receiver: mpsc::Receiver<(u32, Option<CloseReason>)>,
+    /// Whether the user closed the notification of the current alert.
+    dismissed: bool,
+    /// Session bus connection used to query the do-not-disturb mode,
+    /// established on the first query.
+    connection: Option<Connection>,
+}
+
+/// Optional features supported by the notification server.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Capabilities {
+    /// Whether action buttons are shown.
+    actions: bool,
+    /// Whether the body is interpreted as markup.
+    body_markup: bool,
+    /// Whether notifications are kept until the user acknowledges them.
+    persistence: bool,
+}
+
+/// How the desktop notification looks and behaves.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[allow(clippy::struct_excessive_bools)]
+struct Appearance {
+    /// Urgency overriding the urgency of the `Message`.
+    urgency: Option<notification::Urgency>,
+    icon: Icon,
+    timeout: Timeout,
+    category: Option<String>,
+    sound: Option<Sound>,
+    transient: bool,
+    resident: bool,
+    /// Whether the battery percentage is shown as a progress bar.
+    progress: bool,
+    /// Whether the body has to be escaped because the server
+    /// interprets it as markup.
+    body_markup: bool,
+}
+
+/// Icon of the desktop notification.
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Icon {
+    /// Icon name or `file://` URI.
+    Static(String),
+    /// Icon name chosen by the battery percentage and state,
+    /// such as `battery-080-charging`.
+    Dynamic,
+}
+
+/// Sound played when the desktop notification is shown.
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Sound {
+    /// Path to a sound file.
+    File(String),
+    /// Name of a themeable sound.
+    Name(String),
 }
 
 impl PlatformNotifier for Notifier {
     type Error = error::Notification;
 
     fn notify(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        self.process_finished();
+
+        if self.dismissed {
+            log::trace!("notification/desktop: dismissed by the user");
+
+            return Ok(());
+        }
+
+        self.show(message)?;
+
+        Ok(())
+    }
+
+    /// Show the notification again if it expired or was closed
+    /// other than by the user.
+    fn repeat(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        self.process_finished();
+
+        if self.dismissed || self.handle.is_some() {
+            return Ok(());
+        }
+
         self.show(message)?;
 
+        log::debug!("notification/desktop: notification shown again");
+
+        Ok(())
+    }
+
+    fn test(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        // shown separately, so the alert notification stays untouched
+        create_notification(message, &[], &self.appearance).show()?;
+
+        log::debug!("notification/desktop: test notification shown");
+
         Ok(())
     }
 
     fn remove(&mut self) -> result::Result<(), Self::Error> {
+        self.process_finished();
+        self.dismissed = false;
         self.close();
 
         Ok(())
@@ -33,8 +157,124 @@ impl PlatformNotifier for Notifier {
 
 impl Notifier {
     /// Return a new `DesktopNotifier` instance.
-    pub const fn new() -> Self {
-        Self { handle: None }
+    ///
+    /// Actions invoked by the user are sent via the given `Sender`.
+    ///
+    /// If `hook_label` is present, an action button with this label
+    /// executes the action hook.
+    ///
+    /// Capabilities of the notification server are queried right away.
+    pub fn new(
+        options: &cli::Desktop,
+        hook_label: Option<&str>,
+        sender: mpsc::Sender<Event>,
+    ) -> Self {
+        Self::with_capabilities(
+            options,
+            hook_label,
+            sender,
+            Capabilities::query(),
+        )
+    }
+
+    /// Return a new `DesktopNotifier` instance adapted to the given
+    /// `Capabilities` of the notification server.
+    fn with_capabilities(
+        options: &cli::Desktop,
+        hook_label: Option<&str>,
+        sender: mpsc::Sender<Event>,
+        capabilities: Capabilities,
+    ) -> Self {
+        let actions = if options.disable_actions {
+            vec![]
+        } else if !capabilities.actions {
+            log::info!(
+                "notification/desktop: server doesn't support actions, \
+                action buttons disabled"
+            );
+
+            vec![]
+        } else {
+            let mut actions = vec![
+                (
+                    Action::Snooze,
+                    locale::format(
+                        "action-snooze",
+                        &[("minutes", options.snooze_mins.into())],
+                    ),
+                ),
+                (Action::Dismiss, locale::text("action-dismiss")),
+                (Action::Unplugged, locale::text("action-unplugged")),
+            ];
+
+            if let Some(label) = hook_label {
+                actions.push((Action::Hook, label.into()));
+            }
+
+            actions
+        };
+
+        if !capabilities.persistence {
+            log::info!(
+                "notification/desktop: server doesn't support persistence, \
+                notifications are shown again once they expire"
+            );
+        }
+
+        let (finished_sender, finished_receiver) = mpsc::channel();
+
+        Self {
+            handle: None,
+            actions,
+            sender,
+            appearance: Appearance {
+                body_markup: capabilities.body_markup,
+                ..Appearance::from(options)
+            },
+            finished_sender,
+            finished_receiver,
+            dismissed: false,
+            connection: None,
+        }
+    }
+
+    /// Return whether the notification server is in do-not-disturb mode.
+    ///
+    /// Servers which don't expose the `Inhibited` property never are.
+    pub fn inhibited(&mut self) -> bool {
+        if self.connection.is_none() {
+            match Connection::session() {
+                Ok(connection) => self.connection = Some(connection),
+                Err(e) => {
+                    log::debug!("notification/desktop: session bus: {e}");
+
+                    return false;
+                }
+            }
+        }
+
+        let inhibited = self.connection.as_ref().map_or(Ok(false), |c| {
+            ProxyBuilder::<Proxy>::new_bare(c)
+                .destination(SERVER)?
+                .path(SERVER_PATH)?
+                .interface(SERVER)?
+                .cache_properties(CacheProperties::No)
+                .build()?
+                .get_property::<bool>(INHIBITED_PROPERTY)
+        });
+
+        match inhibited {
+            Ok(inhibited) => {
+                log::trace!("notification/desktop: inhibited = {inhibited}");
+
+                inhibited
+            }
+            Err(e) => {
+                log::trace!("notification/desktop: inhibited: {e}");
+
+                false
+            }
+        }
     }
 
     /// Show a desktop notification that the battery threshold has been reached.
@@ -49,16 +289,19 @@ impl Notifier {
     /// Return a reference to the current `NotificationHandle`.
     fn show(&mut self, message: &Message) -> Result<&NotificationHandle> {
         if let Some(handle) = &mut self.handle {
-            handle.summary(&message.summary);
-            handle.body(&message.body);
+            **handle =
+                create_notification(message, &self.actions, &self.appearance);
 
             handle.update();
 
             log::debug!("notification/desktop: cached notification shown");
         } else {
-            self.handle = Some(
-                create_notification(&message.summary, &message.body).show()?,
-            );
+            let handle =
+                create_notification(message, &self.actions, &self.appearance)
+                    .show()?;
+
+            self.listen(handle.id());
+            self.handle = Some(handle);
 
             log::debug!("notification/desktop: notification shown and cached");
         }
@@ -90,25 +333,277 @@ impl Notifier {
             },
         )
     }
+
+    /// Drop the cached handle once the thread listening on its
+    /// notification terminated.
+    ///
+    /// Actions invoked on the notification and its closing wouldn't be
+    /// noticed anymore, so a notification still shown after an action
+    /// is closed and the next one is created anew. If the user closed
+    /// the notification, it isn't shown again until the alert is removed.
+    fn process_finished(&mut self) {
+        while let Ok((id, reason)) = self.finished_receiver.try_recv() {
+            if self.handle.as_ref().map(NotificationHandle::id) != Some(id) {
+                continue;
+            }
+
+            let Some(reason) = reason else {
+                self.close();
+
+                log::debug!(
+                    "notification/desktop: action invoked, listener finished"
+                );
+
+                continue;
+            };
+
+            self.handle = None;
+
+            if matches!(reason, CloseReason::Dismissed) {
+                self.dismissed = true;
+
+                log::info!(
+                    "notification/desktop: notification closed by the user"
+                );
+            } else {
+                log::debug!(
+                    "notification/desktop: notification closed: {reason:?}"
+                );
+            }
+        }
+    }
+
+    /// Forward actions invoked on the notification with the given id
+    /// and report when it gets closed.
+    ///
+    /// Works like `NotificationHandle::wait_for_action` on a background
+    /// thread, but the handle stays cached so that the notification can
+    /// still be updated and closed. The thread terminates once an action
+    /// is invoked or the notification is closed, which is reported
+    /// to `process_finished`.
+    fn listen(&self, id: u32) {
+        let sender = self.sender.clone();
+        let finished_sender = self.finished_sender.clone();
+
+        thread::spawn(move || {
+            notify_rust::handle_action(id, |response: &ActionResponse| {
+                match response {
+                    ActionResponse::Custom(identifier) => {
+                        match identifier.parse() {
+                            Ok(action) => {
+                                common::warn_on_err(
+                                    "notification/desktop",
+                                    sender.send(Event::Action(action)),
+                                );
+                            }
+                            Err(e) => log::debug!("notification/desktop: {e}"),
+                        }
+
+                        // the receiver is gone only when the process exits
+                        finished_sender.send((id, None)).ok();
+                    }
+                    ActionResponse::Closed(reason) => {
+                        finished_sender.send((id, Some(*reason))).ok();
+                    }
+                }
+            });
+        });
+    }
 }
 
-/// Create a new desktop notification with the given summary and body.
-fn create_notification(summary: &str, body: &str) -> Notification {
+impl Capabilities {
+    /// Query capabilities and information of the notification server.
+    ///
+    /// If the server can't be queried, everything except body markup
+    /// is expected to be supported.
+    fn query() -> Self {
+        match notify_rust::get_server_information() {
+            Ok(info) => log::info!(
+                "notification/desktop: server {} {} by {}, \
+                specification version {}",
+                info.name,
+                info.version,
+                info.vendor,
+                info.spec_version,
+            ),
+            Err(e) => {
+                log::warn!("notification/desktop: server information: {e}");
+            }
+        }
+
+        notify_rust::get_capabilities().map_or_else(
+            |e| {
+                log::warn!("notification/desktop: capabilities: {e}");
+
+                Self::default()
+            },
+            |capabilities| {
+                log::debug!(
+                    "notification/desktop: capabilities = {}",
+                    capabilities.join(", "),
+                );
+
+                Self::from(capabilities.as_slice())
+            },
+        )
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            actions: true,
+            body_markup: false,
+            persistence: true,
+        }
+    }
+}
+
+impl From<&[String]> for Capabilities {
+    fn from(capabilities: &[String]) -> Self {
+        let supports = |capability: &str| {
+            capabilities.iter().any(|supported| supported == capability)
+        };
+
+        Self {
+            actions: supports(ACTIONS_CAPABILITY),
+            body_markup: supports(BODY_MARKUP_CAPABILITY),
+            persistence: supports(PERSISTENCE_CAPABILITY),
+        }
+    }
+}
+
+impl Icon {
+    /// Return icon name or URI for the given battery percentage and state.
+    fn name(&self, percentage: u8, state: BatteryState) -> String {
+        match self {
+            Self::Static(icon) => icon.clone(),
+            Self::Dynamic => {
+                let level = u16::from(percentage.min(100)) / 10 * 10;
+                let suffix = if state == BatteryState::Charging {
+                    "-charging"
+                } else {
+                    ""
+                };
+
+                format!("battery-{level:03}{suffix}")
+            }
+        }
+    }
+}
+
+impl From<&cli::Desktop> for Appearance {
+    fn from(options: &cli::Desktop) -> Self {
+        let icon = match options.icon.as_str() {
+            DYNAMIC_ICON => Icon::Dynamic,
+            path if path.starts_with('/') => {
+                Icon::Static(format!("file://{path}"))
+            }
+            name => Icon::Static(name.into()),
+        };
+
+        let sound = options.sound.as_ref().map(|sound| {
+            if sound.contains('/') {
+                Sound::File(sound.clone())
+            } else {
+                Sound::Name(sound.clone())
+            }
+        });
+
+        Self {
+            urgency: options.urgency,
+            icon,
+            timeout: options.timeout_secs.map_or(Timeout::Never, |secs| {
+                Timeout::Milliseconds(secs.saturating_mul(1000))
+            }),
+            category: options.category.clone(),
+            sound,
+            transient: options.transient,
+            resident: options.resident,
+            progress: options.progress,
+            body_markup: false,
+        }
+    }
+}
+
+/// Return the given text with markup characters escaped.
+fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Map `notification::Urgency` to the desktop notification urgency.
+const fn urgency(urgency: notification::Urgency) -> Urgency {
+    match urgency {
+        notification::Urgency::Low => Urgency::Low,
+        notification::Urgency::Normal => Urgency::Normal,
+        notification::Urgency::Critical => Urgency::Critical,
+    }
+}
+
+/// Create a new desktop notification of the given `Message`
+/// with the action buttons and `Appearance`.
+fn create_notification(
+    message: &Message,
+    actions: &[(Action, String)],
+    appearance: &Appearance,
+) -> Notification {
     log::trace!(
         "notification/desktop: creating notification with \
         summary = \"{}\" and body = \"{}\"",
-        summary,
-        body,
+        message.summary,
+        message.body,
     );
 
-    Notification::new()
+    let mut notification = Notification::new();
+
+    notification
         .appname(APP_NAME)
-        .summary(summary)
-        .body(body)
-        .icon(ICON)
-        .timeout(Timeout::Never)
-        .urgency(Urgency::Critical)
-        .finalize()
+        .summary(&message.summary)
+        .body(&if appearance.body_markup {
+            escape_markup(&message.body)
+        } else {
+            message.body.clone()
+        })
+        .icon(&appearance.icon.name(message.percentage, message.state))
+        .timeout(appearance.timeout)
+        .urgency(urgency(appearance.urgency.unwrap_or(message.urgency)));
+
+    if let Some(category) = &appearance.category {
+        notification.hint(Hint::Category(category.clone()));
+    }
+
+    match &appearance.sound {
+        Some(Sound::File(path)) => {
+            notification.hint(Hint::SoundFile(path.clone()));
+        }
+        Some(Sound::Name(name)) => {
+            notification.hint(Hint::SoundName(name.clone()));
+        }
+        None => {}
+    }
+
+    if appearance.transient {
+        notification.hint(Hint::Transient(true));
+    }
+
+    if appearance.resident {
+        notification.hint(Hint::Resident(true));
+    }
+
+    if appearance.progress {
+        notification.hint(Hint::CustomInt(
+            PROGRESS_HINT.into(),
+            i32::from(message.percentage),
+        ));
+    }
+
+    for (action, label) in actions {
+        notification.action(action.identifier(), label);
+    }
+
+    notification.finalize()
 }
 
 mod std_fmt_impls {
@@ -141,45 +636,408 @@ mod tests {
 
     use super::*;
 
+    fn create_options() -> cli::Desktop {
+        cli::Desktop {
+            disable_actions: false,
+            snooze_mins: 15,
+            urgency: None,
+            icon: "battery".into(),
+            timeout_secs: None,
+            category: None,
+            sound: None,
+            transient: false,
+            resident: false,
+            progress: false,
+        }
+    }
+
+    fn create_notifier(
+        disable_actions: bool,
+        hook_label: Option<&str>,
+    ) -> Notifier {
+        Notifier::with_capabilities(
+            &cli::Desktop {
+                disable_actions,
+                ..create_options()
+            },
+            hook_label,
+            mpsc::channel().0,
+            Capabilities::default(),
+        )
+    }
+
     fn assert_notification(
         notification: &Notification,
         summary: &str,
         body: &str,
+        icon: &str,
+        timeout: Timeout,
+        hints: &[Hint],
+        actions: &[&str],
     ) {
-        let mut hints = HashSet::new();
-        hints.insert(Hint::Urgency(Urgency::Critical));
+        let (custom_hints, hints): (Vec<_>, Vec<_>) = hints
+            .iter()
+            .cloned()
+            .partition(|hint| matches!(hint, Hint::CustomInt(..)));
+        let debug = format!("{notification:?}");
 
         assert_eq!(notification.appname, APP_NAME);
         assert_eq!(notification.summary, summary);
         assert_eq!(notification.body, body);
-        assert_eq!(notification.icon, ICON);
-        assert_eq!(notification.timeout, Timeout::Never);
-        assert_eq!(notification.hints, hints);
+        assert_eq!(notification.icon, icon);
+        assert_eq!(notification.timeout, timeout);
+        assert_eq!(
+            notification.hints,
+            hints.into_iter().collect::<HashSet<_>>()
+        );
+        for hint in custom_hints {
+            assert!(debug.contains(&format!("{hint:?}")));
+        }
+        assert_eq!(notification.actions, actions);
     }
 
     #[test]
     fn test_notifier_empty_handle() {
-        let notifier = Notifier::new();
+        let notifier = create_notifier(false, None);
 
         assert!(notifier.handle.is_none());
     }
 
     #[test]
     fn test_notifier_display_none_handle() {
-        let notifier = Notifier::new();
+        let notifier = create_notifier(false, None);
 
         let result = notifier.to_string();
 
         assert_eq!(result, "Desktop Notifier: handle = None");
     }
 
+    #[test]
+    fn test_notifier_actions() {
+        let notifier = create_notifier(false, Some("test-label"));
+
+        let result: Vec<_> =
+            notifier.actions.iter().map(|(action, _)| *action).collect();
+
+        assert_eq!(
+            result,
+            [
+                Action::Snooze,
+                Action::Dismiss,
+                Action::Unplugged,
+                Action::Hook
+            ]
+        );
+        assert_eq!(notifier.actions[0].1, "Snooze 15 min");
+        assert_eq!(notifier.actions[3].1, "test-label");
+    }
+
+    #[test]
+    fn test_notifier_actions_disabled() {
+        let notifier = create_notifier(true, Some("test-label"));
+
+        assert!(notifier.actions.is_empty());
+    }
+
+    #[test]
+    fn test_notifier_actions_unsupported() {
+        let notifier = Notifier::with_capabilities(
+            &create_options(),
+            Some("test-label"),
+            mpsc::channel().0,
+            Capabilities {
+                actions: false,
+                ..Capabilities::default()
+            },
+        );
+
+        assert!(notifier.actions.is_empty());
+    }
+
+    #[test]
+    fn test_notifier_notify_dismissed() {
+        let mut notifier = create_notifier(false, None);
+        notifier.dismissed = true;
+        let message = Message::test(
+            "test-summary",
+            "test-body",
+            notification::Urgency::Critical,
+        );
+
+        let result = notifier.notify(&message);
+
+        assert!(result.is_ok());
+        assert!(notifier.handle.is_none());
+    }
+
+    #[test]
+    fn test_notifier_repeat_dismissed() {
+        let mut notifier = create_notifier(false, None);
+        notifier.dismissed = true;
+        let message = Message::test(
+            "test-summary",
+            "test-body",
+            notification::Urgency::Critical,
+        );
+
+        let result = notifier.repeat(&message);
+
+        assert!(result.is_ok());
+        assert!(notifier.handle.is_none());
+    }
+
+    #[test]
+    fn test_notifier_remove_dismissed() {
+        let mut notifier = create_notifier(false, None);
+        notifier.dismissed = true;
+
+        let result = notifier.remove();
+
+        assert!(result.is_ok());
+        assert!(!notifier.dismissed);
+    }
+
+    #[test]
+    fn test_notifier_process_finished_other_notification() {
+        let mut notifier = create_notifier(false, None);
+        notifier
+            .finished_sender
+            .send((42, Some(CloseReason::Dismissed)))
+            .unwrap();
+
+        notifier.process_finished();
+
+        assert!(!notifier.dismissed);
+    }
+
+    #[test]
+    fn test_capabilities_from_list() {
+        let capabilities: Vec<String> =
+            vec!["body".into(), "body-markup".into(), "actions".into()];
+
+        let result = Capabilities::from(capabilities.as_slice());
+
+        assert_eq!(
+            result,
+            Capabilities {
+                actions: true,
+                body_markup: true,
+                persistence: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_escape_markup() {
+        let result = escape_markup("<b>Tom & Jerry</b>");
+
+        assert_eq!(result, "&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;");
+    }
+
+    #[test]
+    fn test_icon_static() {
+        let icon = Icon::Static("battery".into());
+
+        let result = icon.name(85, BatteryState::Charging);
+
+        assert_eq!(result, "battery");
+    }
+
+    #[test]
+    fn test_icon_dynamic_charging() {
+        let icon = Icon::Dynamic;
+
+        let result = icon.name(85, BatteryState::Charging);
+
+        assert_eq!(result, "battery-080-charging");
+    }
+
+    #[test]
+    fn test_icon_dynamic_discharging() {
+        let icon = Icon::Dynamic;
+
+        let result = icon.name(100, BatteryState::Discharging);
+
+        assert_eq!(result, "battery-100");
+    }
+
+    #[test]
+    fn test_appearance_from_options() {
+        let options = cli::Desktop {
+            urgency: Some(notification::Urgency::Low),
+            icon: "/usr/share/icons/battery.svg".into(),
+            timeout_secs: Some(5),
+            category: Some("device".into()),
+            sound: Some("/usr/share/sounds/alert.oga".into()),
+            transient: true,
+            ..create_options()
+        };
+
+        let result = Appearance::from(&options);
+
+        assert_eq!(
+            result,
+            Appearance {
+                urgency: Some(notification::Urgency::Low),
+                icon: Icon::Static(
+                    "file:///usr/share/icons/battery.svg".into()
+                ),
+                timeout: Timeout::Milliseconds(5000),
+                category: Some("device".into()),
+                sound: Some(Sound::File("/usr/share/sounds/alert.oga".into())),
+                transient: true,
+                resident: false,
+                progress: false,
+                body_markup: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_appearance_from_options_dynamic_icon_sound_name() {
+        let options = cli::Desktop {
+            icon: DYNAMIC_ICON.into(),
+            sound: Some("bell".into()),
+            ..create_options()
+        };
+
+        let result = Appearance::from(&options);
+
+        assert_eq!(result.icon, Icon::Dynamic);
+        assert_eq!(result.sound, Some(Sound::Name("bell".into())));
+    }
+
     #[test]
     fn test_create_notification() {
-        let summary = "test-summary";
-        let body = "test-body";
+        let message = Message::test(
+            "test-summary",
+            "test-body",
+            notification::Urgency::Critical,
+        );
+
+        let notification = create_notification(
+            &message,
+            &[],
+            &Appearance::from(&create_options()),
+        );
+
+        assert_notification(
+            &notification,
+            "test-summary",
+            "test-body",
+            "battery",
+            Timeout::Never,
+            &[Hint::Urgency(Urgency::Critical)],
+            &[],
+        );
+    }
+
+    #[test]
+    fn test_create_notification_actions() {
+        let message = Message::test(
+            "test-summary",
+            "test-body",
+            notification::Urgency::Critical,
+        );
+        let actions = [
+            (Action::Snooze, "Snooze".to_owned()),
+            (Action::Unplugged, "Unplugged".to_owned()),
+        ];
+
+        let notification = create_notification(
+            &message,
+            &actions,
+            &Appearance::from(&create_options()),
+        );
+
+        assert_notification(
+            &notification,
+            "test-summary",
+            "test-body",
+            "battery",
+            Timeout::Never,
+            &[Hint::Urgency(Urgency::Critical)],
+            &["snooze", "Snooze", "unplugged", "Unplugged"],
+        );
+    }
+
+    #[test]
+    fn test_create_notification_appearance() {
+        let message = Message::test(
+            "test-summary",
+            "test-body",
+            notification::Urgency::Critical,
+        );
+        let options = cli::Desktop {
+            urgency: Some(notification::Urgency::Normal),
+            icon: DYNAMIC_ICON.into(),
+            timeout_secs: Some(10),
+            category: Some("device".into()),
+            sound: Some("bell".into()),
+            resident: true,
+            progress: true,
+            ..create_options()
+        };
+
+        let notification =
+            create_notification(&message, &[], &Appearance::from(&options));
+
+        assert_notification(
+            &notification,
+            "test-summary",
+            "test-body",
+            "battery-080-charging",
+            Timeout::Milliseconds(10_000),
+            &[
+                Hint::Urgency(Urgency::Normal),
+                Hint::Category("device".into()),
+                Hint::SoundName("bell".into()),
+                Hint::Resident(true),
+                Hint::CustomInt(PROGRESS_HINT.into(), 85),
+            ],
+            &[],
+        );
+    }
+
+    #[test]
+    fn test_create_notification_body_markup() {
+        let message = Message::test(
+            "test-summary",
+            "80% <threshold>",
+            notification::Urgency::Critical,
+        );
+        let appearance = Appearance {
+            body_markup: true,
+            ..Appearance::from(&create_options())
+        };
+
+        let notification = create_notification(&message, &[], &appearance);
+
+        assert_notification(
+            &notification,
+            "test-summary",
+            "80% &lt;threshold&gt;",
+            "battery",
+            Timeout::Never,
+            &[Hint::Urgency(Urgency::Critical)],
+            &[],
+        );
+    }
+
+    #[test]
+    fn test_create_notification_message_urgency() {
+        let message = Message::test(
+            "test-summary",
+            "test-body",
+            notification::Urgency::Low,
+        );
 
-        let notification = create_notification(summary, body);
+        let notification = create_notification(
+            &message,
+            &[],
+            &Appearance::from(&create_options()),
+        );
 
-        assert_notification(&notification, summary, body);
+        assert!(notification.hints.contains(&Hint::Urgency(Urgency::Low)));
     }
 } // tests
@@ -0,0 +1,364 @@
+use std::result;
+use std::time::{Duration, Instant};
+
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{SmtpTransport, Transport};
+
+use crate::cli;
+use crate::error;
+use crate::locale;
+use crate::notification::{message, Message, PlatformNotifier};
+
+type Result<T> = result::Result<T, error::Email>;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How the connection to the SMTP server is secured.
+#[derive(clap::ValueEnum, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Security {
+    /// Implicit TLS, usually on port 465.
+    Tls,
+    /// Upgrade a plain connection with STARTTLS, usually on port 587.
+    Starttls,
+    /// Plain connection without any encryption.
+    None,
+}
+
+/// Email Notifier.
+///
+/// Messages are sent via an SMTP server, at most once per `interval`.
+pub struct Notifier {
+    /// Host of the SMTP server.
+    server: String,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+    /// Minimum time between two alert emails.
+    interval: Duration,
+    /// Whether an email should be sent when the alert is removed.
+    resolved: bool,
+    /// Summary of the last sent alert which hasn't been removed yet.
+    active: Option<String>,
+    /// Time the last alert email was sent at.
+    last_sent: Option<Instant>,
+
+    transport: SmtpTransport,
+}
+
+impl PlatformNotifier for Notifier {
+    type Error = error::Notification;
+
+    fn notify(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        if let Some(last_sent) = self.last_sent {
+            if last_sent.elapsed() < self.interval {
+                log::trace!("notification/email: rate limited");
+
+                return Ok(());
+            }
+        }
+
+        self.send(&message.summary, &body(message))?;
+
+        self.active = Some(message.summary.clone());
+        self.last_sent = Some(Instant::now());
+
+        Ok(())
+    }
+
+    fn test(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        self.send(&message.summary, &body(message))?;
+
+        Ok(())
+    }
+
+    fn remove(&mut self) -> result::Result<(), Self::Error> {
+        match self.active.take() {
+            Some(summary) if self.resolved => {
+                self.send(
+                    &message::resolved_summary(&summary),
+                    &message::resolved_body(),
+                )?;
+
+                log::debug!("notification/email: resolved message sent");
+            }
+            _ => log::trace!("notification/email: remove noop"),
+        }
+
+        Ok(())
+    }
+}
+
+impl Notifier {
+    /// Create a new `Notifier` instance from the given options.
+    ///
+    /// Return `None` if no SMTP server was specified.
+    pub fn new(options: cli::Email) -> Result<Option<Self>> {
+        let cli::Email {
+            server,
+            port,
+            security,
+            username,
+            password,
+            from,
+            to,
+            interval_secs,
+            resolved,
+        } = options;
+
+        let Some(server) = server else {
+            return Ok(None);
+        };
+
+        let mut builder = match security {
+            Security::Tls => SmtpTransport::relay(&server)?,
+            Security::Starttls => SmtpTransport::starttls_relay(&server)?,
+            Security::None => SmtpTransport::builder_dangerous(&server),
+        };
+
+        if let Some(port) = port {
+            builder = builder.port(port);
+        }
+
+        if let Some(username) = username {
+            builder = builder.credentials(Credentials::new(
+                username,
+                password.unwrap_or_default(),
+            ));
+        }
+
+        let from = from.unwrap_or_default().parse()?;
+        let to = to
+            .iter()
+            .map(|address| address.parse())
+            .collect::<result::Result<_, _>>()?;
+
+        Ok(Some(Self {
+            server,
+            from,
+            to,
+            interval: Duration::from_secs(interval_secs),
+            resolved,
+            active: None,
+            last_sent: None,
+            transport: builder.timeout(Some(TIMEOUT)).build(),
+        }))
+    }
+
+    /// Send an email with the given contents to all recipients.
+    fn send(&self, subject: &str, body: &str) -> Result<()> {
+        let mut builder = lettre::Message::builder()
+            .from(self.from.clone())
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN);
+
+        for to in &self.to {
+            builder = builder.to(to.clone());
+        }
+
+        self.transport.send(&builder.body(body.to_owned())?)?;
+
+        log::debug!(
+            "notification/email: sent to {} recipient(s)",
+            self.to.len(),
+        );
+
+        Ok(())
+    }
+}
+
+/// Return email body of the given `Message` with the battery details.
+fn body(message: &Message) -> String {
+    format!(
+        "{}\n\n{}\n{}\n{}\n{}\n",
+        message.body,
+        locale::format(
+            "email-percentage",
+            &[("percentage", message.percentage.into())]
+        ),
+        locale::format(
+            "email-state",
+            &[("state", message.state.localized().into())]
+        ),
+        locale::format(
+            "email-model",
+            &[("model", message.model.as_str().into())]
+        ),
+        locale::format(
+            "email-threshold",
+            &[("threshold", message.threshold.into())]
+        ),
+    )
+}
+
+mod std_fmt_impls {
+    use std::fmt;
+
+    use super::{Notifier, Security};
+
+    impl fmt::Debug for Notifier {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Notifier")
+                .field("server", &self.server)
+                .field("from", &self.from)
+                .field("to", &self.to)
+                .field("interval", &self.interval)
+                .field("resolved", &self.resolved)
+                .field("active", &self.active)
+                .field("last_sent", &self.last_sent)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl fmt::Display for Notifier {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "Email Notifier: server = {}, recipients = {}",
+                self.server,
+                self.to.len(),
+            )
+        }
+    }
+
+    impl fmt::Display for Security {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Tls => write!(f, "TLS"),
+                Self::Starttls => write!(f, "STARTTLS"),
+                Self::None => write!(f, "None"),
+            }
+        }
+    }
+} // std_fmt_impls
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::notification::Urgency;
+    use crate::testing;
+
+    use super::*;
+
+    fn create_options(port: u16, interval_secs: u64) -> cli::Email {
+        cli::Email {
+            server: Some("127.0.0.1".into()),
+            port: Some(port),
+            security: Security::None,
+            username: Some("test-user".into()),
+            password: Some("test-password".into()),
+            from: Some("rusty-battery <battery@example.com>".into()),
+            to: vec!["a@example.com".into(), "b@example.com".into()],
+            interval_secs,
+            resolved: true,
+        }
+    }
+
+    #[test]
+    fn test_notifier_new_without_server() {
+        let mut options = create_options(25, 0);
+        options.server = None;
+
+        let notifier = Notifier::new(options).unwrap();
+
+        assert!(notifier.is_none());
+    }
+
+    #[test]
+    fn test_notifier_new_invalid_address() {
+        let mut options = create_options(25, 0);
+        options.to = vec!["not an address".into()];
+
+        let result = Notifier::new(options);
+
+        assert!(matches!(result, Err(error::Email::Address(_))));
+    }
+
+    #[test]
+    fn test_notifier_display() {
+        let notifier = Notifier::new(create_options(25, 0)).unwrap().unwrap();
+
+        let result = notifier.to_string();
+
+        assert_eq!(
+            result,
+            "Email Notifier: server = 127.0.0.1, recipients = 2"
+        );
+    }
+
+    #[test]
+    fn test_notifier_notify() {
+        let (port, sessions) = testing::smtp_server();
+        let mut notifier =
+            Notifier::new(create_options(port, 0)).unwrap().unwrap();
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        let result = notifier.notify(&message);
+
+        assert!(result.is_ok());
+        let session = sessions.recv().unwrap();
+        assert!(session.contains("AUTH PLAIN"));
+        assert!(session.contains("MAIL FROM:<battery@example.com>"));
+        assert!(session.contains("RCPT TO:<a@example.com>"));
+        assert!(session.contains("RCPT TO:<b@example.com>"));
+        assert!(session.contains("Subject: summary"));
+        assert!(session.contains("body\r\n\r\nPercentage: 85%\r\n"));
+        assert!(session.contains("State: Charging"));
+    }
+
+    #[test]
+    fn test_notifier_notify_rate_limited() {
+        let (port, sessions) = testing::smtp_server();
+        let mut notifier =
+            Notifier::new(create_options(port, 3600)).unwrap().unwrap();
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        notifier.notify(&message).unwrap();
+        let result = notifier.notify(&message);
+
+        assert!(result.is_ok());
+        assert!(sessions.recv().is_ok());
+        assert!(sessions.recv_timeout(Duration::from_millis(200)).is_err());
+    }
+
+    #[test]
+    fn test_notifier_notify_error() {
+        let mut notifier =
+            Notifier::new(create_options(9, 0)).unwrap().unwrap();
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        let result = notifier.notify(&message);
+
+        assert!(matches!(
+            result,
+            Err(error::Notification::Email(error::Email::Smtp(_)))
+        ));
+        assert!(notifier.last_sent.is_none());
+    }
+
+    #[test]
+    fn test_notifier_remove_resolved() {
+        let (port, sessions) = testing::smtp_server();
+        let mut notifier =
+            Notifier::new(create_options(port, 3600)).unwrap().unwrap();
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        notifier.notify(&message).unwrap();
+        let result = notifier.remove();
+
+        assert!(result.is_ok());
+        assert!(sessions.recv().is_ok());
+        let session = sessions.recv().unwrap();
+        assert!(session.contains("Subject: Resolved: summary"));
+    }
+
+    #[test]
+    fn test_security_display() {
+        let security = Security::Starttls;
+
+        let display = format!("{security}");
+
+        assert_eq!(display, "STARTTLS");
+    }
+} // tests
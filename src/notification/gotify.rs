@@ -0,0 +1,213 @@
+use std::result;
+
+use serde_json::json;
+
+use crate::cli;
+use crate::error;
+use crate::notification::{http, message, Message, PlatformNotifier, Urgency};
+
+type Result<T> = result::Result<T, error::Notification>;
+
+/// Gotify Notifier.
+///
+/// Messages are sent on behalf of a Gotify application.
+#[derive(Debug)]
+pub struct Notifier {
+    /// URL of the Gotify server.
+    url: String,
+    /// Token of the Gotify application.
+    token: String,
+    /// Whether a message should be sent when the alert is removed.
+    resolved: bool,
+    /// Summary of the last sent alert which hasn't been removed yet.
+    active: Option<String>,
+
+    agent: ureq::Agent,
+}
+
+impl PlatformNotifier for Notifier {
+    type Error = error::Notification;
+
+    fn notify(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        self.send(&message.summary, &message.body, message.urgency)?;
+
+        self.active = Some(message.summary.clone());
+
+        Ok(())
+    }
+
+    fn test(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        self.send(&message.summary, &message.body, message.urgency)?;
+
+        Ok(())
+    }
+
+    fn remove(&mut self) -> result::Result<(), Self::Error> {
+        match self.active.take() {
+            Some(summary) if self.resolved => {
+                self.send(
+                    &message::resolved_summary(&summary),
+                    &message::resolved_body(),
+                    Urgency::Low,
+                )?;
+
+                log::debug!("notification/gotify: resolved message sent");
+            }
+            _ => log::trace!("notification/gotify: remove noop"),
+        }
+
+        Ok(())
+    }
+}
+
+impl Notifier {
+    /// Create a new `Notifier` instance from the given options.
+    ///
+    /// Return `None` if no Gotify server URL was specified.
+    pub fn new(options: cli::Gotify) -> Option<Self> {
+        let cli::Gotify {
+            url,
+            token,
+            resolved,
+        } = options;
+
+        Some(Self {
+            url: http::trim_url(&url?),
+            token: token?,
+            resolved,
+            active: None,
+            agent: http::agent(),
+        })
+    }
+
+    /// Send a message with the given contents to the Gotify server.
+    fn send(&self, title: &str, message: &str, urgency: Urgency) -> Result<()> {
+        self.agent
+            .post(&format!("{}/message", self.url))
+            .set("X-Gotify-Key", &self.token)
+            .send_json(json!({
+                "title": title,
+                "message": message,
+                "priority": priority(urgency),
+            }))
+            .map_err(Box::new)?;
+
+        log::debug!("notification/gotify: message sent");
+
+        Ok(())
+    }
+}
+
+/// Map `Urgency` to the Gotify message priority.
+const fn priority(urgency: Urgency) -> u8 {
+    http::priority(urgency, 2, 5, 8)
+}
+
+mod std_fmt_impls {
+    use std::fmt;
+
+    use super::Notifier;
+
+    impl fmt::Display for Notifier {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "Gotify Notifier: url = {}", self.url)
+        }
+    }
+} // std_fmt_impls
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::testing;
+
+    use super::*;
+
+    fn create_notifier(url: &str, resolved: bool) -> Notifier {
+        Notifier::new(cli::Gotify {
+            url: Some(url.into()),
+            token: Some("test-token".into()),
+            resolved,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_notifier_new_without_url() {
+        let notifier = Notifier::new(cli::Gotify {
+            url: None,
+            token: None,
+            resolved: false,
+        });
+
+        assert!(notifier.is_none());
+    }
+
+    #[test]
+    fn test_notifier_display() {
+        let notifier = create_notifier("http://localhost/", false);
+
+        let result = notifier.to_string();
+
+        assert_eq!(result, "Gotify Notifier: url = http://localhost");
+    }
+
+    #[test]
+    fn test_notifier_notify() {
+        let (url, requests) = testing::http_server(vec![(200, "{}".into())]);
+        let mut notifier = create_notifier(&url, false);
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        let result = notifier.notify(&message);
+
+        assert!(result.is_ok());
+        let request = requests.recv().unwrap();
+        let body: Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/message");
+        assert_eq!(request.header("X-Gotify-Key"), Some("test-token"));
+        assert_eq!(body["title"], "summary");
+        assert_eq!(body["message"], "body");
+        assert_eq!(body["priority"], 8);
+    }
+
+    #[test]
+    fn test_notifier_notify_error_status() {
+        let (url, _requests) = testing::http_server(vec![(401, "{}".into())]);
+        let mut notifier = create_notifier(&url, false);
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        let result = notifier.notify(&message);
+
+        assert!(matches!(result, Err(error::Notification::Http(_))));
+    }
+
+    #[test]
+    fn test_notifier_remove_resolved() {
+        let (url, requests) =
+            testing::http_server(vec![(200, "{}".into()), (200, "{}".into())]);
+        let mut notifier = create_notifier(&url, true);
+        let message = Message::test("summary", "body", Urgency::Low);
+
+        notifier.notify(&message).unwrap();
+        let result = notifier.remove();
+
+        assert!(result.is_ok());
+        let body: Value =
+            serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(body["priority"], 2);
+        let body: Value =
+            serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(body["title"], "Resolved: summary");
+        assert_eq!(body["message"], message::resolved_body());
+    }
+
+    #[test]
+    fn test_notifier_remove_without_alert() {
+        let mut notifier = create_notifier("http://127.0.0.1:9", true);
+
+        let result = notifier.remove();
+
+        assert!(result.is_ok());
+    }
+} // tests
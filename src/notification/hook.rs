@@ -0,0 +1,425 @@
+use std::{io, result, time};
+
+use serde_json::json;
+
+use crate::cli;
+use crate::common;
+use crate::error;
+use crate::notification::{Message, PlatformNotifier};
+
+type Result<T> = result::Result<T, error::Hook>;
+
+/// Shell which executes the hook commands.
+const SHELL: &str = "sh";
+
+/// Event which triggered the hook.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Event {
+    Alert,
+    Clear,
+    Action,
+}
+
+/// Hook Notifier.
+///
+/// Executes user specified commands whenever an alert is raised or cleared.
+#[derive(Debug)]
+pub struct Notifier {
+    on_alert: Option<String>,
+    on_clear: Option<String>,
+    on_action: Option<String>,
+    timeout: time::Duration,
+
+    /// Whether an alert was raised and wasn't cleared yet.
+    active: bool,
+    /// Battery data of the latest update.
+    data: Option<Data>,
+}
+
+/// Battery data passed to the hook commands.
+#[derive(Debug, Clone)]
+struct Data {
+    percentage: u8,
+    state: String,
+    model: String,
+    threshold: u8,
+    urgency: String,
+    summary: String,
+    body: String,
+}
+
+impl PlatformNotifier for Notifier {
+    type Error = error::Notification;
+
+    fn notify(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        if self.active {
+            log::trace!("notification/hook: alert hook already executed");
+
+            return Ok(());
+        }
+
+        // a failed hook is executed again by the retries of the delivery
+        self.execute(Event::Alert, &Data::from(message))?;
+        self.active = true;
+
+        Ok(())
+    }
+
+    fn test(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        self.execute(Event::Alert, &Data::from(message))?;
+
+        Ok(())
+    }
+
+    fn remove(&mut self) -> result::Result<(), Self::Error> {
+        if !self.active {
+            log::trace!("notification/hook: remove noop");
+
+            return Ok(());
+        }
+
+        self.active = false;
+
+        if let Some(data) = &self.data {
+            self.execute(Event::Clear, data)?;
+        }
+
+        Ok(())
+    }
+
+    fn update(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        self.data = Some(Data::from(message));
+
+        Ok(())
+    }
+}
+
+impl Notifier {
+    /// Create a new `Notifier` instance from the given options.
+    ///
+    /// Return `None` if no hook command was specified.
+    pub fn new(options: cli::Hook) -> Option<Self> {
+        let cli::Hook {
+            on_alert,
+            on_clear,
+            on_action,
+            on_action_label: _,
+            timeout,
+        } = options;
+
+        if on_alert.is_none() && on_clear.is_none() && on_action.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            on_alert,
+            on_clear,
+            on_action,
+            timeout: time::Duration::from_secs(timeout),
+            active: false,
+            data: None,
+        })
+    }
+
+    /// Execute the action hook command with the given `Message` data.
+    pub fn action(&self, message: &Message) -> Result<()> {
+        self.execute(Event::Action, &Data::from(message))
+    }
+
+    /// Execute the hook command of the given `Event` if there is one.
+    ///
+    /// Its stdout and stderr are written into the log.
+    fn execute(&self, event: Event, data: &Data) -> Result<()> {
+        let command = match event {
+            Event::Alert => &self.on_alert,
+            Event::Clear => &self.on_clear,
+            Event::Action => &self.on_action,
+        };
+
+        let Some(command) = command else {
+            return Ok(());
+        };
+
+        log::debug!("notification/hook: executing {event} hook \"{command}\"");
+
+        let stdin = data.json(event).to_string();
+        let input = common::Input {
+            envs: data.envs(event),
+            stdin: Some(stdin.as_bytes()),
+            timeout: Some(self.timeout),
+        };
+
+        let output =
+            common::command_with_input(SHELL, &["-c", command], &input)
+                .map_err(|e| match e.kind() {
+                    io::ErrorKind::TimedOut => error::Hook::Timeout {
+                        command: command.clone(),
+                        secs: self.timeout.as_secs(),
+                    },
+                    _ => error::Hook::Io(e),
+                })?;
+
+        let stdout = common::slice_to_string(&output.stdout);
+        if !stdout.is_empty() {
+            log::info!("notification/hook: stdout = {}", stdout.trim());
+        }
+
+        let stderr = common::slice_to_string(&output.stderr);
+        if !stderr.is_empty() {
+            log::warn!("notification/hook: stderr = {}", stderr.trim());
+        }
+
+        if !output.status.success() {
+            return Err(error::Hook::Exit {
+                command: command.clone(),
+                status: output.status,
+            });
+        }
+
+        log::debug!("notification/hook: {event} hook executed");
+
+        Ok(())
+    }
+}
+
+impl Data {
+    /// Return environment variables describing the `Event`.
+    fn envs(&self, event: Event) -> Vec<(&'static str, String)> {
+        vec![
+            ("RB_EVENT", event.to_string()),
+            ("RB_PERCENTAGE", self.percentage.to_string()),
+            ("RB_STATE", self.state.clone()),
+            ("RB_MODEL", self.model.clone()),
+            ("RB_THRESHOLD", self.threshold.to_string()),
+            ("RB_URGENCY", self.urgency.clone()),
+            ("RB_SUMMARY", self.summary.clone()),
+            ("RB_BODY", self.body.clone()),
+        ]
+    }
+
+    /// Return JSON object describing the `Event`.
+    fn json(&self, event: Event) -> serde_json::Value {
+        json!({
+            "event": event.to_string(),
+            "percentage": self.percentage,
+            "state": self.state,
+            "model": self.model,
+            "threshold": self.threshold,
+            "urgency": self.urgency,
+            "summary": self.summary,
+            "body": self.body,
+        })
+    }
+}
+
+impl From<&Message> for Data {
+    fn from(message: &Message) -> Self {
+        Self {
+            percentage: message.percentage,
+            state: message.state.to_string(),
+            model: message.model.clone(),
+            threshold: message.threshold,
+            urgency: message.urgency.to_string(),
+            summary: message.summary.clone(),
+            body: message.body.clone(),
+        }
+    }
+}
+
+mod std_fmt_impls {
+    use std::fmt;
+
+    use crate::common;
+
+    use super::{Event, Notifier};
+
+    impl fmt::Display for Event {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Alert => write!(f, "alert"),
+                Self::Clear => write!(f, "clear"),
+                Self::Action => write!(f, "action"),
+            }
+        }
+    }
+
+    impl fmt::Display for Notifier {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "Hook Notifier: on_alert = {}, on_clear = {}",
+                common::format_option(self.on_alert.as_ref()),
+                common::format_option(self.on_clear.as_ref()),
+            )
+        }
+    }
+} // std_fmt_impls
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use crate::notification::Urgency;
+
+    use super::*;
+
+    fn create_notifier(on_alert: &str, on_clear: &str) -> Notifier {
+        Notifier::new(cli::Hook {
+            on_alert: Some(on_alert.into()),
+            on_clear: Some(on_clear.into()),
+            on_action: None,
+            on_action_label: "Run hook".into(),
+            timeout: 10,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_notifier_new_without_commands() {
+        let notifier = Notifier::new(cli::Hook {
+            on_alert: None,
+            on_clear: None,
+            on_action: None,
+            on_action_label: "Run hook".into(),
+            timeout: 10,
+        });
+
+        assert!(notifier.is_none());
+    }
+
+    #[test]
+    fn test_notifier_display() {
+        let notifier = Notifier::new(cli::Hook {
+            on_alert: Some("true".into()),
+            on_clear: None,
+            on_action: None,
+            on_action_label: "Run hook".into(),
+            timeout: 10,
+        })
+        .unwrap();
+
+        let result = notifier.to_string();
+
+        assert_eq!(result, "Hook Notifier: on_alert = true, on_clear = None");
+    }
+
+    #[test]
+    fn test_notifier_notify_environment() {
+        let mut notifier = create_notifier(
+            "test \"$RB_EVENT $RB_PERCENTAGE $RB_STATE $RB_MODEL \
+            $RB_THRESHOLD $RB_URGENCY $RB_SUMMARY $RB_BODY\" = \
+            \"alert 85 Charging test-model 80 Critical summary body\"",
+            "true",
+        );
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        let result = notifier.notify(&message);
+
+        assert!(result.is_ok());
+        assert!(notifier.active);
+    }
+
+    #[test]
+    fn test_notifier_notify_stdin() {
+        let mut notifier =
+            create_notifier("grep -q '\"event\":\"alert\"' && true", "true");
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        let result = notifier.notify(&message);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_notifier_notify_once() {
+        let path = env::temp_dir()
+            .join(format!("rusty-battery-test-hook-{}.log", process::id()));
+        let mut notifier = create_notifier(
+            &format!("echo \"$RB_EVENT\" >> {}", path.display()),
+            "true",
+        );
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        notifier.notify(&message).unwrap();
+        notifier.notify(&message).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "alert\n");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_notifier_notify_failed() {
+        let mut notifier = create_notifier("exit 1", "true");
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        let first = notifier.notify(&message);
+        let retry = notifier.notify(&message);
+
+        assert!(matches!(
+            first,
+            Err(error::Notification::Hook(error::Hook::Exit { .. }))
+        ));
+        assert!(matches!(
+            retry,
+            Err(error::Notification::Hook(error::Hook::Exit { .. }))
+        ));
+        assert!(!notifier.active);
+    }
+
+    #[test]
+    fn test_notifier_notify_timeout() {
+        let mut notifier = create_notifier("sleep 10", "true");
+        notifier.timeout = time::Duration::from_millis(50);
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        let result = notifier.notify(&message);
+
+        assert!(matches!(
+            result,
+            Err(error::Notification::Hook(error::Hook::Timeout { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_notifier_remove() {
+        let mut notifier = create_notifier(
+            "true",
+            "test \"$RB_EVENT $RB_PERCENTAGE\" = \"clear 79\"",
+        );
+        let mut message = Message::test("summary", "body", Urgency::Critical);
+
+        notifier.notify(&message).unwrap();
+        message.percentage = 79;
+        notifier.update(&message).unwrap();
+        let result = notifier.remove();
+
+        assert!(result.is_ok());
+        assert!(!notifier.active);
+    }
+
+    #[test]
+    fn test_notifier_action() {
+        let notifier = Notifier::new(cli::Hook {
+            on_alert: None,
+            on_clear: None,
+            on_action: Some("test \"$RB_EVENT\" = \"action\"".into()),
+            on_action_label: "Run hook".into(),
+            timeout: 10,
+        })
+        .unwrap();
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        let result = notifier.action(&message);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_notifier_remove_without_alert() {
+        let mut notifier = create_notifier("true", "exit 1");
+
+        let result = notifier.remove();
+
+        assert!(result.is_ok());
+    }
+} // tests
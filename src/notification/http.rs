@@ -0,0 +1,49 @@
+//! HTTP helpers shared by notifiers talking to web services.
+use std::time;
+
+use crate::notification::Urgency;
+
+const TIMEOUT: time::Duration = time::Duration::from_secs(10);
+
+/// Return a new `ureq::Agent` with a sensible timeout.
+pub(super) fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout(TIMEOUT).build()
+}
+
+/// Return the given URL without any trailing slashes.
+pub(super) fn trim_url(url: &str) -> String {
+    url.trim_end_matches('/').to_owned()
+}
+
+/// Map `Urgency` to one of the given priorities.
+pub(super) const fn priority(
+    urgency: Urgency,
+    low: u8,
+    normal: u8,
+    critical: u8,
+) -> u8 {
+    match urgency {
+        Urgency::Low => low,
+        Urgency::Normal => normal,
+        Urgency::Critical => critical,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trim_url() {
+        let result = trim_url("http://localhost:80//");
+
+        assert_eq!(result, "http://localhost:80");
+    }
+
+    #[test]
+    fn test_priority() {
+        assert_eq!(priority(Urgency::Low, 1, 2, 3), 1);
+        assert_eq!(priority(Urgency::Normal, 1, 2, 3), 2);
+        assert_eq!(priority(Urgency::Critical, 1, 2, 3), 3);
+    }
+} // tests
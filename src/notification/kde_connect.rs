@@ -1,116 +1,690 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::result;
 
-use crate::common;
-use crate::device::{kde_connect, KDEConnect};
+use crate::device::kde_connect::{self, Action, Client, Selector};
+use crate::device::KDEConnect;
 use crate::error;
-use crate::notification::{Message, PlatformNotifier};
+use crate::notification::{message, Message, PlatformNotifier, Urgency};
 
 type Result<T> = result::Result<T, error::KDEConnect>;
 
+/// What happens when a device selected by its name or ID is unknown.
+#[derive(clap::ValueEnum, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Missing {
+    /// Fail at startup.
+    Fail,
+    /// Wait until the device becomes reachable.
+    Wait,
+}
+
 /// KDE Connect Notifier.
 #[derive(Debug)]
 pub struct Notifier {
-    /// Names of KDE Connect devices which should be pinged.
+    /// Selectors of KDE Connect devices which should be pinged.
+    ///
+    /// If this value is empty every available KDE Connect device will pinged.
+    selectors: Vec<Selector>,
+    /// Actions performed on the devices selected by their name.
     ///
-    /// If this value is `None` every available KDE Connect device will pinged.
-    device_names: Option<HashSet<String>>,
+    /// Devices without any actions are pinged.
+    actions: HashMap<String, Vec<Action>>,
+    /// Actions which the devices with the ID don't support.
+    unsupported: HashSet<(String, Action)>,
+    /// IDs of the devices already notified about the current alert.
+    notified: HashSet<String>,
+    /// Whether the notified devices should be pinged
+    /// when the alert is removed.
+    resolved: bool,
+    /// Current alert, a retry of its delivery pings only the devices
+    /// which weren't notified yet.
+    active: Option<Message>,
+    /// Client of the KDE Connect daemon.
+    client: Client,
 }
 
 impl PlatformNotifier for Notifier {
     type Error = error::KDEConnect;
 
     fn notify(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        let retry = self.active.as_ref().is_some_and(|active| {
+            active.summary == message.summary
+                && active.body == message.body
+                && active.urgency == message.urgency
+        });
+
+        // a new or updated alert is delivered to every device again
+        if !retry {
+            self.notified.clear();
+        }
+
+        self.active = Some(message.clone());
         self.ping(message)?;
 
         Ok(())
     }
 
     fn remove(&mut self) -> result::Result<(), Self::Error> {
-        log::trace!("notification/kde_connect: remove noop");
+        let notified = std::mem::take(&mut self.notified);
+
+        match self.active.take() {
+            Some(active) if self.resolved && !notified.is_empty() => {
+                let message = format!(
+                    "{}\n\n{}",
+                    message::resolved_summary(&active.summary),
+                    message::resolved_body()
+                );
+
+                for device in self.find_available()? {
+                    if notified.contains(device.id()) {
+                        device.ping(&self.client, &message)?;
+                    }
+                }
+
+                log::debug!("notification/kde_connect: resolved ping sent");
+            }
+            _ => log::trace!("notification/kde_connect: remove noop"),
+        }
+
+        Ok(())
+    }
+
+    fn repeat(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        self.ping(message)?;
 
         Ok(())
     }
+
+    fn test(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        // devices notified about the alert have to be pinged as well
+        let notified = std::mem::take(&mut self.notified);
+        let result = self.ping(message);
+        self.notified = notified;
+
+        result
+    }
 }
 
 impl Notifier {
     /// Create a new `KDEConnect` instance.
-    pub fn new(device_names: HashSet<String>) -> Result<Self> {
-        let notifier = Self {
-            device_names: if device_names.is_empty() {
-                log::info!(
-                    "notification/kde_connect: no device names specified, \
-                    all available devices will be pinged",
-                );
+    ///
+    /// `actions` are pairs of device names and actions performed on them.
+    pub fn new(
+        selectors: Vec<Selector>,
+        actions: Vec<(String, Action)>,
+        missing: Missing,
+        resolved: bool,
+    ) -> Result<Self> {
+        Self::with_client(selectors, actions, missing, resolved, Client::new())
+    }
 
-                None
-            } else {
-                log::info!(
-                    "notification/kde_connect: will ping devices with names {}",
-                    common::format_string_set(&device_names),
-                );
+    /// Create a new `KDEConnect` instance using the given `Client`.
+    ///
+    /// Name and ID `Selector`s of unknown devices fail with the
+    /// `Missing::Fail` policy, otherwise they are waited for. Actions
+    /// which a reachable device doesn't support fail as well. Type
+    /// `Selector`s fail with the CLI `Client` which doesn't know the types.
+    fn with_client(
+        selectors: Vec<Selector>,
+        actions: Vec<(String, Action)>,
+        missing: Missing,
+        resolved: bool,
+        client: Client,
+    ) -> Result<Self> {
+        if selectors.is_empty() {
+            log::info!(
+                "notification/kde_connect: no device selectors specified, \
+                all available devices will be pinged",
+            );
+        } else {
+            log::info!(
+                "notification/kde_connect: will ping devices selected by [{}]",
+                selectors
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            );
+        }
+
+        if let Client::Cli(_) = client {
+            if let Some(selector) = selectors
+                .iter()
+                .find(|selector| matches!(selector, Selector::Type(_)))
+            {
+                return Err(error::KDEConnect::CliType {
+                    selector: selector.to_string(),
+                });
+            }
+        }
 
-                Some(device_names)
+        let actions = actions.into_iter().fold(
+            HashMap::new(),
+            |mut actions, (name, action)| {
+                actions.entry(name).or_insert_with(Vec::new).push(action);
+
+                actions
             },
-        };
+        );
+
+        // check KDE Connect availability
+        let known = kde_connect::map::all(&client)?;
+
+        for device in known.values() {
+            if let Some(actions) = actions.get(device.name()) {
+                device.check_actions(&client, actions)?;
+            }
+        }
 
-        // check KDE Connect CLI availability
-        // also warns if some specified devices aren't available
-        notifier.find_available()?;
+        for selector in &selectors {
+            if known.values().any(|device| selector.matches(device)) {
+                continue;
+            }
 
-        Ok(notifier)
+            if selector.specific() && missing == Missing::Fail {
+                return Err(error::KDEConnect::Device(
+                    error::KDEConnectDevice::Unknown {
+                        selector: selector.to_string(),
+                    },
+                ));
+            }
+
+            log::warn!(
+                "notification/kde_connect: no known device selected \
+                by \"{selector}\", waiting until one becomes reachable"
+            );
+        }
+
+        Ok(Self {
+            selectors,
+            actions,
+            unsupported: HashSet::new(),
+            notified: HashSet::new(),
+            resolved,
+            active: None,
+            client,
+        })
     }
 
-    /// Ping all available `Device` instances.
-    fn ping(&self, message: &Message) -> Result<()> {
+    /// Perform actions on all available `Device` instances
+    /// which weren't notified about the current alert yet.
+    ///
+    /// Devices ring only if the `Message` is critical. A failed device
+    /// doesn't stop the others, its error is returned once all of them
+    /// were tried.
+    fn ping(&mut self, message: &Message) -> Result<()> {
+        let critical = message.urgency == Urgency::Critical;
         let message = format!("{}\n\n{}", message.summary, message.body);
+        let mut errors = vec![];
+
+        for device in self.find_available()? {
+            if self.notified.contains(device.id()) {
+                continue;
+            }
 
-        self.find_available()?
-            .iter()
-            .try_for_each(|device| device.ping(&message))?;
+            match self.perform_all(&device, &message, critical) {
+                Ok(()) => {
+                    self.notified.insert(device.id().to_owned());
+                }
+                Err(e) => errors.push((device.name().to_owned(), e)),
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(error::KDEConnect::Devices(errors));
+        }
 
         log::debug!("notification/kde_connect: available devices pinged");
 
         Ok(())
     }
 
-    /// Return all `Device` instances which are currently available.
+    /// Perform all actions of the `Device`.
+    fn perform_all(
+        &mut self,
+        device: &KDEConnect,
+        message: &str,
+        critical: bool,
+    ) -> Result<()> {
+        for action in self.device_actions(device) {
+            if action == Action::Ring && !critical {
+                log::trace!(
+                    "notification/kde_connect: ring skipped, \
+                    alert isn't critical"
+                );
+
+                continue;
+            }
+
+            self.perform(device, &action, message)?;
+        }
+
+        Ok(())
+    }
+
+    /// Perform the `Action` on the `Device`.
+    ///
+    /// If the `Device` doesn't support the `Action` it is pinged instead.
+    fn perform(
+        &mut self,
+        device: &KDEConnect,
+        action: &Action,
+        message: &str,
+    ) -> Result<()> {
+        let key = (device.id().to_owned(), action.clone());
+
+        if self.unsupported.contains(&key) {
+            return device.ping(&self.client, message);
+        }
+
+        match device.perform(&self.client, action, message) {
+            Err(e @ error::KDEConnect::Unsupported { .. })
+                if *action != Action::Ping =>
+            {
+                log::warn!("notification/kde_connect: {e}, pinging instead");
+
+                self.unsupported.insert(key);
+
+                device.ping(&self.client, message)
+            }
+            result => result,
+        }
+    }
+
+    /// Return actions which should be performed on the `Device`.
+    fn device_actions(&self, device: &KDEConnect) -> Vec<Action> {
+        self.actions
+            .get(device.name())
+            .cloned()
+            .unwrap_or_else(|| vec![Action::Ping])
+    }
+
+    /// Return all selected `Device` instances which are currently available.
     ///
-    /// If no `device_names` were specified at the creation,
+    /// If no `selectors` were specified at the creation,
     /// all available devices will be returned.
     fn find_available(&self) -> Result<Vec<KDEConnect>> {
-        let mut devices = kde_connect::map::available()?;
-
-        Ok(match &self.device_names {
-            None => devices.into_values().collect(),
-            Some(names) => kde_connect::find::all(&mut devices, names),
-        })
+        Ok(kde_connect::map::available(&self.client)?
+            .into_values()
+            .filter(|device| {
+                self.selectors.is_empty()
+                    || self.selectors.iter().any(|s| s.matches(device))
+            })
+            .collect())
     }
 }
 
 mod std_fmt_impls {
-    use std::borrow::Borrow;
     use std::fmt;
 
-    use super::Notifier;
+    use super::{Missing, Notifier};
+
+    impl fmt::Display for Missing {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Fail => write!(f, "Fail"),
+                Self::Wait => write!(f, "Wait"),
+            }
+        }
+    }
 
     impl fmt::Display for Notifier {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            let device_names = self.device_names.as_ref().map_or_else(
-                || "All".into(),
-                |names| {
-                    format!(
-                        "[{}]",
-                        names
-                            .iter()
-                            .map(Borrow::borrow)
-                            .collect::<Vec<&str>>()
-                            .join(", ")
-                    )
-                },
-            );
+            let selectors = if self.selectors.is_empty() {
+                "All".into()
+            } else {
+                format!(
+                    "[{}]",
+                    self.selectors
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                )
+            };
 
-            write!(f, "KDE Connect Notifier: device_names = {device_names}",)
+            write!(f, "KDE Connect Notifier: selectors = {selectors}",)
         }
     }
 } // std_fmt_impls
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::kde_connect::dbus;
+    use crate::testing::{
+        self, KDEConnectDaemon, KDEConnectDevice, KDEConnectPing,
+    };
+
+    fn create_notifier(actions: Vec<(String, Action)>) -> Notifier {
+        Notifier {
+            selectors: vec![],
+            actions: actions.into_iter().fold(
+                HashMap::new(),
+                |mut actions, (name, action)| {
+                    actions.entry(name).or_insert_with(Vec::new).push(action);
+
+                    actions
+                },
+            ),
+            unsupported: HashSet::new(),
+            notified: HashSet::new(),
+            resolved: false,
+            active: None,
+            client: Client::Cli("kdeconnect-cli".into()),
+        }
+    }
+
+    fn selectors(values: &[&str]) -> Vec<Selector> {
+        values.iter().map(|value| value.parse().unwrap()).collect()
+    }
+
+    fn pings(service: &zbus::blocking::Connection, id: &str) -> Vec<String> {
+        service
+            .object_server()
+            .interface::<_, KDEConnectPing>(dbus::plugin_path(id, "ping"))
+            .unwrap()
+            .get()
+            .messages
+            .clone()
+    }
+
+    #[test]
+    fn test_notifier_device_actions_default() {
+        let notifier = create_notifier(vec![("tablet".into(), Action::Ring)]);
+        let device = KDEConnect::try_from("abc phone").unwrap();
+
+        let result = notifier.device_actions(&device);
+
+        assert_eq!(result, [Action::Ping]);
+    }
+
+    #[test]
+    fn test_notifier_device_actions() {
+        let notifier = create_notifier(vec![
+            ("phone".into(), Action::Ring),
+            ("phone".into(), Action::Share),
+        ]);
+        let device = KDEConnect::try_from("abc phone").unwrap();
+
+        let result = notifier.device_actions(&device);
+
+        assert_eq!(result, [Action::Ring, Action::Share]);
+    }
+
+    #[test]
+    fn test_notifier_new_missing_fail() {
+        let (connection, _service) =
+            testing::kde_connect_service(vec![KDEConnectDevice::phone(
+                "a", "Pixel 7", None,
+            )]);
+
+        let result = Notifier::with_client(
+            selectors(&["Pixel 7", "id:b"]),
+            vec![],
+            Missing::Fail,
+            false,
+            Client::DBus(connection),
+        );
+
+        assert!(matches!(
+            result,
+            Err(error::KDEConnect::Device(
+                error::KDEConnectDevice::Unknown { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_notifier_new_missing_wait() {
+        let (connection, _service) =
+            testing::kde_connect_service(vec![KDEConnectDevice::phone(
+                "a", "Pixel 7", None,
+            )]);
+
+        let result = Notifier::with_client(
+            selectors(&["Pixel 7", "id:b", "type:tablet"]),
+            vec![],
+            Missing::Wait,
+            false,
+            Client::DBus(connection),
+        );
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_notifier_new_unsupported_action() {
+        let (connection, _service) =
+            testing::kde_connect_service(vec![KDEConnectDevice::phone(
+                "a", "Pixel 7", None,
+            )]);
+
+        let result = Notifier::with_client(
+            vec![],
+            vec![
+                ("Pixel 7".into(), Action::Share),
+                ("Pixel 7".into(), Action::Ring),
+            ],
+            Missing::Fail,
+            false,
+            Client::DBus(connection),
+        );
+
+        assert!(matches!(
+            result,
+            Err(error::KDEConnect::Unsupported { device, action })
+                if device == "Pixel 7" && action == "ring"
+        ));
+    }
+
+    #[test]
+    fn test_notifier_new_cli_type() {
+        let result = Notifier::with_client(
+            selectors(&["type:phone"]),
+            vec![],
+            Missing::Wait,
+            false,
+            Client::Cli(testing::kde_connect_cli("exit 0")),
+        );
+
+        assert!(matches!(
+            result,
+            Err(error::KDEConnect::CliType { selector })
+                if selector == "type:smartphone"
+        ));
+    }
+
+    #[test]
+    fn test_notifier_notify_selected() {
+        let mut tablet = KDEConnectDevice::phone("b", "Tablet", None);
+        tablet.kind = "tablet".into();
+        let (connection, service) = testing::kde_connect_service(vec![
+            KDEConnectDevice::phone("a", "Pixel 7", None),
+            tablet,
+            KDEConnectDevice::phone("c", "Work phone", None),
+        ]);
+        let mut notifier = Notifier::with_client(
+            selectors(&["id:a", "type:tablet"]),
+            vec![],
+            Missing::Fail,
+            false,
+            Client::DBus(connection),
+        )
+        .unwrap();
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        notifier.notify(&message).unwrap();
+
+        assert_eq!(pings(&service, "a"), ["summary\n\nbody"]);
+        assert_eq!(pings(&service, "b"), ["summary\n\nbody"]);
+        assert!(pings(&service, "c").is_empty());
+    }
+
+    #[test]
+    fn test_notifier_notify_same_name() {
+        let (connection, service) = testing::kde_connect_service(vec![
+            KDEConnectDevice::phone("a", "Pixel 7", None),
+            KDEConnectDevice::phone("b", "Pixel 7", None),
+        ]);
+        let mut notifier = Notifier::with_client(
+            selectors(&["Pixel 7"]),
+            vec![],
+            Missing::Fail,
+            false,
+            Client::DBus(connection),
+        )
+        .unwrap();
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        notifier.notify(&message).unwrap();
+
+        assert_eq!(pings(&service, "a"), ["summary\n\nbody"]);
+        assert_eq!(pings(&service, "b"), ["summary\n\nbody"]);
+    }
+
+    #[test]
+    fn test_notifier_repeat_once_per_alert() {
+        let (connection, service) =
+            testing::kde_connect_service(vec![KDEConnectDevice::phone(
+                "a", "Pixel 7", None,
+            )]);
+        let mut notifier = Notifier::with_client(
+            selectors(&["paired"]),
+            vec![],
+            Missing::Fail,
+            false,
+            Client::DBus(connection),
+        )
+        .unwrap();
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        notifier.notify(&message).unwrap();
+        notifier.repeat(&message).unwrap();
+        notifier.remove().unwrap();
+        notifier.notify(&message).unwrap();
+
+        assert_eq!(pings(&service, "a").len(), 2);
+    }
+
+    #[test]
+    fn test_notifier_notify_updated() {
+        let (connection, service) =
+            testing::kde_connect_service(vec![KDEConnectDevice::phone(
+                "a", "Pixel 7", None,
+            )]);
+        let mut notifier = Notifier::with_client(
+            selectors(&["paired"]),
+            vec![],
+            Missing::Fail,
+            false,
+            Client::DBus(connection),
+        )
+        .unwrap();
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        notifier.notify(&message).unwrap();
+        notifier.notify(&message.updated()).unwrap();
+
+        assert_eq!(
+            pings(&service, "a"),
+            ["summary\n\nbody", "Updated: summary\n\nbody"]
+        );
+    }
+
+    #[test]
+    fn test_notifier_remove_resolved() {
+        let (connection, service) = testing::kde_connect_service(vec![
+            KDEConnectDevice::phone("a", "Pixel 7", None),
+            KDEConnectDevice::phone("b", "Work phone", None),
+        ]);
+        let mut notifier = Notifier::with_client(
+            selectors(&["Pixel 7"]),
+            vec![],
+            Missing::Fail,
+            true,
+            Client::DBus(connection),
+        )
+        .unwrap();
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        notifier.notify(&message).unwrap();
+        notifier.remove().unwrap();
+        notifier.remove().unwrap();
+
+        assert_eq!(
+            pings(&service, "a"),
+            [
+                "summary\n\nbody".to_owned(),
+                format!("Resolved: summary\n\n{}", message::resolved_body()),
+            ]
+        );
+        assert!(pings(&service, "b").is_empty());
+    }
+
+    #[test]
+    fn test_notifier_notify_device_back_online() {
+        let mut phone = KDEConnectDevice::phone("a", "Pixel 7", None);
+        phone.reachable = false;
+        let (connection, service) = testing::kde_connect_service(vec![phone]);
+        let mut notifier = Notifier::with_client(
+            selectors(&["Pixel 7"]),
+            vec![],
+            Missing::Fail,
+            false,
+            Client::DBus(connection),
+        )
+        .unwrap();
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        notifier.notify(&message).unwrap();
+        let offline = pings(&service, "a");
+        service
+            .object_server()
+            .interface::<_, KDEConnectDaemon>("/modules/kdeconnect")
+            .unwrap()
+            .get_mut()
+            .devices[0]
+            .reachable = true;
+        notifier.repeat(&message).unwrap();
+
+        assert!(offline.is_empty());
+        assert_eq!(pings(&service, "a").len(), 1);
+    }
+
+    #[test]
+    fn test_notifier_notify_failed_device() {
+        let (connection, service) = testing::kde_connect_service(vec![
+            KDEConnectDevice::phone("a", "Pixel 7", None),
+            KDEConnectDevice::phone("b", "Work phone", None),
+        ]);
+        let mut notifier = Notifier::with_client(
+            selectors(&["paired"]),
+            vec![],
+            Missing::Fail,
+            false,
+            Client::DBus(connection),
+        )
+        .unwrap();
+        let message = Message::test("summary", "body", Urgency::Critical);
+        service
+            .object_server()
+            .remove::<KDEConnectPing, _>(dbus::plugin_path("a", "ping"))
+            .unwrap();
+
+        let failed = notifier.notify(&message);
+        service
+            .object_server()
+            .at(dbus::plugin_path("a", "ping"), KDEConnectPing::default())
+            .unwrap();
+        let retry = notifier.notify(&message);
+
+        assert!(matches!(
+            failed,
+            Err(error::KDEConnect::Devices(errors))
+                if errors.len() == 1 && errors[0].0 == "Pixel 7"
+        ));
+        assert!(retry.is_ok());
+        assert_eq!(pings(&service, "a"), ["summary\n\nbody"]);
+        assert_eq!(pings(&service, "b"), ["summary\n\nbody"]);
+    }
+} // tests
@@ -1,80 +1,149 @@
+use std::result;
+
 use crate::device;
+use crate::device::BatteryState;
+use crate::error;
+use crate::locale;
+use crate::notification::{Templates, Urgency};
+
+type Result<T> = result::Result<T, error::Notification>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Message {
     pub summary: String,
     pub body: String,
+    pub urgency: Urgency,
 
-    summary_template: String,
-    body_template: String,
+    /// Battery percentage the message was last updated with.
+    pub percentage: u8,
+    /// Battery state the message was last updated with.
+    pub state: BatteryState,
+    /// Model of the monitored battery.
+    pub model: String,
+    /// Serial number of the monitored battery.
+    pub serial_number: String,
+    /// Battery charge threshold.
+    pub threshold: u8,
+    /// Number of seconds between battery refreshes.
+    pub refresh_secs: u64,
+
+    templates: Templates,
 }
 
 impl Message {
+    /// Create a new `Message` rendered from the summary and body templates.
+    ///
+    /// Return `error::Notification::Config` if the templates are invalid.
     pub fn new(
-        summary_template: String,
-        body_template: String,
+        summary_template: &str,
+        body_template: &str,
         threshold: u8,
         battery: &device::Battery,
         refresh_secs: u64,
-    ) -> Self {
+        urgency: Urgency,
+    ) -> Result<Self> {
         let mut message = Self {
             summary: String::new(),
             body: String::new(),
-            summary_template,
-            body_template,
+            urgency,
+            percentage: battery.percentage,
+            state: battery.state,
+            model: battery.model.clone(),
+            serial_number: battery.serial_number.clone(),
+            threshold,
+            refresh_secs,
+            templates: Templates::new(summary_template, body_template)?,
         };
 
-        message.setup(threshold, refresh_secs, battery);
-        message.update(battery);
+        message.render()?;
 
-        message
+        Ok(message)
     }
 
+    /// Render the templates with the current state of the battery.
+    ///
+    /// If rendering fails the previous summary and body are kept.
     pub fn update(&mut self, battery: &device::Battery) {
-        let charge_state = battery.percentage.to_string();
+        self.percentage = battery.percentage;
+        self.state = battery.state;
 
-        let replace = |template: &str| {
-            log::trace!(
-                "message/update: replacing $CHARGE_STATE on \
-                template \"{template}\" with {charge_state}"
-            );
+        match self.render() {
+            Ok(()) => log::debug!("message/update: success"),
+            Err(e) => log::warn!("message/update: {e}"),
+        }
+    }
 
-            template.replace("$CHARGE_STATE", &charge_state)
-        };
+    /// Change the battery charge threshold and render the templates again.
+    pub fn set_threshold(&mut self, threshold: u8) {
+        self.threshold = threshold;
 
-        self.summary = replace(&self.summary_template);
-        self.body = replace(&self.body_template);
+        match self.render() {
+            Ok(()) => log::debug!("message/set_threshold: success"),
+            Err(e) => log::warn!("message/set_threshold: {e}"),
+        }
+    }
 
-        log::debug!("message/update: success");
+    /// Return copy of the `Message` announcing that the alert was updated.
+    pub fn updated(&self) -> Self {
+        let mut message = self.clone();
+        message.summary = locale::format(
+            "alert-updated",
+            &[("summary", self.summary.as_str().into())],
+        );
+
+        message
     }
 
-    fn setup(
-        &mut self,
-        threshold: u8,
-        refresh_secs: u64,
-        battery: &device::Battery,
-    ) {
-        let threshold_s = threshold.to_string();
-        let model_s = battery.model.clone();
-        let refresh_secs_s = refresh_secs.to_string();
-
-        let replace = |template: &str| {
-            log::trace!(
-                "message/setup: setting up template \"{template}\" with \
-                $THRESHOLD = {threshold_s}, \
-                $MODEL = {model_s}, \
-                $REFRESH_SECS = {refresh_secs_s}"
-            );
-
-            template
-                .replace("$THRESHOLD", &threshold_s)
-                .replace("$MODEL", &model_s)
-                .replace("$REFRESH_SECS", &refresh_secs_s)
-        };
+    fn render(&mut self) -> Result<()> {
+        let (summary, body) = self.templates.render(self)?;
 
-        self.summary_template = replace(&self.summary_template);
-        self.body_template = replace(&self.body_template);
+        self.summary = summary;
+        self.body = body;
 
-        log::debug!("message/setup: success");
+        Ok(())
     }
 }
+
+/// Return summary of a message announcing that the given alert was resolved.
+pub(super) fn resolved_summary(summary: &str) -> String {
+    locale::format("alert-resolved", &[("summary", summary.into())])
+}
+
+/// Return body of a message announcing a resolved alert.
+pub(super) fn resolved_body() -> String {
+    locale::text("alert-resolved-body")
+}
+
+#[cfg(test)]
+impl Message {
+    /// Return a `Message` which doesn't depend on any battery device.
+    ///
+    /// `summary` and `body` are used as both the templates and their
+    /// rendered values.
+    pub fn test(summary: &str, body: &str, urgency: Urgency) -> Self {
+        Self {
+            summary: summary.into(),
+            body: body.into(),
+            urgency,
+            percentage: 85,
+            state: BatteryState::Charging,
+            model: "test-model".into(),
+            serial_number: "test-serial-number".into(),
+            threshold: 80,
+            refresh_secs: 30,
+            templates: Templates::new(summary, body).unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolved_summary() {
+        let result = resolved_summary("test-summary");
+
+        assert_eq!(result, "Resolved: test-summary");
+    }
+} // tests
@@ -1,42 +1,158 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::result;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
 
+pub use action::Action;
+pub use channel::{Channel, Fallback, Health, Policy};
 pub use desktop::Notifier as DesktopNotifier;
-pub use kde_connect::Notifier as KDEConnectNotifier;
+pub use email::{Notifier as EmailNotifier, Security as EmailSecurity};
+pub use gotify::Notifier as GotifyNotifier;
+pub use hook::Notifier as HookNotifier;
+pub use kde_connect::{
+    Missing as KDEConnectMissing, Notifier as KDEConnectNotifier,
+};
 pub use message::Message;
+pub use mqtt::Notifier as MqttNotifier;
+pub use ntfy::Notifier as NtfyNotifier;
+pub use plug::{Kind as PlugKind, Notifier as PlugNotifier};
+pub use quiet::{Mode as QuietMode, QuietHours, Window as QuietWindow};
+pub use record::{Change, Record};
+pub use template::Templates;
+pub use urgency::Urgency;
 
-use crate::error;
+use crate::event::Event;
+use crate::{cli, common, error, locale};
 
+mod action;
+mod channel;
 mod desktop;
+mod email;
+mod gotify;
+mod hook;
+mod http;
 mod kde_connect;
 mod message;
+mod mqtt;
+mod ntfy;
 mod operation;
+mod plug;
+mod quiet;
+mod record;
+mod template;
+mod urgency;
 
 type Result<T> = result::Result<T, error::Error>;
 
 pub trait PlatformNotifier {
     type Error: std::error::Error;
 
+    /// Deliver the alert.
+    ///
+    /// Failed deliveries are retried by calling `notify` again, so state
+    /// marking the alert as sent has to change only once it succeeded.
     fn notify(&mut self, message: &Message) -> result::Result<(), Self::Error>;
 
     fn remove(&mut self) -> result::Result<(), Self::Error>;
+
+    /// Deliver an already sent alert to recipients which missed it.
+    ///
+    /// Called instead of `notify` when the alert didn't change since it was
+    /// sent, most notifiers reach all their recipients at once and don't
+    /// need to do anything here.
+    fn repeat(
+        &mut self,
+        _message: &Message,
+    ) -> result::Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Deliver a test notification without affecting the current alert.
+    ///
+    /// Notifiers which track the alert they sent have to bypass
+    /// that tracking here.
+    fn test(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        self.notify(message)
+    }
+
+    /// Process the current state of the battery.
+    ///
+    /// Called after every battery refresh regardless of the alert state,
+    /// most notifiers don't need to do anything here.
+    fn update(
+        &mut self,
+        _message: &Message,
+    ) -> result::Result<(), Self::Error> {
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
 pub struct Notifier {
     pub threshold: u8,
 
+    /// Duration the alert is suppressed for when snoozed.
+    snooze: Duration,
+    /// Time until which the alert is snoozed.
+    snoozed_until: Option<Instant>,
+    /// Whether the alert is dismissed until the battery discharges
+    /// below the threshold.
+    muted: bool,
+    /// Whether all alerts are suppressed until resumed.
+    paused: bool,
+
     desktop: Option<DesktopNotifier>,
     kde_connect: Option<KDEConnectNotifier>,
+    ntfy: Option<NtfyNotifier>,
+    gotify: Option<GotifyNotifier>,
+    mqtt: Option<MqttNotifier>,
+    plug: Option<PlugNotifier>,
+    hook: Option<HookNotifier>,
+    email: Option<EmailNotifier>,
+
+    /// Delivery statistics of the enabled channels.
+    health: HashMap<Channel, Health>,
+    /// Fallbacks of the channels which fail to deliver an alert.
+    fallbacks: HashMap<Channel, Fallback>,
+
+    /// Number of raised alerts, the ID of the latest one.
+    alerts: u64,
+    /// Whether an alert is raised and wasn't removed yet.
+    active: bool,
+    /// Records of the current alert sent via the channels.
+    records: HashMap<Channel, Record>,
+    /// Minimum battery percentage change which sends an updated alert.
+    update_change: u8,
+    /// Quiet hours and the alerts deferred by them.
+    quiet: QuietHours,
 }
 
 impl Notifier {
     /// Create a new `Notifier` instance.
+    ///
+    /// Actions invoked on notifications are sent via the given `Sender`.
     pub fn new(
         threshold: u8,
-        kde_connect_names: Option<HashSet<String>>,
-        disable_desktop: bool,
+        notifiers: cli::Notifiers,
+        sender: mpsc::Sender<Event>,
     ) -> Result<Self> {
+        let cli::Notifiers {
+            kde_connect_selectors,
+            kde_connect_missing,
+            kde_connect_actions,
+            kde_connect_resolved,
+            disable_desktop,
+            desktop: desktop_options,
+            ntfy,
+            gotify,
+            mqtt,
+            plug,
+            hook,
+            email,
+            delivery,
+            quiet,
+        } = notifiers;
+
         log::info!("notification: threshold set to {threshold}%");
 
         let desktop = if disable_desktop {
@@ -46,11 +162,17 @@ impl Notifier {
         } else {
             log::info!("notification: desktop notifications enabled");
 
-            Some(DesktopNotifier::new())
+            Some(DesktopNotifier::new(
+                &desktop_options,
+                hook.on_action
+                    .as_ref()
+                    .map(|_| hook.on_action_label.as_str()),
+                sender,
+            ))
         };
 
-        let kde_connect: Result<Option<KDEConnectNotifier>> = kde_connect_names
-            .map_or_else(
+        let kde_connect: Result<Option<KDEConnectNotifier>> =
+            kde_connect_selectors.map_or_else(
                 || {
                     log::info!(
                         "notification: KDE Connect notifications disabled"
@@ -58,37 +180,659 @@ impl Notifier {
 
                     Ok(None)
                 },
-                |names| {
+                |selectors| {
                     log::info!(
                         "notification: KDE Connect notifications enabled"
                     );
 
-                    Ok(Some(KDEConnectNotifier::new(names)?))
+                    Ok(Some(KDEConnectNotifier::new(
+                        selectors,
+                        kde_connect_actions,
+                        kde_connect_missing,
+                        kde_connect_resolved,
+                    )?))
                 },
             );
 
-        Ok(Self {
+        let ntfy = NtfyNotifier::new(ntfy);
+        log::info!(
+            "notification: ntfy notifications {}",
+            enabled_str(ntfy.is_some()),
+        );
+
+        let gotify = GotifyNotifier::new(gotify);
+        log::info!(
+            "notification: Gotify notifications {}",
+            enabled_str(gotify.is_some()),
+        );
+
+        let mqtt = MqttNotifier::new(mqtt);
+        log::info!(
+            "notification: MQTT notifications {}",
+            enabled_str(mqtt.is_some()),
+        );
+
+        let plug = PlugNotifier::new(plug);
+        log::info!("notification: smart plug {}", enabled_str(plug.is_some()));
+
+        let hook = HookNotifier::new(hook);
+        log::info!("notification: hooks {}", enabled_str(hook.is_some()));
+
+        let email =
+            EmailNotifier::new(email).map_err(error::Notification::from)?;
+        log::info!(
+            "notification: email notifications {}",
+            enabled_str(email.is_some()),
+        );
+
+        let mut notifier = Self {
             threshold,
+            snooze: Duration::from_secs(desktop_options.snooze_mins * 60),
+            snoozed_until: None,
+            muted: false,
+            paused: false,
             desktop,
             kde_connect: kde_connect?,
-        })
+            ntfy,
+            gotify,
+            mqtt,
+            plug,
+            hook,
+            email,
+            health: HashMap::new(),
+            fallbacks: delivery.fallbacks.iter().copied().collect(),
+            alerts: 0,
+            active: false,
+            records: HashMap::new(),
+            update_change: delivery.update_change,
+            quiet: QuietHours::new(&quiet),
+        };
+        notifier.setup_health(&delivery);
+        notifier.validate_fallbacks()?;
+
+        Ok(notifier)
+    }
+
+    /// Create `Health` of every enabled channel with its `Policy`.
+    fn setup_health(&mut self, delivery: &cli::Delivery) {
+        for channel in Channel::ALL {
+            if !self.enabled(channel) {
+                continue;
+            }
+
+            let retries = delivery
+                .channel_retries
+                .iter()
+                .rev()
+                .find(|(retried, _)| *retried == channel)
+                .map_or(delivery.retries, |(_, retries)| *retries);
+
+            self.health.insert(
+                channel,
+                Health::new(Policy {
+                    retries,
+                    backoff: Duration::from_millis(delivery.retry_backoff_ms),
+                    circuit_failures: delivery.circuit_failures,
+                    circuit_cooldown: Duration::from_secs(
+                        delivery.circuit_cooldown_secs,
+                    ),
+                }),
+            );
+        }
+    }
+
+    /// Make sure both channels of every fallback are enabled.
+    fn validate_fallbacks(&self) -> Result<()> {
+        for (channel, fallback) in &self.fallbacks {
+            for checked in [*channel, fallback.channel] {
+                if !self.enabled(checked) {
+                    return Err(error::Error::from(
+                        error::Notification::Config {
+                            kind: format!(
+                                "fallback {channel}={fallback} needs \
+                                the {checked} channel to be enabled"
+                            ),
+                        },
+                    ));
+                }
+            }
+
+            log::info!("notification: {channel} falls back to {fallback}");
+        }
+
+        Ok(())
+    }
+
+    /// Return whether the notifier of the `Channel` is enabled.
+    const fn enabled(&self, channel: Channel) -> bool {
+        match channel {
+            Channel::Desktop => self.desktop.is_some(),
+            Channel::KDEConnect => self.kde_connect.is_some(),
+            Channel::Ntfy => self.ntfy.is_some(),
+            Channel::Gotify => self.gotify.is_some(),
+            Channel::Mqtt => self.mqtt.is_some(),
+            Channel::Hook => self.hook.is_some(),
+            Channel::Email => self.email.is_some(),
+        }
     }
 
     /// Send notification to every supported platform.
+    ///
+    /// If a smart plug is used, notifications are only sent when it fails
+    /// to switch off the charger.
     pub fn notify(&mut self, message: &Message) {
-        operation::notify(&mut self.desktop, message);
-        operation::notify(&mut self.kde_connect, message);
+        if self.suppressed() {
+            log::debug!("notification: alert suppressed");
+
+            return;
+        }
+
+        if let Some(plug) = &mut self.plug {
+            match plug.notify(message) {
+                Ok(()) => {
+                    log::info!("notification: handled by the smart plug");
+
+                    return;
+                }
+                Err(e) => log::warn!(
+                    "notification: {e}, falling back to notifications"
+                ),
+            }
+        }
+
+        if !self.active {
+            self.active = true;
+            self.alerts += 1;
+
+            log::info!("notification: alert {} raised", self.alerts);
+        }
+
+        let quiet = self.quiet.active();
+        self.limit_retries();
+
+        for channel in Channel::ALL {
+            match self.quiet_mode(channel, quiet) {
+                QuietMode::Suppress => {
+                    if !self.records.contains_key(&channel) {
+                        self.quiet.defer(channel, self.alerts);
+                    }
+                }
+                QuietMode::Downgrade => {
+                    let mut downgraded = message.clone();
+                    downgraded.urgency = Urgency::Low;
+
+                    self.deliver(channel, &downgraded);
+                }
+                QuietMode::Deliver => self.deliver(channel, message),
+            }
+        }
+
+        log::debug!("notification: all sent");
+    }
+
+    /// Return `QuietMode` of the `Channel` for the current alert.
+    ///
+    /// Desktop notifications are suppressed while the desktop is
+    /// in do-not-disturb mode if requested. The deferred alert of a channel
+    /// which isn't suppressed anymore is released.
+    fn quiet_mode(&mut self, channel: Channel, quiet: bool) -> QuietMode {
+        if !self.enabled(channel) {
+            return QuietMode::Deliver;
+        }
+
+        let mode = if channel == Channel::Desktop
+            && self.quiet.desktop_dnd
+            && self
+                .desktop
+                .as_mut()
+                .is_some_and(DesktopNotifier::inhibited)
+        {
+            QuietMode::Suppress
+        } else if quiet {
+            self.quiet.mode(channel)
+        } else {
+            QuietMode::Deliver
+        };
+
+        if mode != QuietMode::Suppress {
+            if let Some(deferred) = self.quiet.release(channel) {
+                log::info!(
+                    "notification/{channel}: delivering alert {} deferred \
+                    at {}",
+                    deferred.alert,
+                    deferred.since.format("%H:%M"),
+                );
+            }
+        }
+
+        mode
+    }
 
-        log::info!("notification: all sent");
+    /// Deliver the alert via the `Channel` unless it was already sent.
+    ///
+    /// Identical and minor repeats of the alert are suppressed,
+    /// material changes are sent as an updated alert. If the `Channel`
+    /// fails its fallbacks are used instead.
+    fn deliver(&mut self, channel: Channel, message: &Message) {
+        let change = self
+            .records
+            .get(&channel)
+            .map(|record| record.change(message, self.update_change));
+        let updated;
+
+        let outgoing = match change {
+            None => message,
+            Some(Change::Material | Change::Minor) if channel.replaces() => {
+                message
+            }
+            Some(Change::Material) => {
+                log::info!(
+                    "notification/{channel}: alert {} updated",
+                    self.alerts
+                );
+
+                updated = message.updated();
+                &updated
+            }
+            Some(Change::Identical | Change::Minor) => {
+                log::trace!(
+                    "notification/{channel}: repeat of alert {} suppressed",
+                    self.alerts
+                );
+
+                self.repeat(channel, message);
+
+                return;
+            }
+        };
+
+        let delivered = match self.send(channel, outgoing) {
+            None => return,
+            Some(true) => true,
+            Some(false) => self.fall_back(channel, outgoing),
+        };
+
+        if delivered {
+            self.records
+                .insert(channel, Record::new(self.alerts, message));
+        }
+    }
+
+    /// Deliver the `Message` via the `Channel`.
+    ///
+    /// Return `None` if the `Channel` isn't enabled,
+    /// otherwise whether the `Message` was delivered.
+    fn send(&mut self, channel: Channel, message: &Message) -> Option<bool> {
+        let health = self.health.get_mut(&channel)?;
+
+        match channel {
+            Channel::Desktop => {
+                operation::notify(channel, &mut self.desktop, health, message)
+            }
+            Channel::KDEConnect => operation::notify(
+                channel,
+                &mut self.kde_connect,
+                health,
+                message,
+            ),
+            Channel::Ntfy => {
+                operation::notify(channel, &mut self.ntfy, health, message)
+            }
+            Channel::Gotify => {
+                operation::notify(channel, &mut self.gotify, health, message)
+            }
+            Channel::Mqtt => {
+                operation::notify(channel, &mut self.mqtt, health, message)
+            }
+            Channel::Hook => {
+                operation::notify(channel, &mut self.hook, health, message)
+            }
+            Channel::Email => {
+                operation::notify(channel, &mut self.email, health, message)
+            }
+        }
+    }
+
+    /// Deliver the repeated `Message` via the `Channel`.
+    fn repeat(&mut self, channel: Channel, message: &Message) {
+        match channel {
+            Channel::Desktop => operation::repeat(&mut self.desktop, message),
+            Channel::KDEConnect => {
+                operation::repeat(&mut self.kde_connect, message);
+            }
+            Channel::Ntfy => operation::repeat(&mut self.ntfy, message),
+            Channel::Gotify => operation::repeat(&mut self.gotify, message),
+            Channel::Mqtt => operation::repeat(&mut self.mqtt, message),
+            Channel::Hook => operation::repeat(&mut self.hook, message),
+            Channel::Email => operation::repeat(&mut self.email, message),
+        }
+    }
+
+    /// Deliver the `Message` via the fallback chain of the failed `Channel`.
+    ///
+    /// Return whether any fallback delivered the `Message`.
+    fn fall_back(&mut self, failed: Channel, message: &Message) -> bool {
+        let mut visited = HashSet::from([failed]);
+        let mut current = failed;
+
+        while let Some(fallback) = self.fallbacks.get(&current).copied() {
+            if !visited.insert(fallback.channel) {
+                log::warn!("notification: fallback cycle at {fallback}");
+
+                break;
+            }
+
+            log::info!(
+                "notification: {current} failed, falling back to {fallback}"
+            );
+
+            let delivered = if fallback.critical {
+                let mut message = message.clone();
+                message.urgency = Urgency::Critical;
+
+                self.send(fallback.channel, &message)
+            } else {
+                self.send(fallback.channel, message)
+            };
+
+            if delivered == Some(true) {
+                return true;
+            }
+
+            current = fallback.channel;
+        }
+
+        log::error!("notification: alert wasn't delivered via {failed}");
+
+        false
+    }
+
+    /// Change the battery charge threshold.
+    ///
+    /// A smart plug switched off by the previous threshold is switched
+    /// back on if the battery is under the new one.
+    pub fn set_threshold(&mut self, threshold: u8, message: &Message) {
+        log::info!(
+            "notification: threshold changed from {}% to {threshold}%",
+            self.threshold,
+        );
+
+        self.threshold = threshold;
+
+        if message.percentage < threshold {
+            if let Some(plug) = &mut self.plug {
+                common::warn_on_err("notification", plug.restore());
+            }
+        }
+    }
+
+    /// Make sure the threshold can be used with the smart plug.
+    ///
+    /// Return the reason if the threshold isn't higher than the battery
+    /// percentage under which the plug is switched back on.
+    pub fn check_threshold(&self, threshold: u8) -> result::Result<(), String> {
+        match &self.plug {
+            Some(plug) if threshold <= plug.on_threshold() => Err(format!(
+                "threshold must be higher than the plug on threshold {}%",
+                plug.on_threshold(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Return ID of the raised alert if it wasn't removed yet.
+    pub const fn alert(&self) -> Option<u64> {
+        if self.active {
+            Some(self.alerts)
+        } else {
+            None
+        }
+    }
+
+    /// Return `Health` and `Record` of the current alert
+    /// of every enabled channel.
+    pub fn channels(&mut self) -> Vec<(Channel, &mut Health, Option<&Record>)> {
+        let mut channels = self
+            .health
+            .iter_mut()
+            .map(|(channel, health)| {
+                (*channel, health, self.records.get(channel))
+            })
+            .collect::<Vec<(Channel, &mut Health, Option<&Record>)>>();
+        channels.sort_by_key(|(channel, _, _)| {
+            Channel::ALL.iter().position(|ordered| ordered == channel)
+        });
+
+        channels
     }
 
     /// Remove notification on every supported platform.
+    ///
+    /// Channels which can't retract their notifications
+    /// may announce that the alert was resolved instead.
     pub fn remove(&mut self) {
+        if self.active {
+            self.active = false;
+
+            log::info!("notification: alert {} resolved", self.alerts);
+        }
+
+        self.records.clear();
+        self.quiet.clear();
+
         operation::remove(&mut self.desktop);
         operation::remove(&mut self.kde_connect);
+        operation::remove(&mut self.ntfy);
+        operation::remove(&mut self.gotify);
+        operation::remove(&mut self.mqtt);
+        operation::remove(&mut self.hook);
+        operation::remove(&mut self.email);
 
         log::info!("notification: all removed");
     }
+
+    /// Update every supported platform with the current battery state.
+    pub fn update(&mut self, message: &Message) {
+        if self.muted && message.percentage < self.threshold {
+            self.muted = false;
+
+            log::info!("notification: alert no longer dismissed");
+        }
+
+        operation::update(&mut self.desktop, message);
+        operation::update(&mut self.kde_connect, message);
+        operation::update(&mut self.ntfy, message);
+        operation::update(&mut self.gotify, message);
+        operation::update(&mut self.mqtt, message);
+        operation::update(&mut self.hook, message);
+        operation::update(&mut self.email, message);
+        operation::update(&mut self.plug, message);
+
+        log::debug!("notification: all updated");
+    }
+
+    /// Handle `Action` invoked by the user on a notification.
+    pub fn handle_action(&mut self, action: Action, message: &Message) {
+        match action {
+            Action::Snooze => {
+                common::warn_on_err(
+                    "notification",
+                    self.snooze_for(self.snooze),
+                );
+            }
+            Action::Dismiss => {
+                self.muted = true;
+                operation::remove(&mut self.desktop);
+                self.records.remove(&Channel::Desktop);
+
+                log::info!(
+                    "notification: alert dismissed until the battery \
+                    discharges below the threshold"
+                );
+            }
+            Action::Unplugged => {
+                log::info!("notification: charger reported as unplugged");
+            }
+            Action::Hook => {
+                if let Some(hook) = &self.hook {
+                    common::warn_on_err("notification", hook.action(message));
+                }
+            }
+        }
+    }
+
+    /// Suppress the alert for the `Duration`.
+    ///
+    /// Nothing is changed if the end of the snooze can't be represented.
+    pub fn snooze_for(
+        &mut self,
+        duration: Duration,
+    ) -> result::Result<(), String> {
+        let snoozed_until = Instant::now()
+            .checked_add(duration)
+            .ok_or("snooze duration is too long")?;
+
+        self.snoozed_until = Some(snoozed_until);
+        operation::remove(&mut self.desktop);
+        self.records.remove(&Channel::Desktop);
+
+        log::info!(
+            "notification: alert snoozed for {} minutes",
+            duration.as_secs() / 60,
+        );
+
+        Ok(())
+    }
+
+    /// Suppress all alerts until `resume` is called.
+    pub fn pause(&mut self) {
+        self.paused = true;
+        operation::remove(&mut self.desktop);
+        self.records.remove(&Channel::Desktop);
+
+        log::info!("notification: alerts paused");
+    }
+
+    /// Resume alerts suppressed by `pause` or `snooze_for`.
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.snoozed_until = None;
+
+        log::info!("notification: alerts resumed");
+    }
+
+    /// Return whether all alerts are paused.
+    pub const fn paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Send a test notification via every enabled channel.
+    ///
+    /// Return whether the notification was delivered by each channel.
+    /// The current alert isn't affected and smart plugs aren't switched.
+    pub fn test(&mut self, message: &Message) -> Vec<(Channel, bool)> {
+        let mut test = message.clone();
+        test.summary = locale::text("test-summary");
+        test.body = locale::text("test-body");
+        self.limit_retries();
+
+        let results = Channel::ALL
+            .into_iter()
+            .filter_map(|channel| {
+                self.send_test(channel, &test)
+                    .map(|delivered| (channel, delivered))
+            })
+            .collect();
+
+        log::info!("notification: test notification sent");
+
+        results
+    }
+
+    /// Limit retries of all channels to the `RETRY_BUDGET` from now on.
+    fn limit_retries(&mut self) {
+        let deadline = Instant::now() + channel::RETRY_BUDGET;
+
+        for health in self.health.values_mut() {
+            health.limit_retries(deadline);
+        }
+    }
+
+    /// Deliver the test `Message` via the `Channel`.
+    ///
+    /// Return `None` if the `Channel` isn't enabled,
+    /// otherwise whether the `Message` was delivered.
+    fn send_test(
+        &mut self,
+        channel: Channel,
+        message: &Message,
+    ) -> Option<bool> {
+        let health = self.health.get_mut(&channel)?;
+
+        match channel {
+            Channel::Desktop => {
+                operation::test(channel, &mut self.desktop, health, message)
+            }
+            Channel::KDEConnect => {
+                operation::test(channel, &mut self.kde_connect, health, message)
+            }
+            Channel::Ntfy => {
+                operation::test(channel, &mut self.ntfy, health, message)
+            }
+            Channel::Gotify => {
+                operation::test(channel, &mut self.gotify, health, message)
+            }
+            Channel::Mqtt => {
+                operation::test(channel, &mut self.mqtt, health, message)
+            }
+            Channel::Hook => {
+                operation::test(channel, &mut self.hook, health, message)
+            }
+            Channel::Email => {
+                operation::test(channel, &mut self.email, health, message)
+            }
+        }
+    }
+
+    /// Return whether the alert is currently paused, snoozed or dismissed.
+    fn suppressed(&mut self) -> bool {
+        if self.paused {
+            return true;
+        }
+
+        if let Some(snoozed_until) = self.snoozed_until {
+            if Instant::now() < snoozed_until {
+                return true;
+            }
+
+            self.snoozed_until = None;
+
+            log::info!("notification: snooze expired");
+        }
+
+        self.muted
+    }
+
+    /// Remove notifications and switch the smart plug back on.
+    ///
+    /// Makes sure that the charger isn't left without power once
+    /// the process terminates.
+    pub fn shutdown(&mut self) {
+        self.remove();
+
+        if let Some(plug) = &mut self.plug {
+            common::warn_on_err("notification", plug.restore());
+        }
+    }
+}
+
+/// Return human readable representation of whether a notifier is enabled.
+const fn enabled_str(enabled: bool) -> &'static str {
+    if enabled {
+        "enabled"
+    } else {
+        "disabled"
+    }
 }
 
 mod std_fmt_impls {
@@ -102,10 +846,298 @@ mod std_fmt_impls {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             write!(
                 f,
-                "Notifier: Desktop = {}, KDE Connect = {}",
+                "Notifier: Desktop = {}, KDE Connect = {}, ntfy = {}, \
+                Gotify = {}, MQTT = {}, Plug = {}, Hook = {}, Email = {}",
                 common::format_option(self.desktop.as_ref()),
                 common::format_option(self.kde_connect.as_ref()),
+                common::format_option(self.ntfy.as_ref()),
+                common::format_option(self.gotify.as_ref()),
+                common::format_option(self.mqtt.as_ref()),
+                common::format_option(self.plug.as_ref()),
+                common::format_option(self.hook.as_ref()),
+                common::format_option(self.email.as_ref()),
             )
         }
     }
 } // std_fmt_impls
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn create_notifier() -> Notifier {
+        Notifier {
+            threshold: 80,
+            snooze: Duration::from_secs(10),
+            snoozed_until: None,
+            muted: false,
+            paused: false,
+            desktop: None,
+            kde_connect: None,
+            ntfy: None,
+            gotify: None,
+            mqtt: None,
+            plug: None,
+            hook: None,
+            email: None,
+            health: HashMap::new(),
+            fallbacks: HashMap::new(),
+            alerts: 0,
+            active: false,
+            records: HashMap::new(),
+            update_change: 5,
+            quiet: QuietHours::new(&create_quiet_options(vec![])),
+        }
+    }
+
+    fn create_quiet_options(windows: Vec<QuietWindow>) -> cli::Quiet {
+        cli::Quiet {
+            windows,
+            mode: QuietMode::Suppress,
+            channels: vec![],
+            desktop_dnd: false,
+        }
+    }
+
+    fn create_ntfy_notifier(url: &str) -> Notifier {
+        let mut notifier = create_notifier();
+        notifier.ntfy = NtfyNotifier::new(cli::Ntfy {
+            url: Some(url.into()),
+            topic: Some("test-topic".into()),
+            token: None,
+            tags: vec![],
+            resolved: false,
+        });
+        notifier.health.insert(
+            Channel::Ntfy,
+            Health::new(Policy {
+                retries: 0,
+                backoff: Duration::ZERO,
+                circuit_failures: 0,
+                circuit_cooldown: Duration::ZERO,
+            }),
+        );
+
+        notifier
+    }
+
+    #[test]
+    fn test_notifier_not_suppressed() {
+        let mut notifier = create_notifier();
+
+        let result = notifier.suppressed();
+
+        assert!(!result);
+    }
+
+    #[test]
+    fn test_notifier_snooze() {
+        let mut notifier = create_notifier();
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        notifier.handle_action(Action::Snooze, &message);
+
+        assert!(notifier.suppressed());
+    }
+
+    #[test]
+    fn test_notifier_snooze_expired() {
+        let mut notifier = create_notifier();
+        notifier.snooze = Duration::ZERO;
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        notifier.handle_action(Action::Snooze, &message);
+
+        assert!(!notifier.suppressed());
+        assert!(notifier.snoozed_until.is_none());
+    }
+
+    #[test]
+    fn test_notifier_snooze_for_too_long() {
+        let mut notifier = create_notifier();
+
+        let result = notifier.snooze_for(Duration::MAX);
+
+        assert!(result.is_err());
+        assert!(notifier.snoozed_until.is_none());
+    }
+
+    #[test]
+    fn test_notifier_dismiss() {
+        let mut notifier = create_notifier();
+        let mut message = Message::test("summary", "body", Urgency::Critical);
+
+        notifier.handle_action(Action::Dismiss, &message);
+        notifier.update(&message);
+        let dismissed = notifier.suppressed();
+        message.percentage = 79;
+        notifier.update(&message);
+
+        assert!(dismissed);
+        assert!(!notifier.suppressed());
+    }
+
+    #[test]
+    fn test_notifier_pause_resume() {
+        let mut notifier = create_notifier();
+        let message = Message::test("summary", "body", Urgency::Critical);
+        notifier.handle_action(Action::Snooze, &message);
+
+        notifier.pause();
+        let paused = notifier.suppressed();
+        notifier.resume();
+
+        assert!(paused);
+        assert!(!notifier.suppressed());
+    }
+
+    #[test]
+    fn test_notifier_test() {
+        let (url, requests) =
+            testing::http_server(vec![(200, "{}".into()), (200, "{}".into())]);
+        let mut notifier = create_ntfy_notifier(&url);
+        let message = Message::test("summary", "body", Urgency::Normal);
+
+        let result = notifier.test(&message);
+        notifier.notify(&message);
+
+        assert_eq!(result, [(Channel::Ntfy, true)]);
+        let titles = requests
+            .iter()
+            .map(|request| {
+                serde_json::from_str::<serde_json::Value>(&request.body)
+                    .unwrap()["title"]
+                    .clone()
+            })
+            .collect::<Vec<serde_json::Value>>();
+        assert_eq!(titles, ["Test notification", "summary"]);
+    }
+
+    #[test]
+    fn test_notifier_notify_deduplicated() {
+        let (url, requests) =
+            testing::http_server(vec![(200, "{}".into()), (200, "{}".into())]);
+        let mut notifier = create_ntfy_notifier(&url);
+        let message = |percentage: u8| {
+            let mut message = Message::test(
+                &format!("{percentage}%"),
+                "body",
+                Urgency::Normal,
+            );
+            message.percentage = percentage;
+
+            message
+        };
+
+        notifier.notify(&message(85));
+        notifier.notify(&message(85));
+        notifier.notify(&message(87));
+        notifier.notify(&message(90));
+
+        let titles = requests
+            .iter()
+            .map(|request| {
+                serde_json::from_str::<serde_json::Value>(&request.body)
+                    .unwrap()["title"]
+                    .clone()
+            })
+            .collect::<Vec<serde_json::Value>>();
+        assert_eq!(titles, ["85%", "Updated: 90%"]);
+        assert_eq!(notifier.records[&Channel::Ntfy].alert, 1);
+    }
+
+    #[test]
+    fn test_notifier_remove_new_alert() {
+        let (url, requests) =
+            testing::http_server(vec![(200, "{}".into()), (200, "{}".into())]);
+        let mut notifier = create_ntfy_notifier(&url);
+        let message = Message::test("summary", "body", Urgency::Normal);
+
+        notifier.notify(&message);
+        notifier.remove();
+        notifier.notify(&message);
+
+        assert_eq!(requests.iter().count(), 2);
+        assert_eq!(notifier.records[&Channel::Ntfy].alert, 2);
+    }
+
+    #[test]
+    fn test_notifier_notify_quiet_hours_deferred() {
+        let (url, requests) = testing::http_server(vec![(200, "{}".into())]);
+        let mut notifier = create_ntfy_notifier(&url);
+        let clock = quiet::FakeClock::new("2026-10-16 23:00");
+        notifier.quiet = QuietHours::with_clock(
+            &create_quiet_options(vec!["22:00-07:00".parse().unwrap()]),
+            Box::new(clock.clone()),
+        );
+        let message = Message::test("summary", "body", Urgency::Normal);
+
+        notifier.notify(&message);
+        let deferred = notifier.records.contains_key(&Channel::Ntfy);
+        clock.advance(8 * 60);
+        notifier.notify(&message);
+
+        assert!(!deferred);
+        assert_eq!(requests.iter().count(), 1);
+        assert_eq!(notifier.records[&Channel::Ntfy].alert, 1);
+    }
+
+    #[test]
+    fn test_notifier_notify_quiet_hours_downgraded() {
+        let (url, requests) =
+            testing::http_server(vec![(200, "{}".into()), (200, "{}".into())]);
+        let mut notifier = create_ntfy_notifier(&url);
+        let clock = quiet::FakeClock::new("2026-10-16 23:00");
+        notifier.quiet = QuietHours::with_clock(
+            &cli::Quiet {
+                channels: vec![(Channel::Ntfy, QuietMode::Downgrade)],
+                ..create_quiet_options(vec!["22:00-07:00".parse().unwrap()])
+            },
+            Box::new(clock.clone()),
+        );
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        notifier.notify(&message);
+        clock.advance(8 * 60);
+        notifier.notify(&message);
+
+        let bodies = requests
+            .iter()
+            .map(|request| {
+                let body =
+                    serde_json::from_str::<serde_json::Value>(&request.body)
+                        .unwrap();
+
+                (body["title"].clone(), body["priority"].clone())
+            })
+            .collect::<Vec<(serde_json::Value, serde_json::Value)>>();
+        assert_eq!(
+            bodies,
+            [
+                ("summary".into(), 2.into()),
+                ("Updated: summary".into(), 5.into())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_notifier_remove_quiet_hours() {
+        let (url, requests) = testing::http_server(vec![(200, "{}".into())]);
+        let mut notifier = create_ntfy_notifier(&url);
+        let clock = quiet::FakeClock::new("2026-10-16 23:00");
+        notifier.quiet = QuietHours::with_clock(
+            &create_quiet_options(vec!["22:00-07:00".parse().unwrap()]),
+            Box::new(clock.clone()),
+        );
+        let message = Message::test("summary", "body", Urgency::Normal);
+
+        notifier.notify(&message);
+        notifier.remove();
+        clock.advance(8 * 60);
+        notifier.notify(&message);
+
+        assert_eq!(requests.iter().count(), 1);
+        assert_eq!(notifier.records[&Channel::Ntfy].alert, 2);
+    }
+} // tests
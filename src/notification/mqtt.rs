@@ -0,0 +1,672 @@
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::{io, result, time};
+
+use serde_json::json;
+
+use crate::cli;
+use crate::error;
+use crate::notification::{Message, PlatformNotifier};
+
+type Result<T> = result::Result<T, error::Mqtt>;
+
+const TIMEOUT: time::Duration = time::Duration::from_secs(10);
+
+/// Payload of the availability topic while connected.
+const ONLINE: &str = "online";
+/// Payload of the availability topic once the connection is lost.
+const OFFLINE: &str = "offline";
+
+/// Topic prefix which Home Assistant listens on for discovery messages.
+const HOME_ASSISTANT_PREFIX: &str = "homeassistant";
+
+/// MQTT Notifier.
+///
+/// Publishes the battery state and alert transitions to an MQTT broker.
+#[derive(Debug)]
+pub struct Notifier {
+    /// Address of the MQTT broker in the `host:port` format.
+    address: String,
+    client_id: String,
+    username: Option<String>,
+    password: Option<String>,
+    keep_alive: u16,
+    topic_prefix: String,
+    /// Whether Home Assistant discovery configuration should be published.
+    home_assistant: bool,
+
+    /// Last published alert state, `None` if it wasn't published yet.
+    alert: Option<bool>,
+    /// Battery state of the latest update.
+    state: Option<serde_json::Value>,
+    /// Whether discovery configuration was published on this connection.
+    discovered: bool,
+    stream: Option<TcpStream>,
+}
+
+impl PlatformNotifier for Notifier {
+    type Error = error::Notification;
+
+    fn notify(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        if self.alert == Some(true) {
+            log::trace!("notification/mqtt: alert already published");
+
+            return Ok(());
+        }
+
+        self.publish_alert(Some(message))?;
+
+        Ok(())
+    }
+
+    fn test(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        let payload = json!({
+            "summary": message.summary,
+            "body": message.body,
+            "urgency": message.urgency.to_string(),
+        });
+
+        self.publish(&self.topic("test"), &payload.to_string(), false)?;
+
+        log::debug!("notification/mqtt: test published");
+
+        Ok(())
+    }
+
+    fn remove(&mut self) -> result::Result<(), Self::Error> {
+        if self.alert == Some(false) {
+            log::trace!("notification/mqtt: remove noop");
+
+            return Ok(());
+        }
+
+        self.publish_alert(None)?;
+
+        Ok(())
+    }
+
+    fn update(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        if self.home_assistant && !self.discovered {
+            self.publish_discovery(message)?;
+        }
+
+        self.state = Some(json!({
+            "percentage": message.percentage,
+            "state": message.state.to_string(),
+            "model": message.model,
+            "threshold": message.threshold,
+        }));
+
+        self.publish_state()?;
+
+        Ok(())
+    }
+}
+
+impl Notifier {
+    /// Create a new `Notifier` instance from the given options.
+    ///
+    /// Return `None` if no MQTT broker host was specified.
+    ///
+    /// Connection to the broker is established lazily on the first publish.
+    pub fn new(options: cli::Mqtt) -> Option<Self> {
+        let cli::Mqtt {
+            host,
+            port,
+            username,
+            password,
+            client_id,
+            topic_prefix,
+            keep_alive,
+            home_assistant,
+        } = options;
+
+        Some(Self {
+            address: format!("{}:{port}", host?),
+            client_id,
+            username,
+            password,
+            keep_alive,
+            topic_prefix: topic_prefix.trim_end_matches('/').to_owned(),
+            home_assistant,
+            alert: None,
+            state: None,
+            discovered: false,
+            stream: None,
+        })
+    }
+
+    /// Return full name of the topic with the given suffix.
+    fn topic(&self, suffix: &str) -> String {
+        format!("{}/{suffix}", self.topic_prefix)
+    }
+
+    /// Publish whether an alert is active along with its `Message`.
+    fn publish_alert(&mut self, message: Option<&Message>) -> Result<()> {
+        let payload = message.map_or_else(
+            || json!({ "active": false }),
+            |message| {
+                json!({
+                    "active": true,
+                    "summary": message.summary,
+                    "body": message.body,
+                    "urgency": message.urgency.to_string(),
+                })
+            },
+        );
+
+        self.publish(&self.topic("alert"), &payload.to_string(), true)?;
+        let previous = self.alert.replace(message.is_some());
+
+        log::debug!("notification/mqtt: alert = {} published", payload);
+
+        // published again by a retry if the state fails
+        self.publish_state().inspect_err(|_| self.alert = previous)
+    }
+
+    /// Publish the battery state of the latest update with the alert state.
+    fn publish_state(&mut self) -> Result<()> {
+        let Some(mut state) = self.state.clone() else {
+            return Ok(());
+        };
+
+        state["alert"] = json!(self.alert.unwrap_or(false));
+
+        self.publish(&self.topic("state"), &state.to_string(), true)?;
+
+        log::debug!("notification/mqtt: state published");
+
+        Ok(())
+    }
+
+    /// Publish Home Assistant MQTT discovery configuration.
+    fn publish_discovery(&mut self, message: &Message) -> Result<()> {
+        let node_id = node_id(&self.client_id);
+        let device = json!({
+            "identifiers": [node_id],
+            "name": self.client_id,
+            "model": message.model,
+            "manufacturer": env!("CARGO_PKG_NAME"),
+            "sw_version": env!("CARGO_PKG_VERSION"),
+        });
+
+        let configs = [
+            (
+                "sensor",
+                "percentage",
+                json!({
+                    "name": "Battery",
+                    "device_class": "battery",
+                    "state_class": "measurement",
+                    "unit_of_measurement": "%",
+                    "value_template": "{{ value_json.percentage }}",
+                }),
+            ),
+            (
+                "binary_sensor",
+                "charging",
+                json!({
+                    "name": "Battery charging",
+                    "device_class": "battery_charging",
+                    "value_template":
+                        "{{ 'ON' if value_json.state == 'Charging' \
+                        else 'OFF' }}",
+                }),
+            ),
+            (
+                "binary_sensor",
+                "alert",
+                json!({
+                    "name": "Battery charge limit",
+                    "device_class": "problem",
+                    "value_template":
+                        "{{ 'ON' if value_json.alert else 'OFF' }}",
+                }),
+            ),
+        ];
+
+        for (component, object_id, mut config) in configs {
+            config["unique_id"] = json!(format!("{node_id}_{object_id}"));
+            config["state_topic"] = json!(self.topic("state"));
+            config["availability_topic"] = json!(self.topic("availability"));
+            config["device"] = device.clone();
+
+            self.publish(
+                &format!(
+                    "{HOME_ASSISTANT_PREFIX}/{component}/{node_id}/\
+                    {object_id}/config"
+                ),
+                &config.to_string(),
+                true,
+            )?;
+        }
+
+        self.discovered = true;
+
+        log::debug!("notification/mqtt: discovery configuration published");
+
+        Ok(())
+    }
+
+    /// Publish the payload to the topic, connecting to the broker if needed.
+    ///
+    /// The connection is dropped on failure so that the next publish
+    /// attempts to reconnect.
+    fn publish(
+        &mut self,
+        topic: &str,
+        payload: &str,
+        retain: bool,
+    ) -> Result<()> {
+        let packet = packet::publish(topic, payload.as_bytes(), retain);
+
+        let result = self
+            .connection()
+            .and_then(|stream| Ok(stream.write_all(&packet)?));
+
+        if result.is_err() {
+            self.stream = None;
+            self.discovered = false;
+        } else {
+            log::trace!("notification/mqtt: published to {topic}");
+        }
+
+        result
+    }
+
+    /// Return the current connection to the broker or establish a new one.
+    fn connection(&mut self) -> Result<&mut TcpStream> {
+        let stream = match self.stream.take() {
+            Some(stream) => stream,
+            None => self.connect()?,
+        };
+
+        Ok(self.stream.insert(stream))
+    }
+
+    /// Connect to the broker and announce availability.
+    ///
+    /// The broker publishes the "offline" last will once the connection
+    /// gets lost without a proper disconnect.
+    fn connect(&self) -> Result<TcpStream> {
+        let address =
+            self.address.to_socket_addrs()?.next().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("failed to resolve {}", self.address),
+                )
+            })?;
+
+        let mut stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+
+        stream.write_all(&packet::connect(
+            &self.client_id,
+            self.keep_alive,
+            (&self.topic("availability"), OFFLINE.as_bytes()),
+            self.username.as_deref(),
+            self.password.as_deref(),
+        ))?;
+
+        let mut connack = [0; 4];
+        stream.read_exact(&mut connack)?;
+
+        match connack {
+            [packet::CONNACK, 2, _, 0] => {}
+            [packet::CONNACK, 2, _, code] => {
+                return Err(error::Mqtt::Refused { code })
+            }
+            [kind, ..] => return Err(error::Mqtt::UnexpectedPacket { kind }),
+        }
+
+        stream.write_all(&packet::publish(
+            &self.topic("availability"),
+            ONLINE.as_bytes(),
+            true,
+        ))?;
+
+        log::info!("notification/mqtt: connected to {}", self.address);
+
+        Ok(stream)
+    }
+}
+
+/// Return Home Assistant node ID created from the given client ID.
+fn node_id(client_id: &str) -> String {
+    client_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Encoding of MQTT 3.1.1 control packets.
+mod packet {
+    pub const CONNECT: u8 = 0x10;
+    pub const CONNACK: u8 = 0x20;
+    pub const PUBLISH: u8 = 0x30;
+
+    const PROTOCOL_NAME: &str = "MQTT";
+    const PROTOCOL_LEVEL: u8 = 4;
+
+    const FLAG_RETAIN: u8 = 0x01;
+    const FLAG_CLEAN_SESSION: u8 = 0x02;
+    const FLAG_WILL: u8 = 0x04;
+    const FLAG_WILL_RETAIN: u8 = 0x20;
+    const FLAG_PASSWORD: u8 = 0x40;
+    const FLAG_USERNAME: u8 = 0x80;
+
+    /// Return a CONNECT packet with a retained last will.
+    pub fn connect(
+        client_id: &str,
+        keep_alive: u16,
+        will: (&str, &[u8]),
+        username: Option<&str>,
+        password: Option<&str>,
+    ) -> Vec<u8> {
+        let mut flags = FLAG_CLEAN_SESSION | FLAG_WILL | FLAG_WILL_RETAIN;
+        if username.is_some() {
+            flags |= FLAG_USERNAME;
+        }
+        if password.is_some() {
+            flags |= FLAG_PASSWORD;
+        }
+
+        let mut body = Vec::new();
+        bytes(&mut body, PROTOCOL_NAME.as_bytes());
+        body.push(PROTOCOL_LEVEL);
+        body.push(flags);
+        body.extend_from_slice(&keep_alive.to_be_bytes());
+        bytes(&mut body, client_id.as_bytes());
+        bytes(&mut body, will.0.as_bytes());
+        bytes(&mut body, will.1);
+        if let Some(username) = username {
+            bytes(&mut body, username.as_bytes());
+        }
+        if let Some(password) = password {
+            bytes(&mut body, password.as_bytes());
+        }
+
+        packet(CONNECT, &body)
+    }
+
+    /// Return a PUBLISH packet with `QoS` 0.
+    pub fn publish(topic: &str, payload: &[u8], retain: bool) -> Vec<u8> {
+        let mut body = Vec::with_capacity(topic.len() + payload.len() + 2);
+        bytes(&mut body, topic.as_bytes());
+        body.extend_from_slice(payload);
+
+        packet(
+            if retain {
+                PUBLISH | FLAG_RETAIN
+            } else {
+                PUBLISH
+            },
+            &body,
+        )
+    }
+
+    /// Return a packet with the given fixed header byte and body.
+    fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+        let mut packet = vec![header];
+        packet.extend(remaining_length(body.len()));
+        packet.extend_from_slice(body);
+
+        packet
+    }
+
+    /// Encode remaining length of a packet as a variable byte integer.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn remaining_length(mut length: usize) -> Vec<u8> {
+        let mut encoded = Vec::with_capacity(4);
+
+        loop {
+            let mut byte = (length % 128) as u8;
+            length /= 128;
+
+            if length > 0 {
+                byte |= 0x80;
+            }
+
+            encoded.push(byte);
+
+            if length == 0 {
+                return encoded;
+            }
+        }
+    }
+
+    /// Append length prefixed bytes to the buffer.
+    #[allow(clippy::cast_possible_truncation)]
+    fn bytes(buffer: &mut Vec<u8>, data: &[u8]) {
+        buffer.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        buffer.extend_from_slice(data);
+    }
+} // packet
+
+mod std_fmt_impls {
+    use std::fmt;
+
+    use super::Notifier;
+
+    impl fmt::Display for Notifier {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "MQTT Notifier: address = {}, topic_prefix = {}",
+                self.address, self.topic_prefix,
+            )
+        }
+    }
+} // std_fmt_impls
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use serde_json::Value;
+
+    use crate::notification::Urgency;
+    use crate::testing::{self, MqttPacket};
+
+    use super::*;
+
+    fn create_notifier(address: &str, home_assistant: bool) -> Notifier {
+        let (host, port) = address.split_once(':').unwrap();
+
+        Notifier::new(cli::Mqtt {
+            host: Some(host.into()),
+            port: port.parse().unwrap(),
+            username: Some("test-user".into()),
+            password: Some("test-password".into()),
+            client_id: "test-client".into(),
+            topic_prefix: "test/".into(),
+            keep_alive: 60,
+            home_assistant,
+        })
+        .unwrap()
+    }
+
+    /// Receive the next PUBLISH packet and return its topic and payload.
+    fn recv_publish(packets: &mpsc::Receiver<MqttPacket>) -> (String, Value) {
+        let packet = packets.recv_timeout(TIMEOUT).unwrap();
+        let (topic, payload) = packet.publish();
+
+        (
+            topic,
+            serde_json::from_str(&payload).unwrap_or(Value::String(payload)),
+        )
+    }
+
+    #[test]
+    fn test_notifier_new_without_host() {
+        let notifier = Notifier::new(cli::Mqtt {
+            host: None,
+            port: 1883,
+            username: None,
+            password: None,
+            client_id: "test-client".into(),
+            topic_prefix: "test".into(),
+            keep_alive: 60,
+            home_assistant: false,
+        });
+
+        assert!(notifier.is_none());
+    }
+
+    #[test]
+    fn test_notifier_display() {
+        let notifier = create_notifier("localhost:1883", false);
+
+        let result = notifier.to_string();
+
+        assert_eq!(
+            result,
+            "MQTT Notifier: address = localhost:1883, topic_prefix = test"
+        );
+    }
+
+    #[test]
+    fn test_notifier_update() {
+        let (address, packets) = testing::mqtt_broker(0);
+        let mut notifier = create_notifier(&address, false);
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        let result = notifier.update(&message);
+
+        assert!(result.is_ok());
+        let connect = packets.recv_timeout(TIMEOUT).unwrap();
+        assert_eq!(connect.kind, packet::CONNECT);
+        assert!(connect.contains(b"test/availability"));
+        assert!(connect.contains(OFFLINE.as_bytes()));
+        assert!(connect.contains(b"test-user"));
+        assert!(connect.contains(b"test-password"));
+        let packet = packets.recv_timeout(TIMEOUT).unwrap();
+        assert!(packet.retain());
+        assert_eq!(
+            packet.publish(),
+            ("test/availability".into(), ONLINE.into())
+        );
+        let (topic, state) = recv_publish(&packets);
+        assert_eq!(topic, "test/state");
+        assert_eq!(state["percentage"], 85);
+        assert_eq!(state["state"], "Charging");
+        assert_eq!(state["model"], "test-model");
+        assert_eq!(state["threshold"], 80);
+        assert_eq!(state["alert"], false);
+    }
+
+    #[test]
+    fn test_notifier_update_home_assistant() {
+        let (address, packets) = testing::mqtt_broker(0);
+        let mut notifier = create_notifier(&address, true);
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        notifier.update(&message).unwrap();
+
+        packets.recv_timeout(TIMEOUT).unwrap();
+        recv_publish(&packets);
+        let (topic, config) = recv_publish(&packets);
+        assert_eq!(topic, "homeassistant/sensor/test_client/percentage/config");
+        assert_eq!(config["unique_id"], "test_client_percentage");
+        assert_eq!(config["state_topic"], "test/state");
+        assert_eq!(config["availability_topic"], "test/availability");
+        assert_eq!(config["device"]["model"], "test-model");
+        let (topic, _) = recv_publish(&packets);
+        assert_eq!(
+            topic,
+            "homeassistant/binary_sensor/test_client/charging/config"
+        );
+        let (topic, _) = recv_publish(&packets);
+        assert_eq!(
+            topic,
+            "homeassistant/binary_sensor/test_client/alert/config"
+        );
+        let (topic, _) = recv_publish(&packets);
+        assert_eq!(topic, "test/state");
+    }
+
+    #[test]
+    fn test_notifier_notify_and_remove() {
+        let (address, packets) = testing::mqtt_broker(0);
+        let mut notifier = create_notifier(&address, false);
+        let message = Message::test("summary", "body", Urgency::Normal);
+
+        notifier.notify(&message).unwrap();
+        notifier.notify(&message).unwrap();
+        notifier.remove().unwrap();
+        notifier.remove().unwrap();
+        notifier.update(&message).unwrap();
+
+        packets.recv_timeout(TIMEOUT).unwrap();
+        recv_publish(&packets);
+        let (topic, alert) = recv_publish(&packets);
+        assert_eq!(topic, "test/alert");
+        assert_eq!(alert["active"], true);
+        assert_eq!(alert["summary"], "summary");
+        assert_eq!(alert["urgency"], "Normal");
+        let (topic, alert) = recv_publish(&packets);
+        assert_eq!(topic, "test/alert");
+        assert_eq!(alert["active"], false);
+        let (topic, _) = recv_publish(&packets);
+        assert_eq!(topic, "test/state");
+    }
+
+    #[test]
+    fn test_notifier_update_and_notify() {
+        let (address, packets) = testing::mqtt_broker(0);
+        let mut notifier = create_notifier(&address, false);
+        let message = Message::test("summary", "body", Urgency::Normal);
+
+        notifier.update(&message).unwrap();
+        notifier.notify(&message).unwrap();
+
+        packets.recv_timeout(TIMEOUT).unwrap();
+        recv_publish(&packets);
+        let (_, state) = recv_publish(&packets);
+        assert_eq!(state["alert"], false);
+        let (topic, _) = recv_publish(&packets);
+        assert_eq!(topic, "test/alert");
+        let (topic, state) = recv_publish(&packets);
+        assert_eq!(topic, "test/state");
+        assert_eq!(state["alert"], true);
+    }
+
+    #[test]
+    fn test_notifier_connection_refused() {
+        let (address, _packets) = testing::mqtt_broker(5);
+        let mut notifier = create_notifier(&address, false);
+
+        let result = notifier.remove();
+
+        assert!(matches!(
+            result,
+            Err(error::Notification::Mqtt(error::Mqtt::Refused { code: 5 }))
+        ));
+        assert!(notifier.stream.is_none());
+        assert!(notifier.alert.is_none());
+    }
+
+    #[test]
+    fn test_node_id() {
+        let result = node_id("rusty-battery@laptop");
+
+        assert_eq!(result, "rusty_battery_laptop");
+    }
+
+    #[test]
+    fn test_packet_remaining_length() {
+        assert_eq!(packet::remaining_length(0), [0x00]);
+        assert_eq!(packet::remaining_length(127), [0x7f]);
+        assert_eq!(packet::remaining_length(128), [0x80, 0x01]);
+        assert_eq!(packet::remaining_length(16_383), [0xff, 0x7f]);
+        assert_eq!(packet::remaining_length(16_384), [0x80, 0x80, 0x01]);
+    }
+
+    #[test]
+    fn test_packet_publish() {
+        let result = packet::publish("a/b", b"xy", true);
+
+        assert_eq!(result, [0x31, 7, 0, 3, b'a', b'/', b'b', b'x', b'y']);
+    }
+} // tests
@@ -0,0 +1,274 @@
+use std::result;
+
+use serde_json::json;
+
+use crate::cli;
+use crate::error;
+use crate::notification::{http, message, Message, PlatformNotifier, Urgency};
+
+type Result<T> = result::Result<T, error::Notification>;
+
+/// Tag of messages announcing a resolved alert.
+const RESOLVED_TAG: &str = "white_check_mark";
+
+/// ntfy Notifier.
+///
+/// Messages are published via the JSON API of the ntfy server.
+#[derive(Debug)]
+pub struct Notifier {
+    /// URL of the ntfy server.
+    url: String,
+    /// Topic which messages are published to.
+    topic: String,
+    /// Access token used for authorization.
+    token: Option<String>,
+    /// Tags attached to every alert.
+    tags: Vec<String>,
+    /// Whether a message should be published when the alert is removed.
+    resolved: bool,
+    /// Summary of the last published alert which hasn't been removed yet.
+    active: Option<String>,
+
+    agent: ureq::Agent,
+}
+
+impl PlatformNotifier for Notifier {
+    type Error = error::Notification;
+
+    fn notify(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        self.publish(
+            &message.summary,
+            &message.body,
+            message.urgency,
+            &self.tags,
+        )?;
+
+        self.active = Some(message.summary.clone());
+
+        Ok(())
+    }
+
+    fn test(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        self.publish(
+            &message.summary,
+            &message.body,
+            message.urgency,
+            &self.tags,
+        )?;
+
+        Ok(())
+    }
+
+    fn remove(&mut self) -> result::Result<(), Self::Error> {
+        match self.active.take() {
+            Some(summary) if self.resolved => {
+                self.publish(
+                    &message::resolved_summary(&summary),
+                    &message::resolved_body(),
+                    Urgency::Low,
+                    &[RESOLVED_TAG.into()],
+                )?;
+
+                log::debug!("notification/ntfy: resolved message published");
+            }
+            _ => log::trace!("notification/ntfy: remove noop"),
+        }
+
+        Ok(())
+    }
+}
+
+impl Notifier {
+    /// Create a new `Notifier` instance from the given options.
+    ///
+    /// Return `None` if no ntfy server URL was specified.
+    pub fn new(options: cli::Ntfy) -> Option<Self> {
+        let cli::Ntfy {
+            url,
+            topic,
+            token,
+            tags,
+            resolved,
+        } = options;
+
+        Some(Self {
+            url: http::trim_url(&url?),
+            topic: topic?,
+            token,
+            tags,
+            resolved,
+            active: None,
+            agent: http::agent(),
+        })
+    }
+
+    /// Publish a message with the given contents to the ntfy topic.
+    fn publish(
+        &self,
+        title: &str,
+        message: &str,
+        urgency: Urgency,
+        tags: &[String],
+    ) -> Result<()> {
+        let mut request = self.agent.post(&self.url);
+
+        if let Some(token) = &self.token {
+            request = request.set("Authorization", &format!("Bearer {token}"));
+        }
+
+        request
+            .send_json(json!({
+                "topic": self.topic,
+                "title": title,
+                "message": message,
+                "priority": priority(urgency),
+                "tags": tags,
+            }))
+            .map_err(Box::new)?;
+
+        log::debug!("notification/ntfy: published to topic {}", self.topic);
+
+        Ok(())
+    }
+}
+
+/// Map `Urgency` to the ntfy message priority.
+const fn priority(urgency: Urgency) -> u8 {
+    http::priority(urgency, 2, 3, 5)
+}
+
+mod std_fmt_impls {
+    use std::fmt;
+
+    use super::Notifier;
+
+    impl fmt::Display for Notifier {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "ntfy Notifier: url = {}, topic = {}",
+                self.url, self.topic,
+            )
+        }
+    }
+} // std_fmt_impls
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::testing;
+
+    use super::*;
+
+    fn create_notifier(url: &str, resolved: bool) -> Notifier {
+        Notifier::new(cli::Ntfy {
+            url: Some(url.into()),
+            topic: Some("test-topic".into()),
+            token: Some("test-token".into()),
+            tags: vec!["battery".into()],
+            resolved,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_notifier_new_without_url() {
+        let notifier = Notifier::new(cli::Ntfy {
+            url: None,
+            topic: None,
+            token: None,
+            tags: vec![],
+            resolved: false,
+        });
+
+        assert!(notifier.is_none());
+    }
+
+    #[test]
+    fn test_notifier_display() {
+        let notifier = create_notifier("http://localhost/", false);
+
+        let result = notifier.to_string();
+
+        assert_eq!(
+            result,
+            "ntfy Notifier: url = http://localhost, topic = test-topic"
+        );
+    }
+
+    #[test]
+    fn test_notifier_notify() {
+        let (url, requests) = testing::http_server(vec![(200, "{}".into())]);
+        let mut notifier = create_notifier(&url, false);
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        let result = notifier.notify(&message);
+
+        assert!(result.is_ok());
+        let request = requests.recv().unwrap();
+        let body: Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.header("Authorization"), Some("Bearer test-token"));
+        assert_eq!(body["topic"], "test-topic");
+        assert_eq!(body["title"], "summary");
+        assert_eq!(body["message"], "body");
+        assert_eq!(body["priority"], 5);
+        assert_eq!(body["tags"][0], "battery");
+    }
+
+    #[test]
+    fn test_notifier_notify_error_status() {
+        let (url, _requests) = testing::http_server(vec![(403, "{}".into())]);
+        let mut notifier = create_notifier(&url, false);
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        let result = notifier.notify(&message);
+
+        assert!(matches!(result, Err(error::Notification::Http(_))));
+        assert!(notifier.active.is_none());
+    }
+
+    #[test]
+    fn test_notifier_remove_resolved() {
+        let (url, requests) =
+            testing::http_server(vec![(200, "{}".into()), (200, "{}".into())]);
+        let mut notifier = create_notifier(&url, true);
+        let message = Message::test("summary", "body", Urgency::Normal);
+
+        notifier.notify(&message).unwrap();
+        let result = notifier.remove();
+
+        assert!(result.is_ok());
+        let body: Value =
+            serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(body["priority"], 3);
+        let body: Value =
+            serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(body["title"], "Resolved: summary");
+        assert_eq!(body["priority"], 2);
+        assert_eq!(body["tags"][0], RESOLVED_TAG);
+    }
+
+    #[test]
+    fn test_notifier_remove_without_alert() {
+        let mut notifier = create_notifier("http://127.0.0.1:9", true);
+
+        let result = notifier.remove();
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_notifier_remove_resolved_disabled() {
+        let (url, _requests) = testing::http_server(vec![(200, "{}".into())]);
+        let mut notifier = create_notifier(&url, false);
+        let message = Message::test("summary", "body", Urgency::Low);
+
+        notifier.notify(&message).unwrap();
+        let result = notifier.remove();
+
+        assert!(result.is_ok());
+        assert!(notifier.active.is_none());
+    }
+} // tests
@@ -1,13 +1,40 @@
 use crate::common;
-use crate::notification::{Message, PlatformNotifier};
+use crate::notification::{Channel, Health, Message, PlatformNotifier};
 
-pub(super) fn notify<N>(notifier: &mut Option<N>, message: &Message)
+/// Deliver the `Message` via the notifier of the `Channel`.
+///
+/// Return `None` if the notifier isn't enabled,
+/// otherwise whether the `Message` was delivered.
+pub(super) fn notify<N>(
+    channel: Channel,
+    notifier: &mut Option<N>,
+    health: &mut Health,
+    message: &Message,
+) -> Option<bool>
 where
     N: PlatformNotifier,
 {
-    if let Some(notifier) = notifier {
-        common::warn_on_err("notification", notifier.notify(message));
-    }
+    notifier
+        .as_mut()
+        .map(|notifier| health.deliver(channel, || notifier.notify(message)))
+}
+
+/// Deliver the test `Message` via the notifier of the `Channel`.
+///
+/// Return `None` if the notifier isn't enabled,
+/// otherwise whether the `Message` was delivered.
+pub(super) fn test<N>(
+    channel: Channel,
+    notifier: &mut Option<N>,
+    health: &mut Health,
+    message: &Message,
+) -> Option<bool>
+where
+    N: PlatformNotifier,
+{
+    notifier
+        .as_mut()
+        .map(|notifier| health.deliver(channel, || notifier.test(message)))
 }
 
 pub(super) fn remove<N>(notifier: &mut Option<N>)
@@ -18,3 +45,21 @@ where
         common::warn_on_err("notification", notifier.remove());
     }
 }
+
+pub(super) fn repeat<N>(notifier: &mut Option<N>, message: &Message)
+where
+    N: PlatformNotifier,
+{
+    if let Some(notifier) = notifier {
+        common::warn_on_err("notification", notifier.repeat(message));
+    }
+}
+
+pub(super) fn update<N>(notifier: &mut Option<N>, message: &Message)
+where
+    N: PlatformNotifier,
+{
+    if let Some(notifier) = notifier {
+        common::warn_on_err("notification", notifier.update(message));
+    }
+}
@@ -0,0 +1,213 @@
+//! TP-Link Kasa local protocol.
+//!
+//! JSON commands are sent over TCP, obfuscated with an XOR autokey cipher
+//! and prefixed with their big endian length.
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::{io, time};
+
+use serde_json::json;
+
+use super::{field, Result};
+
+use crate::error;
+
+const PORT: u16 = 9999;
+const KEY: u8 = 171;
+const TIMEOUT: time::Duration = time::Duration::from_secs(10);
+/// Maximum length of a response, the plugs answer with a few kilobytes.
+const MAX_RESPONSE: u32 = 64 * 1024;
+
+/// Switch the relay of the plug.
+pub fn set(host: &str, on: bool) -> Result<()> {
+    let response = request(
+        host,
+        &json!({"system": {"set_relay_state": {"state": u8::from(on)}}}),
+    )?;
+
+    match field(&response, "/system/set_relay_state/err_code")?.as_i64() {
+        Some(0) => Ok(()),
+        _ => Err(error::Plug::Response {
+            response: response.to_string(),
+        }),
+    }
+}
+
+/// Return whether the relay of the plug is on.
+pub fn state(host: &str) -> Result<bool> {
+    let response = request(host, &json!({"system": {"get_sysinfo": {}}}))?;
+
+    match field(&response, "/system/get_sysinfo/relay_state")?.as_u64() {
+        Some(0) => Ok(false),
+        Some(1) => Ok(true),
+        _ => Err(error::Plug::Response {
+            response: response.to_string(),
+        }),
+    }
+}
+
+/// Send the command to the plug and return its response.
+fn request(
+    host: &str,
+    command: &serde_json::Value,
+) -> Result<serde_json::Value> {
+    let address = if host.contains(':') {
+        host.to_owned()
+    } else {
+        format!("{host}:{PORT}")
+    };
+
+    log::trace!("notification/plug/kasa: sending {command} to {address}");
+
+    let address = address.to_socket_addrs()?.next().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("failed to resolve {address}"),
+        )
+    })?;
+
+    let mut stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    stream.write_all(&encrypt(command.to_string().as_bytes()))?;
+
+    let mut length = [0; 4];
+    stream.read_exact(&mut length)?;
+    let length = u32::from_be_bytes(length);
+    if length > MAX_RESPONSE {
+        return Err(error::Plug::ResponseLength { length });
+    }
+
+    let mut payload = vec![0; length as usize];
+    stream.read_exact(&mut payload)?;
+
+    Ok(serde_json::from_slice(&decrypt(&payload))?)
+}
+
+/// Return the length prefixed and encrypted data.
+#[allow(clippy::cast_possible_truncation)]
+pub fn encrypt(data: &[u8]) -> Vec<u8> {
+    let mut encrypted = Vec::with_capacity(data.len() + 4);
+    encrypted.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+    let mut key = KEY;
+    for byte in data {
+        key ^= byte;
+        encrypted.push(key);
+    }
+
+    encrypted
+}
+
+/// Return the decrypted data without a length prefix.
+pub fn decrypt(data: &[u8]) -> Vec<u8> {
+    let mut key = KEY;
+
+    data.iter()
+        .map(|byte| {
+            let decrypted = key ^ byte;
+            key = *byte;
+
+            decrypted
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+
+    /// Start a stand-in Kasa plug on localhost.
+    ///
+    /// Every connection is answered with the next response from `responses`.
+    ///
+    /// Return the `host:port` address of the plug.
+    fn kasa_plug(responses: Vec<serde_json::Value>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+
+                let mut length = [0; 4];
+                stream.read_exact(&mut length).unwrap();
+                let mut payload = vec![0; u32::from_be_bytes(length) as usize];
+                stream.read_exact(&mut payload).unwrap();
+                serde_json::from_slice::<serde_json::Value>(&decrypt(&payload))
+                    .unwrap();
+
+                stream
+                    .write_all(&encrypt(response.to_string().as_bytes()))
+                    .unwrap();
+            }
+        });
+
+        address
+    }
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let data = br#"{"system":{"get_sysinfo":{}}}"#;
+
+        let encrypted = encrypt(data);
+
+        assert_eq!(&encrypted[..4], &[0, 0, 0, 29]);
+        assert_eq!(encrypted[4], KEY ^ b'{');
+        assert_eq!(decrypt(&encrypted[4..]), data);
+    }
+
+    #[test]
+    fn test_set() {
+        let address = kasa_plug(vec![
+            json!({"system": {"set_relay_state": {"err_code": 0}}}),
+        ]);
+
+        let result = set(&address, false);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_set_error_code() {
+        let address = kasa_plug(vec![
+            json!({"system": {"set_relay_state": {"err_code": -1}}}),
+        ]);
+
+        let result = set(&address, true);
+
+        assert!(matches!(result, Err(error::Plug::Response { .. })));
+    }
+
+    #[test]
+    fn test_state() {
+        let address = kasa_plug(vec![
+            json!({"system": {"get_sysinfo": {"relay_state": 1}}}),
+        ]);
+
+        let result = state(&address);
+
+        assert!(matches!(result, Ok(true)));
+    }
+
+    #[test]
+    fn test_state_response_too_long() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(&u32::MAX.to_be_bytes()).unwrap();
+        });
+
+        let result = state(&address);
+
+        assert!(matches!(
+            result,
+            Err(error::Plug::ResponseLength { length: u32::MAX })
+        ));
+    }
+} // tests
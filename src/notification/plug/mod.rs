@@ -0,0 +1,329 @@
+//! Smart plug which powers the charger.
+//!
+//! Instead of asking the user to unplug the charger, the plug is switched off
+//! once the alert is raised and switched back on once the battery discharges
+//! under a lower threshold.
+use std::result;
+
+use crate::cli;
+use crate::error;
+use crate::notification::{http, Message, PlatformNotifier};
+
+mod kasa;
+mod shelly;
+mod tasmota;
+
+type Result<T> = result::Result<T, error::Plug>;
+
+/// Supported smart plug protocols.
+#[derive(clap::ValueEnum, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Kind {
+    /// Tasmota firmware via its HTTP API
+    Tasmota,
+    /// Shelly Gen1 device via its local HTTP API
+    Shelly,
+    /// Shelly Gen2 (Plus, Pro) device via its local RPC API
+    ShellyGen2,
+    /// TP-Link Kasa device via its local protocol
+    Kasa,
+}
+
+/// Smart plug Notifier.
+#[derive(Debug)]
+pub struct Notifier {
+    kind: Kind,
+    /// Address of the plug, HTTP based plugs use an URL.
+    host: String,
+    /// Battery percentage under which the plug is switched back on.
+    on_threshold: u8,
+    /// Whether the plug has been switched off by this notifier.
+    off: bool,
+
+    agent: ureq::Agent,
+}
+
+impl PlatformNotifier for Notifier {
+    type Error = error::Notification;
+
+    fn notify(
+        &mut self,
+        _message: &Message,
+    ) -> result::Result<(), Self::Error> {
+        if self.off {
+            // battery is still charging even though the plug should be off
+            return Err(error::Notification::from(error::Plug::StillCharging));
+        }
+
+        self.switch(false)?;
+        self.off = true;
+
+        log::info!("notification/plug: charger switched off");
+
+        Ok(())
+    }
+
+    fn remove(&mut self) -> result::Result<(), Self::Error> {
+        log::trace!("notification/plug: remove noop");
+
+        Ok(())
+    }
+
+    fn update(&mut self, message: &Message) -> result::Result<(), Self::Error> {
+        if self.off && message.percentage <= self.on_threshold {
+            self.restore()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Notifier {
+    /// Create a new `Notifier` instance from the given options.
+    ///
+    /// Return `None` if no smart plug was specified.
+    pub fn new(options: cli::Plug) -> Option<Self> {
+        let cli::Plug {
+            kind,
+            host,
+            on_threshold,
+        } = options;
+
+        let kind = kind?;
+        let host = host?;
+
+        Some(Self {
+            kind,
+            host: match kind {
+                Kind::Kasa => host,
+                Kind::Tasmota | Kind::Shelly | Kind::ShellyGen2 => url(&host),
+            },
+            on_threshold,
+            off: false,
+            agent: http::agent(),
+        })
+    }
+
+    /// Return battery percentage under which the plug is switched back on.
+    pub const fn on_threshold(&self) -> u8 {
+        self.on_threshold
+    }
+
+    /// Switch the plug back on if it has been switched off.
+    pub fn restore(&mut self) -> Result<()> {
+        if self.off {
+            self.switch(true)?;
+            self.off = false;
+
+            log::info!("notification/plug: charger switched on");
+        }
+
+        Ok(())
+    }
+
+    /// Switch the plug and verify that it reports the new state.
+    fn switch(&self, on: bool) -> Result<()> {
+        log::debug!("notification/plug: switching {self} to on = {on}");
+
+        match self.kind {
+            Kind::Tasmota => tasmota::set(&self.agent, &self.host, on)?,
+            Kind::Shelly => shelly::set(&self.agent, &self.host, on)?,
+            Kind::ShellyGen2 => shelly::set_gen2(&self.agent, &self.host, on)?,
+            Kind::Kasa => kasa::set(&self.host, on)?,
+        }
+
+        let state = match self.kind {
+            Kind::Tasmota => tasmota::state(&self.agent, &self.host)?,
+            Kind::Shelly => shelly::state(&self.agent, &self.host)?,
+            Kind::ShellyGen2 => shelly::state_gen2(&self.agent, &self.host)?,
+            Kind::Kasa => kasa::state(&self.host)?,
+        };
+
+        if state == on {
+            Ok(())
+        } else {
+            Err(error::Plug::Verification { expected: on })
+        }
+    }
+}
+
+/// Return URL of the given host, `http` is used if the scheme is missing.
+fn url(host: &str) -> String {
+    if host.contains("://") {
+        http::trim_url(host)
+    } else {
+        http::trim_url(&format!("http://{host}"))
+    }
+}
+
+/// Return the value of the JSON response or an error if it's missing.
+fn field<'a>(
+    response: &'a serde_json::Value,
+    pointer: &str,
+) -> Result<&'a serde_json::Value> {
+    response
+        .pointer(pointer)
+        .ok_or_else(|| error::Plug::Response {
+            response: response.to_string(),
+        })
+}
+
+mod std_fmt_impls {
+    use std::fmt;
+
+    use super::{Kind, Notifier};
+
+    impl fmt::Display for Kind {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Tasmota => write!(f, "Tasmota"),
+                Self::Shelly => write!(f, "Shelly"),
+                Self::ShellyGen2 => write!(f, "Shelly Gen2"),
+                Self::Kasa => write!(f, "Kasa"),
+            }
+        }
+    }
+
+    impl fmt::Display for Notifier {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "Plug Notifier: kind = {}, host = {}",
+                self.kind, self.host,
+            )
+        }
+    }
+} // std_fmt_impls
+
+#[cfg(test)]
+mod tests {
+    use crate::notification::Urgency;
+    use crate::testing;
+
+    use super::*;
+
+    const OFF: &str = r#"{"POWER":"OFF"}"#;
+    const ON: &str = r#"{"POWER":"ON"}"#;
+
+    fn create_notifier(host: &str) -> Notifier {
+        Notifier::new(cli::Plug {
+            kind: Some(Kind::Tasmota),
+            host: Some(host.into()),
+            on_threshold: 40,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_notifier_new_without_kind() {
+        let notifier = Notifier::new(cli::Plug {
+            kind: None,
+            host: None,
+            on_threshold: 40,
+        });
+
+        assert!(notifier.is_none());
+    }
+
+    #[test]
+    fn test_notifier_display() {
+        let notifier = create_notifier("192.168.1.2");
+
+        let result = notifier.to_string();
+
+        assert_eq!(
+            result,
+            "Plug Notifier: kind = Tasmota, host = http://192.168.1.2"
+        );
+    }
+
+    #[test]
+    fn test_notifier_notify() {
+        let (url, requests) =
+            testing::http_server(vec![(200, OFF.into()), (200, OFF.into())]);
+        let mut notifier = create_notifier(&url);
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        let result = notifier.notify(&message);
+
+        assert!(result.is_ok());
+        assert!(notifier.off);
+        assert_eq!(requests.recv().unwrap().path, "/cm?cmnd=Power+Off");
+        assert_eq!(requests.recv().unwrap().path, "/cm?cmnd=Power");
+    }
+
+    #[test]
+    fn test_notifier_notify_verification_failure() {
+        let (url, _requests) =
+            testing::http_server(vec![(200, OFF.into()), (200, ON.into())]);
+        let mut notifier = create_notifier(&url);
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        let result = notifier.notify(&message);
+
+        assert!(matches!(
+            result,
+            Err(error::Notification::Plug(error::Plug::Verification {
+                expected: false
+            }))
+        ));
+        assert!(!notifier.off);
+    }
+
+    #[test]
+    fn test_notifier_notify_still_charging() {
+        let mut notifier = create_notifier("127.0.0.1:9");
+        notifier.off = true;
+        let message = Message::test("summary", "body", Urgency::Critical);
+
+        let result = notifier.notify(&message);
+
+        assert!(matches!(
+            result,
+            Err(error::Notification::Plug(error::Plug::StillCharging))
+        ));
+    }
+
+    #[test]
+    fn test_notifier_update_above_on_threshold() {
+        let mut notifier = create_notifier("127.0.0.1:9");
+        notifier.off = true;
+        let mut message = Message::test("summary", "body", Urgency::Critical);
+        message.percentage = 41;
+
+        let result = notifier.update(&message);
+
+        assert!(result.is_ok());
+        assert!(notifier.off);
+    }
+
+    #[test]
+    fn test_notifier_update_under_on_threshold() {
+        let (url, requests) =
+            testing::http_server(vec![(200, ON.into()), (200, ON.into())]);
+        let mut notifier = create_notifier(&url);
+        notifier.off = true;
+        let mut message = Message::test("summary", "body", Urgency::Critical);
+        message.percentage = 40;
+
+        let result = notifier.update(&message);
+
+        assert!(result.is_ok());
+        assert!(!notifier.off);
+        assert_eq!(requests.recv().unwrap().path, "/cm?cmnd=Power+On");
+    }
+
+    #[test]
+    fn test_notifier_restore_not_off() {
+        let mut notifier = create_notifier("127.0.0.1:9");
+
+        let result = notifier.restore();
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_url() {
+        assert_eq!(url("10.0.0.2"), "http://10.0.0.2");
+        assert_eq!(url("https://plug.local/"), "https://plug.local");
+    }
+} // tests
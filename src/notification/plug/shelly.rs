@@ -0,0 +1,132 @@
+//! Shelly local HTTP APIs.
+use super::{field, Result};
+
+use crate::error;
+
+/// Switch the relay of a Gen1 plug.
+pub fn set(agent: &ureq::Agent, url: &str, on: bool) -> Result<()> {
+    get(
+        agent,
+        &format!("{url}/relay/0"),
+        &[("turn", if on { "on" } else { "off" })],
+    )?;
+
+    Ok(())
+}
+
+/// Return whether the relay of a Gen1 plug is on.
+pub fn state(agent: &ureq::Agent, url: &str) -> Result<bool> {
+    let response = get(agent, &format!("{url}/relay/0"), &[])?;
+
+    bool_field(&response, "/ison")
+}
+
+/// Switch the output of a Gen2 plug.
+pub fn set_gen2(agent: &ureq::Agent, url: &str, on: bool) -> Result<()> {
+    get(
+        agent,
+        &format!("{url}/rpc/Switch.Set"),
+        &[("id", "0"), ("on", if on { "true" } else { "false" })],
+    )?;
+
+    Ok(())
+}
+
+/// Return whether the output of a Gen2 plug is on.
+pub fn state_gen2(agent: &ureq::Agent, url: &str) -> Result<bool> {
+    let response = get(
+        agent,
+        &format!("{url}/rpc/Switch.GetStatus"),
+        &[("id", "0")],
+    )?;
+
+    bool_field(&response, "/output")
+}
+
+/// Send a GET request and return its JSON response.
+fn get(
+    agent: &ureq::Agent,
+    url: &str,
+    query: &[(&str, &str)],
+) -> Result<serde_json::Value> {
+    log::trace!("notification/plug/shelly: requesting {url}");
+
+    Ok(agent
+        .get(url)
+        .query_pairs(query.iter().copied())
+        .call()
+        .map_err(Box::new)?
+        .into_json()?)
+}
+
+/// Return the boolean value of the JSON response.
+fn bool_field(response: &serde_json::Value, pointer: &str) -> Result<bool> {
+    field(response, pointer)?
+        .as_bool()
+        .ok_or_else(|| error::Plug::Response {
+            response: response.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing;
+
+    use super::*;
+
+    #[test]
+    fn test_set() {
+        let (url, requests) =
+            testing::http_server(vec![(200, r#"{"ison":false}"#.into())]);
+
+        let result = set(&ureq::agent(), &url, false);
+
+        assert!(result.is_ok());
+        assert_eq!(requests.recv().unwrap().path, "/relay/0?turn=off");
+    }
+
+    #[test]
+    fn test_state() {
+        let (url, _requests) =
+            testing::http_server(vec![(200, r#"{"ison":true}"#.into())]);
+
+        let result = state(&ureq::agent(), &url);
+
+        assert!(matches!(result, Ok(true)));
+    }
+
+    #[test]
+    fn test_set_gen2() {
+        let (url, requests) =
+            testing::http_server(vec![(200, r#"{"was_on":true}"#.into())]);
+
+        let result = set_gen2(&ureq::agent(), &url, false);
+
+        assert!(result.is_ok());
+        assert_eq!(
+            requests.recv().unwrap().path,
+            "/rpc/Switch.Set?id=0&on=false"
+        );
+    }
+
+    #[test]
+    fn test_state_gen2() {
+        let (url, requests) =
+            testing::http_server(vec![(200, r#"{"output":false}"#.into())]);
+
+        let result = state_gen2(&ureq::agent(), &url);
+
+        assert!(matches!(result, Ok(false)));
+        assert_eq!(requests.recv().unwrap().path, "/rpc/Switch.GetStatus?id=0");
+    }
+
+    #[test]
+    fn test_state_gen2_invalid_response() {
+        let (url, _requests) =
+            testing::http_server(vec![(200, r#"{"output":"on"}"#.into())]);
+
+        let result = state_gen2(&ureq::agent(), &url);
+
+        assert!(matches!(result, Err(error::Plug::Response { .. })));
+    }
+} // tests
@@ -0,0 +1,69 @@
+//! Tasmota HTTP API.
+use super::{field, Result};
+
+use crate::error;
+
+/// Switch the relay of the plug.
+pub fn set(agent: &ureq::Agent, url: &str, on: bool) -> Result<()> {
+    power(agent, url, if on { "Power On" } else { "Power Off" })?;
+
+    Ok(())
+}
+
+/// Return whether the relay of the plug is on.
+pub fn state(agent: &ureq::Agent, url: &str) -> Result<bool> {
+    power(agent, url, "Power")
+}
+
+/// Execute the `Power` command and return the reported relay state.
+fn power(agent: &ureq::Agent, url: &str, command: &str) -> Result<bool> {
+    log::trace!("notification/plug/tasmota: executing \"{command}\"");
+
+    let response: serde_json::Value = agent
+        .get(&format!("{url}/cm"))
+        .query("cmnd", command)
+        .call()
+        .map_err(Box::new)?
+        .into_json()?;
+
+    // plugs with multiple relays report the first one as POWER1
+    let power =
+        field(&response, "/POWER").or_else(|_| field(&response, "/POWER1"))?;
+
+    match power.as_str() {
+        Some("ON") => Ok(true),
+        Some("OFF") => Ok(false),
+        _ => Err(error::Plug::Response {
+            response: response.to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing;
+
+    use super::*;
+
+    #[test]
+    fn test_state_multiple_relays() {
+        let (url, _requests) =
+            testing::http_server(vec![(200, r#"{"POWER1":"ON"}"#.into())]);
+
+        let result = state(&ureq::agent(), &url);
+
+        assert!(matches!(result, Ok(true)));
+    }
+
+    #[test]
+    fn test_state_invalid_response() {
+        let (url, _requests) = testing::http_server(vec![(
+            200,
+            r#"{"Command":"Unknown"}"#.into(),
+        )]);
+
+        let result = state(&ureq::agent(), &url);
+
+        assert!(matches!(result, Err(error::Plug::Response { .. })));
+    }
+} // tests
@@ -0,0 +1,467 @@
+//! Quiet hours during which alerts are suppressed or downgraded.
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::str::FromStr;
+
+use chrono::{Datelike, Local, NaiveDateTime, NaiveTime, Weekday};
+
+use crate::cli;
+use crate::notification::Channel;
+
+/// Format of the start and end of a quiet hours window.
+const TIME_FORMAT: &str = "%H:%M";
+
+/// Source of the local time, replaced by a fake clock in tests.
+pub trait Clock: Debug + Send {
+    fn now(&self) -> NaiveDateTime;
+}
+
+/// `Clock` returning the current local time.
+#[derive(Debug)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> NaiveDateTime {
+        Local::now().naive_local()
+    }
+}
+
+/// What happens to the alerts of a channel during quiet hours.
+#[derive(clap::ValueEnum, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Mode {
+    /// Alerts are deferred until the quiet hours end.
+    Suppress,
+    /// Alerts are sent with the low urgency.
+    Downgrade,
+    /// Alerts are sent as usual.
+    Deliver,
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <Self as clap::ValueEnum>::from_str(s, false)
+            .map_err(|_| format!("unknown quiet hours mode \"{s}\""))
+    }
+}
+
+/// Weekly window of quiet hours, e.g. `mon-fri@22:00-07:00`.
+///
+/// A window which ends before it starts spans midnight and belongs
+/// to the day it starts on.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Window {
+    /// Days the window starts on, indexed from Monday.
+    days: [bool; 7],
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl Window {
+    /// Return whether the time falls into the `Window`.
+    ///
+    /// A window which starts and ends at the same time lasts the whole day.
+    pub fn contains(&self, time: NaiveDateTime) -> bool {
+        let today = self.starts_on(time.weekday());
+        let clock = time.time();
+
+        match self.start.cmp(&self.end) {
+            std::cmp::Ordering::Less => {
+                today && self.start <= clock && clock < self.end
+            }
+            std::cmp::Ordering::Equal => today,
+            std::cmp::Ordering::Greater => {
+                (today && clock >= self.start)
+                    || (self.starts_on(time.weekday().pred())
+                        && clock < self.end)
+            }
+        }
+    }
+
+    fn starts_on(&self, day: Weekday) -> bool {
+        self.days[day.num_days_from_monday() as usize]
+    }
+}
+
+impl FromStr for Window {
+    type Err = String;
+
+    /// Parse `[DAYS@]HH:MM-HH:MM` where `DAYS` is a comma separated list
+    /// of days or day ranges, e.g. `mon-fri` or `sat,sun`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (days, hours) = s
+            .split_once('@')
+            .map_or((None, s), |(days, hours)| (Some(days), hours));
+        let (start, end) = hours.split_once('-').ok_or_else(|| {
+            format!("expected [DAYS@]HH:MM-HH:MM, got \"{s}\"")
+        })?;
+
+        Ok(Self {
+            days: days.map_or(Ok([true; 7]), parse_days)?,
+            start: parse_time(start)?,
+            end: parse_time(end)?,
+        })
+    }
+}
+
+/// Parse comma separated list of days or day ranges.
+fn parse_days(s: &str) -> Result<[bool; 7], String> {
+    let mut days = [false; 7];
+
+    for part in s.split(',') {
+        let (first, last) = part.split_once('-').unwrap_or((part, part));
+        let last = parse_day(last)?;
+        let mut day = parse_day(first)?;
+
+        days[day.num_days_from_monday() as usize] = true;
+
+        while day != last {
+            day = day.succ();
+            days[day.num_days_from_monday() as usize] = true;
+        }
+    }
+
+    Ok(days)
+}
+
+fn parse_day(s: &str) -> Result<Weekday, String> {
+    s.parse().map_err(|_| {
+        format!("unknown day \"{s}\", expected e.g. mon or monday")
+    })
+}
+
+fn parse_time(s: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(s, TIME_FORMAT)
+        .map_err(|e| format!("invalid time \"{s}\": {e}"))
+}
+
+/// Alert of a channel deferred until the quiet hours end.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Deferred {
+    pub channel: Channel,
+    /// ID of the deferred alert.
+    pub alert: u64,
+    /// Time the alert was deferred at.
+    pub since: NaiveDateTime,
+}
+
+/// Schedule of the quiet hours and the alerts deferred by them.
+#[derive(Debug)]
+pub struct QuietHours {
+    windows: Vec<Window>,
+    mode: Mode,
+    /// Modes overriding `mode` for the channels.
+    channels: HashMap<Channel, Mode>,
+    /// Whether desktop notifications are deferred while the desktop
+    /// is in do-not-disturb mode.
+    pub desktop_dnd: bool,
+
+    clock: Box<dyn Clock>,
+    /// Whether the quiet hours were active when last checked.
+    active: bool,
+    /// Deferred alerts in the order they were deferred in.
+    deferred: Vec<Deferred>,
+}
+
+impl QuietHours {
+    /// Create a new `QuietHours` instance using the system clock.
+    pub fn new(options: &cli::Quiet) -> Self {
+        Self::with_clock(options, Box::new(SystemClock))
+    }
+
+    /// Create a new `QuietHours` instance using the `Clock`.
+    pub fn with_clock(options: &cli::Quiet, clock: Box<dyn Clock>) -> Self {
+        for window in &options.windows {
+            log::info!("notification/quiet: quiet hours {window}");
+        }
+
+        Self {
+            windows: options.windows.clone(),
+            mode: options.mode,
+            channels: options.channels.iter().copied().collect(),
+            desktop_dnd: options.desktop_dnd,
+            clock,
+            active: false,
+            deferred: vec![],
+        }
+    }
+
+    /// Return whether the quiet hours are currently active.
+    pub fn active(&mut self) -> bool {
+        let now = self.clock.now();
+        let active = self.windows.iter().any(|window| window.contains(now));
+
+        if active != self.active {
+            self.active = active;
+
+            log::info!(
+                "notification/quiet: quiet hours {}",
+                if active { "started" } else { "ended" }
+            );
+        }
+
+        active
+    }
+
+    /// Return `Mode` of the `Channel` during quiet hours.
+    pub fn mode(&self, channel: Channel) -> Mode {
+        self.channels.get(&channel).copied().unwrap_or(self.mode)
+    }
+
+    /// Defer the alert of the `Channel` unless it's already deferred.
+    pub fn defer(&mut self, channel: Channel, alert: u64) {
+        if self
+            .deferred
+            .iter()
+            .any(|deferred| deferred.channel == channel)
+        {
+            return;
+        }
+
+        log::info!("notification/{channel}: alert {alert} deferred");
+
+        self.deferred.push(Deferred {
+            channel,
+            alert,
+            since: self.clock.now(),
+        });
+    }
+
+    /// Take the deferred alert of the `Channel` which should be delivered.
+    pub fn release(&mut self, channel: Channel) -> Option<Deferred> {
+        let index = self
+            .deferred
+            .iter()
+            .position(|deferred| deferred.channel == channel)?;
+
+        Some(self.deferred.remove(index))
+    }
+
+    /// Drop all the deferred alerts, e.g. once the alert is resolved.
+    pub fn clear(&mut self) {
+        for deferred in self.deferred.drain(..) {
+            log::info!(
+                "notification/{}: deferred alert {} dropped",
+                deferred.channel,
+                deferred.alert,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+pub use fake::FakeClock;
+
+#[cfg(test)]
+mod fake {
+    use std::sync::{Arc, Mutex};
+
+    use chrono::{Duration, NaiveDateTime};
+
+    use super::Clock;
+
+    /// `Clock` returning the time set by the test.
+    #[derive(Debug, Clone)]
+    pub struct FakeClock(Arc<Mutex<NaiveDateTime>>);
+
+    impl FakeClock {
+        /// Create a new `FakeClock` set to the `YYYY-MM-DD HH:MM` time.
+        pub fn new(time: &str) -> Self {
+            Self(Arc::new(Mutex::new(
+                NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M").unwrap(),
+            )))
+        }
+
+        /// Move the clock forward by the number of minutes.
+        pub fn advance(&self, minutes: i64) {
+            *self.0.lock().unwrap() += Duration::minutes(minutes);
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> NaiveDateTime {
+            *self.0.lock().unwrap()
+        }
+    }
+} // fake
+
+mod std_fmt_impls {
+    use std::fmt;
+
+    use chrono::Weekday;
+
+    use super::{Mode, Window, TIME_FORMAT};
+
+    impl fmt::Display for Mode {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Suppress => write!(f, "suppress"),
+                Self::Downgrade => write!(f, "downgrade"),
+                Self::Deliver => write!(f, "deliver"),
+            }
+        }
+    }
+
+    impl fmt::Display for Window {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            if self.days != [true; 7] {
+                let days = [
+                    Weekday::Mon,
+                    Weekday::Tue,
+                    Weekday::Wed,
+                    Weekday::Thu,
+                    Weekday::Fri,
+                    Weekday::Sat,
+                    Weekday::Sun,
+                ]
+                .into_iter()
+                .filter(|day| self.starts_on(*day))
+                .map(|day| day.to_string().to_lowercase())
+                .collect::<Vec<String>>();
+
+                write!(f, "{}@", days.join(","))?;
+            }
+
+            write!(
+                f,
+                "{}-{}",
+                self.start.format(TIME_FORMAT),
+                self.end.format(TIME_FORMAT),
+            )
+        }
+    }
+} // std_fmt_impls
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn create_quiet_hours(windows: &[&str], clock: &FakeClock) -> QuietHours {
+        QuietHours::with_clock(
+            &cli::Quiet {
+                windows: windows
+                    .iter()
+                    .map(|window| window.parse().unwrap())
+                    .collect(),
+                mode: Mode::Suppress,
+                channels: vec![(Channel::Desktop, Mode::Downgrade)],
+                desktop_dnd: false,
+            },
+            Box::new(clock.clone()),
+        )
+    }
+
+    #[test]
+    fn test_window_from_str() {
+        let result = Window::from_str("fri-mon,wed@22:00-07:30");
+
+        assert_eq!(
+            result.map(|window| window.to_string()),
+            Ok("mon,wed,fri,sat,sun@22:00-07:30".into())
+        );
+    }
+
+    #[test]
+    fn test_window_from_str_invalid() {
+        for value in [
+            "22:00",
+            "mon-fri@22:00",
+            "weekdays@22:00-07:00",
+            "25:00-07:00",
+        ] {
+            let result = Window::from_str(value);
+
+            assert!(result.is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_window_contains_overnight() {
+        // 2026-10-16 is a Friday.
+        let window = Window::from_str("mon-fri@22:00-07:00").unwrap();
+
+        let result = [
+            "2026-10-16 21:59",
+            "2026-10-16 22:00",
+            "2026-10-17 06:59",
+            "2026-10-17 07:00",
+            "2026-10-17 23:00",
+            "2026-10-19 06:00",
+        ]
+        .map(|value| window.contains(time(value)));
+
+        assert_eq!(result, [false, true, true, false, false, false]);
+    }
+
+    #[test]
+    fn test_window_contains_same_day() {
+        let window = Window::from_str("12:00-13:00").unwrap();
+
+        let result = ["2026-10-18 12:30", "2026-10-18 13:00"]
+            .map(|value| window.contains(time(value)));
+
+        assert_eq!(result, [true, false]);
+    }
+
+    #[test]
+    fn test_window_contains_whole_day() {
+        let window = Window::from_str("sun@00:00-00:00").unwrap();
+
+        let result = ["2026-10-18 08:00", "2026-10-19 08:00"]
+            .map(|value| window.contains(time(value)));
+
+        assert_eq!(result, [true, false]);
+    }
+
+    #[test]
+    fn test_quiet_hours_active() {
+        let clock = FakeClock::new("2026-10-16 21:30");
+        let mut quiet_hours = create_quiet_hours(&["22:00-07:00"], &clock);
+
+        let before = quiet_hours.active();
+        clock.advance(60);
+        let during = quiet_hours.active();
+        clock.advance(9 * 60);
+        let after = quiet_hours.active();
+
+        assert_eq!([before, during, after], [false, true, false]);
+    }
+
+    #[test]
+    fn test_quiet_hours_mode() {
+        let clock = FakeClock::new("2026-10-16 21:30");
+        let quiet_hours = create_quiet_hours(&[], &clock);
+
+        let result = [Channel::Desktop, Channel::Ntfy]
+            .map(|channel| quiet_hours.mode(channel));
+
+        assert_eq!(result, [Mode::Downgrade, Mode::Suppress]);
+    }
+
+    #[test]
+    fn test_quiet_hours_defer_once() {
+        let clock = FakeClock::new("2026-10-16 23:00");
+        let mut quiet_hours = create_quiet_hours(&[], &clock);
+
+        quiet_hours.defer(Channel::Ntfy, 1);
+        clock.advance(30);
+        quiet_hours.defer(Channel::Ntfy, 1);
+        quiet_hours.defer(Channel::Email, 1);
+        let released = quiet_hours.release(Channel::Ntfy);
+
+        assert_eq!(
+            released,
+            Some(Deferred {
+                channel: Channel::Ntfy,
+                alert: 1,
+                since: time("2026-10-16 23:00"),
+            })
+        );
+        assert_eq!(quiet_hours.deferred.len(), 1);
+    }
+} // tests
@@ -0,0 +1,134 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::Instant;
+
+use crate::notification::{Message, Urgency};
+
+/// Record of the alert last sent via a channel.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Record {
+    /// ID of the sent alert.
+    pub alert: u64,
+    /// Time the alert was last sent at.
+    pub sent_at: Instant,
+    /// Hash of the sent summary, body and urgency.
+    hash: u64,
+    /// Battery percentage of the sent alert.
+    percentage: u8,
+    /// Urgency of the sent alert.
+    urgency: Urgency,
+}
+
+/// Change of an alert since it was last sent.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Change {
+    /// Content of the alert is the same.
+    Identical,
+    /// Content changed but not enough to bother the user again.
+    Minor,
+    /// Content changed enough to send an updated alert.
+    Material,
+}
+
+impl Record {
+    /// Create a new `Record` of the `Message` sent as the alert with the ID.
+    pub fn new(alert: u64, message: &Message) -> Self {
+        Self {
+            alert,
+            sent_at: Instant::now(),
+            hash: hash(message),
+            percentage: message.percentage,
+            urgency: message.urgency,
+        }
+    }
+
+    /// Return how the `Message` changed since the recorded alert was sent.
+    ///
+    /// Change is material if the urgency is different or if the battery
+    /// percentage changed by at least `min_change`, `0` makes every
+    /// percentage change minor.
+    pub fn change(&self, message: &Message, min_change: u8) -> Change {
+        if hash(message) == self.hash {
+            return Change::Identical;
+        }
+
+        if message.urgency != self.urgency
+            || (min_change > 0
+                && message.percentage.abs_diff(self.percentage) >= min_change)
+        {
+            Change::Material
+        } else {
+            Change::Minor
+        }
+    }
+}
+
+/// Return hash of the content of the `Message`.
+fn hash(message: &Message) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    message.summary.hash(&mut hasher);
+    message.body.hash(&mut hasher);
+    message.urgency.hash(&mut hasher);
+
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_message(summary: &str, percentage: u8) -> Message {
+        let mut message = Message::test(summary, "body", Urgency::Normal);
+        message.percentage = percentage;
+
+        message
+    }
+
+    #[test]
+    fn test_record_change_identical() {
+        let record = Record::new(1, &create_message("85%", 85));
+
+        let result = record.change(&create_message("85%", 85), 5);
+
+        assert_eq!(result, Change::Identical);
+    }
+
+    #[test]
+    fn test_record_change_minor() {
+        let record = Record::new(1, &create_message("85%", 85));
+
+        let result = record.change(&create_message("89%", 89), 5);
+
+        assert_eq!(result, Change::Minor);
+    }
+
+    #[test]
+    fn test_record_change_material() {
+        let record = Record::new(1, &create_message("85%", 85));
+
+        let result = record.change(&create_message("90%", 90), 5);
+
+        assert_eq!(result, Change::Material);
+    }
+
+    #[test]
+    fn test_record_change_updates_disabled() {
+        let record = Record::new(1, &create_message("85%", 85));
+
+        let result = record.change(&create_message("100%", 100), 0);
+
+        assert_eq!(result, Change::Minor);
+    }
+
+    #[test]
+    fn test_record_change_urgency() {
+        let record = Record::new(1, &create_message("85%", 85));
+        let mut message = create_message("85%", 85);
+        message.urgency = Urgency::Critical;
+
+        let result = record.change(&message, 5);
+
+        assert_eq!(result, Change::Material);
+    }
+} // tests
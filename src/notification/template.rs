@@ -0,0 +1,264 @@
+//! Templates of the notification summary and body.
+use std::fmt::Write;
+use std::result;
+
+use minijinja::{context, Environment, UndefinedBehavior, Value};
+
+use crate::error;
+use crate::notification::Message;
+
+type Result<T> = result::Result<T, error::Notification>;
+
+/// Name of the summary template.
+const SUMMARY: &str = "summary";
+/// Name of the body template.
+const BODY: &str = "body";
+
+/// Variables available in the templates.
+const VARIABLES: [&str; 7] = [
+    "percentage",
+    "state",
+    "model",
+    "serial_number",
+    "threshold",
+    "refresh_secs",
+    "urgency",
+];
+
+/// Deprecated `$VARIABLE` names and the variables replacing them.
+const LEGACY_VARIABLES: [(&str, &str); 4] = [
+    ("THRESHOLD", "threshold"),
+    ("CHARGE_STATE", "percentage"),
+    ("MODEL", "model"),
+    ("REFRESH_SECS", "refresh_secs"),
+];
+
+/// Compiled summary and body templates.
+#[derive(Debug, Clone)]
+pub struct Templates {
+    env: Environment<'static>,
+}
+
+impl Templates {
+    /// Compile and validate the summary and body templates.
+    ///
+    /// Return `error::Notification::Config` if a template has invalid syntax
+    /// or uses an unknown variable.
+    pub fn new(summary: &str, body: &str) -> Result<Self> {
+        let mut env = Environment::new();
+        env.set_undefined_behavior(UndefinedBehavior::Strict);
+        env.add_filter("pad", pad);
+        env.add_filter("duration", duration);
+
+        for (name, source) in [(SUMMARY, summary), (BODY, body)] {
+            env.add_template_owned(name, legacy(source))
+                .map_err(|e| config(name, &e))?;
+
+            let mut unknown = env
+                .get_template(name)
+                .map_err(|e| config(name, &e))?
+                .undeclared_variables(false)
+                .into_iter()
+                .filter(|variable| {
+                    !VARIABLES.contains(&variable.as_str())
+                        && env.globals().all(|(global, _)| global != variable)
+                })
+                .collect::<Vec<String>>();
+
+            if !unknown.is_empty() {
+                unknown.sort();
+
+                return Err(error::Notification::Config {
+                    kind: format!(
+                        "{name} template uses unknown variables {}, \
+                        supported variables are {}",
+                        unknown.join(", "),
+                        VARIABLES.join(", "),
+                    ),
+                });
+            }
+        }
+
+        log::debug!("template: summary and body compiled");
+
+        Ok(Self { env })
+    }
+
+    /// Render the summary and body with the data of the `Message`.
+    pub fn render(&self, message: &Message) -> Result<(String, String)> {
+        let context = context! {
+            percentage => message.percentage,
+            state => message.state.to_string(),
+            model => message.model,
+            serial_number => message.serial_number,
+            threshold => message.threshold,
+            refresh_secs => message.refresh_secs,
+            urgency => message.urgency.to_string(),
+        };
+
+        let render = |name| {
+            self.env
+                .get_template(name)
+                .and_then(|template| template.render(&context))
+                .map_err(|e| config(name, &e))
+        };
+
+        Ok((render(SUMMARY)?, render(BODY)?))
+    }
+}
+
+/// Return configuration error of the template with the name.
+fn config(name: &str, e: &minijinja::Error) -> error::Notification {
+    error::Notification::Config {
+        kind: format!("{name} template: {e}"),
+    }
+}
+
+/// Convert the deprecated `$VARIABLE` syntax into template expressions.
+///
+/// Only whole known variable names are converted, so `$THRESHOLDS`
+/// stays untouched.
+fn legacy(source: &str) -> String {
+    let mut converted = String::with_capacity(source.len());
+    let mut rest = source;
+
+    while let Some(index) = rest.find('$') {
+        converted.push_str(&rest[..index]);
+        rest = &rest[index + 1..];
+
+        let end = rest
+            .find(|c: char| !(c.is_ascii_uppercase() || c == '_'))
+            .unwrap_or(rest.len());
+        let name = &rest[..end];
+
+        match LEGACY_VARIABLES.iter().find(|(legacy, _)| *legacy == name) {
+            Some((_, variable)) => {
+                log::warn!(
+                    "template: ${name} is deprecated, \
+                    use {{{{ {variable} }}}} instead"
+                );
+
+                let _ = write!(converted, "{{{{ {variable} }}}}");
+                rest = &rest[end..];
+            }
+            None => converted.push('$'),
+        }
+    }
+
+    converted.push_str(rest);
+
+    converted
+}
+
+/// Filter padding the value from the left to the width.
+fn pad(value: &Value, width: usize, fill: Option<String>) -> String {
+    let fill = fill.and_then(|fill| fill.chars().next()).unwrap_or(' ');
+    let value = value.to_string();
+    let length = value.chars().count();
+
+    fill.to_string().repeat(width.saturating_sub(length)) + &value
+}
+
+/// Filter formatting the number of seconds as a human readable duration.
+fn duration(secs: u64) -> String {
+    let (hours, minutes, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+
+    [(hours, "h"), (minutes, "m"), (secs, "s")]
+        .into_iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{value}{unit}"))
+        .reduce(|duration, part| format!("{duration} {part}"))
+        .unwrap_or_else(|| "0s".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::BatteryState;
+    use crate::notification::Urgency;
+
+    fn render(summary: &str, body: &str) -> (String, String) {
+        let message = Message::test(summary, body, Urgency::Normal);
+
+        Templates::new(summary, body)
+            .unwrap()
+            .render(&message)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_templates_render() {
+        let result = render("{{ percentage }}%", "{{ model }}/{{ threshold }}");
+
+        assert_eq!(result, ("85%".into(), "test-model/80".into()));
+    }
+
+    #[test]
+    fn test_templates_render_conditional() {
+        let mut message = Message::test("", "", Urgency::Normal);
+        message.state = BatteryState::Discharging;
+        let templates = Templates::new(
+            "{% if state == \"Charging\" %}unplug{% else %}ok{% endif %}",
+            "",
+        )
+        .unwrap();
+
+        let result = templates.render(&message).unwrap();
+
+        assert_eq!(result.0, "ok");
+    }
+
+    #[test]
+    fn test_templates_render_filters() {
+        let result = render(
+            "{{ percentage | pad(4, \"0\") }}",
+            "{{ refresh_secs | duration }} {{ (percentage / 8) | round(1) }}",
+        );
+
+        assert_eq!(result, ("0085".into(), "30s 10.6".into()));
+    }
+
+    #[test]
+    fn test_templates_new_unknown_variables() {
+        let result = Templates::new("{{ percent }}", "{{ range(2) }}");
+
+        assert!(matches!(
+            result,
+            Err(error::Notification::Config { kind })
+                if kind.starts_with("summary template uses unknown \
+                variables percent,")
+        ));
+    }
+
+    #[test]
+    fn test_templates_new_invalid_syntax() {
+        let result = Templates::new("summary", "{% if %}");
+
+        assert!(matches!(
+            result,
+            Err(error::Notification::Config { kind })
+                if kind.starts_with("body template:")
+        ));
+    }
+
+    #[test]
+    fn test_legacy() {
+        let result = legacy("$THRESHOLD% $THRESHOLDS $CHARGE_STATE $5");
+
+        assert_eq!(result, "{{ threshold }}% $THRESHOLDS {{ percentage }} $5");
+    }
+
+    #[test]
+    fn test_pad() {
+        let result = pad(&Value::from(7), 3, None);
+
+        assert_eq!(result, "  7");
+    }
+
+    #[test]
+    fn test_duration() {
+        let result = [0, 45, 3600, 3725].map(duration);
+
+        assert_eq!(result, ["0s", "45s", "1h", "1h 2m 5s"]);
+    }
+} // tests
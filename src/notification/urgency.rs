@@ -0,0 +1,57 @@
+/// How urgent a notification is.
+///
+/// Every notifier maps this value to its own closest priority level.
+#[derive(clap::ValueEnum, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Urgency {
+    Low,
+    Normal,
+    Critical,
+}
+
+mod std_fmt_impls {
+    use std::fmt;
+
+    use super::Urgency;
+
+    impl fmt::Display for Urgency {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Low => write!(f, "Low"),
+                Self::Normal => write!(f, "Normal"),
+                Self::Critical => write!(f, "Critical"),
+            }
+        }
+    }
+} // std_fmt_impls
+
+#[cfg(test)]
+mod tests {
+    use super::Urgency;
+
+    #[test]
+    fn test_urgency_low_display() {
+        let urgency = Urgency::Low;
+
+        let display = format!("{urgency}");
+
+        assert_eq!(display, "Low");
+    }
+
+    #[test]
+    fn test_urgency_normal_display() {
+        let urgency = Urgency::Normal;
+
+        let display = format!("{urgency}");
+
+        assert_eq!(display, "Normal");
+    }
+
+    #[test]
+    fn test_urgency_critical_display() {
+        let urgency = Urgency::Critical;
+
+        let display = format!("{urgency}");
+
+        assert_eq!(display, "Critical");
+    }
+} // tests
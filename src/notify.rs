@@ -3,84 +3,246 @@ use std::sync::mpsc;
 
 use crate::device::Battery;
 use crate::notification::Message;
-use crate::{common, error, event, notification};
+use crate::{
+    boost, cli, common, control, error, event, instance, locale, notification,
+    service, sleep, systemd,
+};
 
-pub fn notify(
-    threshold: u8,
-    model: Option<&str>,
-    refresh_secs: u64,
-    summary: String,
-    body: String,
-    kde_connect_names: Option<Vec<String>>,
-    disable_desktop: bool,
-) -> error::Result<()> {
-    validate_input(
+pub fn notify(opts: cli::Notify) -> error::Result<()> {
+    validate_input(&opts)?;
+
+    let cli::Notify {
         threshold,
         model,
+        include_kde_connect,
         refresh_secs,
+        summary,
+        body,
+        urgency,
+        replace,
+        inhibit_sleep,
+        notifiers,
+    } = opts;
+
+    let _lock = instance::Lock::acquire(replace)?;
+
+    let summary =
+        summary.unwrap_or_else(|| locale::text("notification-summary"));
+    // the threshold stays a template variable as a boost can change it
+    let body = body.unwrap_or_else(|| {
+        locale::format(
+            "notification-body",
+            &[("threshold", "{{ threshold }}".into())],
+        )
+    });
+
+    let battery_device = battery(model.as_deref(), include_kde_connect)?;
+    let message = Message::new(
         &summary,
         &body,
-        kde_connect_names.as_ref(),
-        disable_desktop,
-    )?;
-
-    let battery_device = Battery::try_from(model)?;
-    let message =
-        Message::new(summary, body, threshold, &battery_device, refresh_secs);
-    let notifier = notification::Notifier::new(
         threshold,
-        kde_connect_names.map(common::vec_to_set),
-        disable_desktop,
+        &battery_device,
+        refresh_secs,
+        urgency,
     )?;
+    let (sender, receiver) = mpsc::channel();
+
+    let notifier =
+        notification::Notifier::new(threshold, notifiers, sender.clone())?;
 
-    let (shutdown_sender, shutdown_receiver) = mpsc::channel();
+    common::warn_on_err("notify", control::listen(sender.clone()));
+    let service =
+        common::warn_on_err("notify", service::Service::new(sender.clone()));
+    common::warn_on_err("notify", sleep::watch(sender.clone(), inhibit_sleep));
+    event::set_handler(sender)?;
 
-    event::set_handler(shutdown_sender)?;
+    systemd::ready();
 
     event::loop_(
-        &shutdown_receiver,
+        &receiver,
         battery_device,
         message,
         notifier,
+        boost::Tracker::new(threshold),
+        service,
+        systemd::Systemd::new(),
         refresh_secs,
     )?;
 
     Ok(())
 }
 
-fn validate_input(
-    _threshold: u8,
-    _model: Option<&str>,
-    _refresh_secs: u64,
-    _summary: &str,
-    _body: &str,
-    kde_connect_names: Option<&Vec<String>>,
-    disable_desktop: bool,
-) -> error::Result<()> {
-    if disable_desktop && kde_connect_names.is_none() {
+/// Return the `Battery` to monitor.
+///
+/// KDE Connect devices are searched only if no battery of the current
+/// device matches the model.
+fn battery(
+    model: Option<&str>,
+    include_kde_connect: bool,
+) -> error::Result<Battery> {
+    match (model, include_kde_connect) {
+        (Some(model), true) => match Battery::new(model) {
+            Err(error::Battery::NotFound { .. }) => {
+                log::info!(
+                    "notify: searching KDE Connect devices for \"{model}\""
+                );
+
+                Ok(Battery::kde_connect(model)?)
+            }
+            result => Ok(result?),
+        },
+        (model, _) => Ok(Battery::try_from(model)?),
+    }
+}
+
+fn validate_input(opts: &cli::Notify) -> error::Result<()> {
+    let notifiers = &opts.notifiers;
+
+    if notifiers.disable_desktop
+        && notifiers.kde_connect_selectors.is_none()
+        && notifiers.ntfy.url.is_none()
+        && notifiers.gotify.url.is_none()
+        && notifiers.mqtt.host.is_none()
+        && notifiers.plug.kind.is_none()
+        && notifiers.hook.on_alert.is_none()
+        && notifiers.email.server.is_none()
+    {
         return Err(error::Error::from(error::Notification::Config {
-            kind: "both desktop and KDE connect can't be disabled".into(),
+            kind: "desktop can't be disabled when no other notifier \
+            is enabled"
+                .into(),
         }));
     };
 
+    if notifiers.plug.kind.is_some()
+        && notifiers.plug.on_threshold >= opts.threshold
+    {
+        return Err(error::Error::from(error::Notification::Config {
+            kind: "plug on threshold must be lower than the threshold".into(),
+        }));
+    }
+
+    // the state published after every refresh keeps the connection alive
+    if notifiers.mqtt.host.is_some()
+        && notifiers.mqtt.keep_alive != 0
+        && u64::from(notifiers.mqtt.keep_alive) < opts.refresh_secs
+    {
+        return Err(error::Error::from(error::Notification::Config {
+            kind: "MQTT keep alive must not be shorter than the refresh \
+            interval"
+                .into(),
+        }));
+    }
+
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::device::kde_connect;
+    use crate::notification::{KDEConnectMissing, Urgency};
+
     use super::*;
 
+    fn create_opts(
+        kde_connect_selectors: Option<Vec<kde_connect::Selector>>,
+        disable_desktop: bool,
+    ) -> cli::Notify {
+        cli::Notify {
+            threshold: 0,
+            model: None,
+            include_kde_connect: false,
+            refresh_secs: 0,
+            summary: Some("test summary".into()),
+            body: Some("test body".into()),
+            urgency: Urgency::Critical,
+            replace: false,
+            inhibit_sleep: false,
+            notifiers: cli::Notifiers {
+                kde_connect_selectors,
+                kde_connect_missing: KDEConnectMissing::Wait,
+                kde_connect_resolved: false,
+                kde_connect_actions: vec![],
+                disable_desktop,
+                desktop: cli::Desktop {
+                    disable_actions: false,
+                    snooze_mins: 15,
+                    urgency: None,
+                    icon: "battery".into(),
+                    timeout_secs: None,
+                    category: None,
+                    sound: None,
+                    transient: false,
+                    resident: false,
+                    progress: false,
+                },
+                ntfy: cli::Ntfy {
+                    url: None,
+                    topic: None,
+                    token: None,
+                    tags: vec![],
+                    resolved: false,
+                },
+                gotify: cli::Gotify {
+                    url: None,
+                    token: None,
+                    resolved: false,
+                },
+                mqtt: cli::Mqtt {
+                    host: None,
+                    port: 1883,
+                    username: None,
+                    password: None,
+                    client_id: "rusty-battery".into(),
+                    topic_prefix: "rusty-battery".into(),
+                    keep_alive: 60,
+                    home_assistant: false,
+                },
+                plug: cli::Plug {
+                    kind: None,
+                    host: None,
+                    on_threshold: 40,
+                },
+                hook: cli::Hook {
+                    on_alert: None,
+                    on_clear: None,
+                    on_action: None,
+                    on_action_label: "Run hook".into(),
+                    timeout: 30,
+                },
+                email: cli::Email {
+                    server: None,
+                    port: None,
+                    security: notification::EmailSecurity::Starttls,
+                    username: None,
+                    password: None,
+                    from: None,
+                    to: vec![],
+                    interval_secs: 3600,
+                    resolved: false,
+                },
+                delivery: cli::Delivery {
+                    retries: 2,
+                    channel_retries: vec![],
+                    retry_backoff_ms: 500,
+                    circuit_failures: 5,
+                    circuit_cooldown_secs: 300,
+                    fallbacks: vec![],
+                    update_change: 5,
+                },
+                quiet: cli::Quiet {
+                    windows: vec![],
+                    mode: notification::QuietMode::Suppress,
+                    channels: vec![],
+                    desktop_dnd: false,
+                },
+            },
+        }
+    }
+
     #[test]
     fn test_notify_notifications_disabled_kde_disabled() {
-        let result = notify(
-            0,
-            None,
-            0,
-            "test summary".into(),
-            "test body".into(),
-            None,
-            true,
-        );
+        let result = notify(create_opts(None, true));
 
         assert!(result.is_err());
         result.unwrap_or_else(|e| {
@@ -93,53 +255,31 @@ mod tests {
 
     #[test]
     fn test_validate_validate_input_desktop_enabled_kde_disabled() {
-        let result = validate_input(
-            0,
-            None,
-            0,
-            "test summary",
-            "test body",
-            None,
-            false,
-        );
+        let result = validate_input(&create_opts(None, false));
 
         assert!(result.is_ok());
     }
 
     #[test]
     fn test_validate_validate_input_desktop_enabled_kde_empty() {
-        let result = validate_input(
-            0,
-            None,
-            0,
-            "test summary",
-            "test body",
-            Some(&vec![]),
-            false,
-        );
+        let result = validate_input(&create_opts(Some(vec![]), false));
 
         assert!(result.is_ok());
     }
 
     #[test]
     fn test_validate_validate_input_desktop_enabled_kde_populated() {
-        let result = validate_input(
-            0,
-            None,
-            0,
-            "test summary",
-            "test body",
-            Some(&vec!["a".into(), "5".into()]),
+        let result = validate_input(&create_opts(
+            Some(vec!["a".parse().unwrap(), "id:5".parse().unwrap()]),
             false,
-        );
+        ));
 
         assert!(result.is_ok());
     }
 
     #[test]
     fn test_validate_validate_input_desktop_disabled_kde_disabled() {
-        let result =
-            validate_input(0, None, 0, "test summary", "test body", None, true);
+        let result = validate_input(&create_opts(None, true));
 
         assert!(result.is_err());
         result.unwrap_or_else(|e| {
@@ -152,30 +292,126 @@ mod tests {
 
     #[test]
     fn test_validate_validate_input_desktop_disabled_kde_empty() {
-        let result = validate_input(
-            0,
-            None,
-            0,
-            "test summary",
-            "test body",
-            Some(&vec![]),
-            true,
-        );
+        let result = validate_input(&create_opts(Some(vec![]), true));
 
         assert!(result.is_ok());
     }
 
     #[test]
     fn test_validate_validate_input_desktop_disabled_kde_populated() {
-        let result = validate_input(
-            0,
-            None,
-            0,
-            "test summary",
-            "test body",
-            Some(&vec!["a".into(), "5".into()]),
+        let result = validate_input(&create_opts(
+            Some(vec!["a".parse().unwrap(), "id:5".parse().unwrap()]),
             true,
-        );
+        ));
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_validate_input_desktop_disabled_ntfy_enabled() {
+        let mut opts = create_opts(None, true);
+        opts.notifiers.ntfy.url = Some("https://ntfy.sh".into());
+
+        let result = validate_input(&opts);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_validate_input_desktop_disabled_gotify_enabled() {
+        let mut opts = create_opts(None, true);
+        opts.notifiers.gotify.url = Some("https://gotify.example.com".into());
+
+        let result = validate_input(&opts);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_validate_input_desktop_disabled_mqtt_enabled() {
+        let mut opts = create_opts(None, true);
+        opts.notifiers.mqtt.host = Some("localhost".into());
+
+        let result = validate_input(&opts);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_validate_input_mqtt_keep_alive_too_short() {
+        let mut opts = create_opts(None, false);
+        opts.refresh_secs = 90;
+        opts.notifiers.mqtt.host = Some("localhost".into());
+
+        let result = validate_input(&opts);
+
+        assert!(matches!(
+            result,
+            Err(error::Error::Notification(
+                error::Notification::Config { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_validate_validate_input_mqtt_keep_alive_disabled() {
+        let mut opts = create_opts(None, false);
+        opts.refresh_secs = 90;
+        opts.notifiers.mqtt.host = Some("localhost".into());
+        opts.notifiers.mqtt.keep_alive = 0;
+
+        let result = validate_input(&opts);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_validate_input_plug_on_threshold_too_high() {
+        let mut opts = create_opts(None, false);
+        opts.threshold = 80;
+        opts.notifiers.plug.kind = Some(notification::PlugKind::Tasmota);
+        opts.notifiers.plug.host = Some("localhost".into());
+        opts.notifiers.plug.on_threshold = 80;
+
+        let result = validate_input(&opts);
+
+        assert!(matches!(
+            result,
+            Err(error::Error::Notification(
+                error::Notification::Config { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_validate_validate_input_plug_on_threshold() {
+        let mut opts = create_opts(None, false);
+        opts.threshold = 80;
+        opts.notifiers.plug.kind = Some(notification::PlugKind::Kasa);
+        opts.notifiers.plug.host = Some("localhost".into());
+        opts.notifiers.plug.on_threshold = 40;
+
+        let result = validate_input(&opts);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_validate_input_desktop_disabled_hook_enabled() {
+        let mut opts = create_opts(None, true);
+        opts.notifiers.hook.on_alert = Some("true".into());
+
+        let result = validate_input(&opts);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_validate_input_desktop_disabled_email_enabled() {
+        let mut opts = create_opts(None, true);
+        opts.notifiers.email.server = Some("smtp.example.com".into());
+
+        let result = validate_input(&opts);
 
         assert!(result.is_ok());
     }
@@ -1,7 +1,18 @@
-use std::ops;
+use std::str::FromStr;
+use std::{ops, time};
+
+use chrono::{Duration, Local, NaiveDateTime, NaiveTime};
+
+use crate::boost;
+use crate::device::kde_connect;
+use crate::locale;
+use crate::notification::{Channel, Fallback, QuietMode};
 
 const THRESHOLD_RANGE: ops::RangeInclusive<u8> = 0..=100;
 
+/// Minutes the alert can be snoozed for, up to a week.
+const SNOOZE_MINS_RANGE: ops::RangeInclusive<u64> = 0..=7 * 24 * 60;
+
 pub fn threshold(s: &str) -> Result<u8, String> {
     let threshold = s.parse::<u8>().map_err(|e| e.to_string())?;
 
@@ -16,6 +27,145 @@ pub fn threshold(s: &str) -> Result<u8, String> {
     }
 }
 
+/// Parse number of minutes the alert is snoozed for.
+pub fn snooze_mins(s: &str) -> Result<u64, String> {
+    let mins = s.parse::<u64>().map_err(|e| e.to_string())?;
+
+    if SNOOZE_MINS_RANGE.contains(&mins) {
+        Ok(mins)
+    } else {
+        Err(format!(
+            "not in range {}-{}",
+            SNOOZE_MINS_RANGE.start(),
+            SNOOZE_MINS_RANGE.end()
+        ))
+    }
+}
+
+/// Parse `NAME=ACTION` pair of a KDE Connect device name and its action.
+pub fn kde_connect_action(
+    s: &str,
+) -> Result<(String, kde_connect::Action), String> {
+    let (name, action) = s
+        .rsplit_once('=')
+        .ok_or_else(|| format!("expected NAME=ACTION, got \"{s}\""))?;
+
+    if name.is_empty() {
+        return Err("device name can't be empty".into());
+    }
+
+    Ok((name.into(), action.parse()?))
+}
+
+/// Parse the local time until which the boost lasts.
+pub fn boost_until(s: &str) -> Result<NaiveDateTime, String> {
+    next_time(s, Local::now().naive_local())
+}
+
+/// Parse `YYYY-MM-DD HH:MM` or the next occurrence of `HH:MM` after `now`.
+fn next_time(s: &str, now: NaiveDateTime) -> Result<NaiveDateTime, String> {
+    if let Ok(time) = NaiveDateTime::parse_from_str(s, boost::TIME_FORMAT) {
+        return Ok(time);
+    }
+
+    let time = NaiveTime::parse_from_str(s, "%H:%M").map_err(|_| {
+        format!("expected YYYY-MM-DD HH:MM or HH:MM, got \"{s}\"")
+    })?;
+    let today = now.date().and_time(time);
+
+    Ok(if today > now {
+        today
+    } else {
+        today + Duration::days(1)
+    })
+}
+
+/// Parse duration made of `h`, `m` and `s` parts, e.g. `1h30m` or `90s`.
+pub fn duration(s: &str) -> Result<time::Duration, String> {
+    let invalid = || format!("expected e.g. 30m, 1h30m or 90s, got \"{s}\"");
+    let mut secs = 0_u64;
+    let mut number = String::new();
+
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+
+            continue;
+        }
+
+        let unit = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+        let value = number.parse::<u64>().map_err(|_| invalid())?;
+
+        secs = value
+            .checked_mul(unit)
+            .and_then(|part| secs.checked_add(part))
+            .ok_or_else(invalid)?;
+        number.clear();
+    }
+
+    if s.is_empty() || !number.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(time::Duration::from_secs(secs))
+}
+
+/// Parse the locale into the language of a bundled catalog.
+pub fn locale(s: &str) -> Result<String, String> {
+    locale::language_of(s).map(Into::into).ok_or_else(|| {
+        format!(
+            "unsupported locale \"{s}\", expected one of {}",
+            locale::languages().join(", ")
+        )
+    })
+}
+
+/// Parse `CHANNEL=RETRIES` pair of a notification channel and its retries.
+pub fn channel_retries(s: &str) -> Result<(Channel, u32), String> {
+    key_value(s, "CHANNEL=RETRIES")
+}
+
+/// Parse `CHANNEL=FALLBACK` pair of a notification channel and its fallback.
+pub fn fallback(s: &str) -> Result<(Channel, Fallback), String> {
+    let (channel, fallback): (Channel, Fallback) =
+        key_value(s, "CHANNEL=FALLBACK")?;
+
+    if channel == fallback.channel {
+        return Err(format!("channel \"{channel}\" can't fall back to itself"));
+    }
+
+    Ok((channel, fallback))
+}
+
+/// Parse `CHANNEL=MODE` pair of a notification channel and its quiet hours
+/// mode.
+pub fn quiet_channel(s: &str) -> Result<(Channel, QuietMode), String> {
+    key_value(s, "CHANNEL=MODE")
+}
+
+/// Parse `KEY=VALUE` pair in the given format.
+fn key_value<K, V>(s: &str, format: &str) -> Result<(K, V), String>
+where
+    K: FromStr,
+    V: FromStr,
+    K::Err: ToString,
+    V::Err: ToString,
+{
+    let (key, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected {format}, got \"{s}\""))?;
+
+    Ok((
+        key.parse().map_err(|e: K::Err| e.to_string())?,
+        value.parse().map_err(|e: V::Err| e.to_string())?,
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,4 +218,157 @@ mod tests {
         test_threshold_number_and_invalid_digit: "1r",
         test_threshold_number_and_multiple_invalid_digit: "1rusty-battery",
     }
+
+    #[test]
+    fn test_snooze_mins() {
+        let result = snooze_mins("10080");
+
+        assert_eq!(result, Ok(10_080));
+    }
+
+    #[test]
+    fn test_snooze_mins_invalid() {
+        for value in ["10081", "18446744073709551615", "-1", "soon"] {
+            let result = snooze_mins(value);
+
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn test_kde_connect_action() {
+        let result = kde_connect_action("Lukas's Pixel 7=sms:+420123456789");
+
+        assert_eq!(
+            result,
+            Ok((
+                "Lukas's Pixel 7".into(),
+                kde_connect::Action::Sms {
+                    destination: "+420123456789".into()
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_kde_connect_action_invalid() {
+        for value in ["phone", "=ring", "phone=call"] {
+            let result = kde_connect_action(value);
+
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn test_next_time() {
+        let now = NaiveDateTime::parse_from_str(
+            "2026-10-18 12:00",
+            boost::TIME_FORMAT,
+        )
+        .unwrap();
+
+        let result = ["18:30", "06:30", "2026-10-25 06:30"].map(|value| {
+            next_time(value, now)
+                .map(|time| time.format(boost::TIME_FORMAT).to_string())
+        });
+
+        assert_eq!(
+            result,
+            [
+                Ok("2026-10-18 18:30".into()),
+                Ok("2026-10-19 06:30".into()),
+                Ok("2026-10-25 06:30".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_next_time_invalid() {
+        let result = next_time("tomorrow", Local::now().naive_local());
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_duration() {
+        let result =
+            ["30m", "1h30m", "90s", "2h"].map(|s| duration(s).unwrap());
+
+        assert_eq!(
+            result,
+            [1800, 5400, 90, 7200].map(time::Duration::from_secs)
+        );
+    }
+
+    #[test]
+    fn test_duration_invalid() {
+        for s in ["", "30", "m", "30x", "1h30"] {
+            let result = duration(s);
+
+            assert!(result.is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn test_locale() {
+        let result = locale("cs_CZ.UTF-8");
+
+        assert_eq!(result, Ok("cs".into()));
+    }
+
+    #[test]
+    fn test_locale_unsupported() {
+        let result = locale("de_DE");
+
+        assert_eq!(
+            result,
+            Err("unsupported locale \"de_DE\", expected one of en, cs".into())
+        );
+    }
+
+    #[test]
+    fn test_channel_retries() {
+        let result = channel_retries("kde-connect=4");
+
+        assert_eq!(result, Ok((Channel::KDEConnect, 4)));
+    }
+
+    #[test]
+    fn test_channel_retries_invalid() {
+        for value in ["kde-connect", "pager=4", "email=-1"] {
+            let result = channel_retries(value);
+
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn test_quiet_channel() {
+        let result = quiet_channel("desktop=downgrade");
+
+        assert_eq!(result, Ok((Channel::Desktop, QuietMode::Downgrade)));
+    }
+
+    #[test]
+    fn test_fallback() {
+        let result = fallback("kde-connect=desktop:critical");
+
+        assert_eq!(
+            result,
+            Ok((
+                Channel::KDEConnect,
+                Fallback {
+                    channel: Channel::Desktop,
+                    critical: true,
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_fallback_itself() {
+        let result = fallback("desktop=desktop:critical");
+
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,469 @@
+//! D-Bus service of the running `notify` process.
+//!
+//! The `notify` process owns the `com.lukaskucera.RustyBattery1` name
+//! on the session bus. The properties mirror the `Notifier` and the monitored
+//! battery after every refresh, the methods are forwarded to the `notify`
+//! loop just like the requests of the control socket.
+use std::collections::HashMap;
+use std::result;
+use std::sync::{mpsc, Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use zbus::blocking::{Connection, ConnectionBuilder};
+use zbus::zvariant::Value;
+use zbus::{dbus_interface, fdo, SignalContext};
+
+use crate::control::{self, Request};
+use crate::event::Event;
+use crate::notification::{Message, Notifier};
+use crate::{common, device, error};
+
+type Result<T> = result::Result<T, error::Service>;
+
+/// Well-known name of the service, also the name of its interface.
+pub const NAME: &str = "com.lukaskucera.RustyBattery1";
+/// Object path of the service.
+pub const PATH: &str = "/com/lukaskucera/RustyBattery1";
+
+/// Interface of the standard `PropertiesChanged` signal.
+const PROPERTIES_INTERFACE: &str = "org.freedesktop.DBus.Properties";
+
+/// Values of the properties of the service.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+struct Properties {
+    threshold: u8,
+    alert_active: bool,
+    percentage: u8,
+    state: String,
+}
+
+impl Properties {
+    fn new(battery: &device::Battery, notifier: &Notifier) -> Self {
+        Self {
+            threshold: notifier.threshold,
+            alert_active: notifier.alert().is_some(),
+            percentage: battery.percentage,
+            state: battery.state.to_string(),
+        }
+    }
+
+    /// Return D-Bus names and values of the properties
+    /// which differ from the `previous` ones.
+    fn changed(
+        &self,
+        previous: &Self,
+    ) -> HashMap<&'static str, Value<'static>> {
+        let mut changed = HashMap::new();
+
+        if self.threshold != previous.threshold {
+            changed.insert("Threshold", Value::from(self.threshold));
+        }
+
+        if self.alert_active != previous.alert_active {
+            changed.insert("AlertActive", Value::from(self.alert_active));
+        }
+
+        if self.percentage != previous.percentage {
+            changed.insert("Percentage", Value::from(self.percentage));
+        }
+
+        if self.state != previous.state {
+            changed.insert("State", Value::from(self.state.clone()));
+        }
+
+        changed
+    }
+}
+
+/// `com.lukaskucera.RustyBattery1` interface.
+struct Interface {
+    properties: Arc<Mutex<Properties>>,
+    sender: mpsc::Sender<Event>,
+}
+
+impl Interface {
+    /// Forward the `Request` to the `notify` loop and wait for its result.
+    fn request(&self, request: Request) -> fdo::Result<()> {
+        log::debug!("service: {request} called");
+
+        control::forward(&self.sender, request)
+            .map(|_| ())
+            .map_err(fdo::Error::Failed)
+    }
+
+    fn properties(&self) -> Properties {
+        self.properties
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+#[dbus_interface(name = "com.lukaskucera.RustyBattery1")]
+impl Interface {
+    /// Suppress the alert for the number of seconds.
+    fn snooze(&self, secs: u64) -> fdo::Result<()> {
+        let duration = Duration::from_secs(secs);
+
+        if control::snooze_end(duration).is_none() {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "snooze of {secs} seconds is too long"
+            )));
+        }
+
+        self.request(Request::Snooze(duration))
+    }
+
+    /// Change the threshold until the `notify` process exits.
+    fn set_threshold(&self, threshold: u8) -> fdo::Result<()> {
+        if threshold > 100 {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "threshold {threshold} not in range 0-100"
+            )));
+        }
+
+        self.request(Request::SetThreshold(threshold))
+    }
+
+    /// Suppress all alerts until resumed.
+    fn pause(&self) -> fdo::Result<()> {
+        self.request(Request::Pause)
+    }
+
+    /// Resume paused or snoozed alerts.
+    fn resume(&self) -> fdo::Result<()> {
+        self.request(Request::Resume)
+    }
+
+    #[dbus_interface(property)]
+    fn threshold(&self) -> u8 {
+        self.properties().threshold
+    }
+
+    #[dbus_interface(property)]
+    fn alert_active(&self) -> bool {
+        self.properties().alert_active
+    }
+
+    #[dbus_interface(property)]
+    fn percentage(&self) -> u8 {
+        self.properties().percentage
+    }
+
+    #[dbus_interface(property)]
+    fn state(&self) -> String {
+        self.properties().state
+    }
+
+    /// Alert with the ID and summary was raised.
+    ///
+    /// Declared for introspection, `Service::update` emits it.
+    #[dbus_interface(signal)]
+    async fn alert_raised(
+        ctxt: &SignalContext<'_>,
+        id: u64,
+        summary: &str,
+    ) -> zbus::Result<()>;
+
+    /// Alert with the ID was cleared.
+    ///
+    /// Declared for introspection, `Service::update` emits it.
+    #[dbus_interface(signal)]
+    async fn alert_cleared(
+        ctxt: &SignalContext<'_>,
+        id: u64,
+    ) -> zbus::Result<()>;
+}
+
+/// D-Bus service publishing the state of the `notify` loop.
+#[derive(Debug)]
+pub struct Service {
+    connection: Connection,
+    properties: Arc<Mutex<Properties>>,
+    /// ID of the alert announced by the latest `AlertRaised` signal.
+    alert: Option<u64>,
+}
+
+impl Service {
+    /// Own the well-known name on the session bus and serve the interface.
+    ///
+    /// Method calls are forwarded to the `notify` loop via the `Sender`.
+    pub fn new(sender: mpsc::Sender<Event>) -> Result<Self> {
+        Self::with_builder(ConnectionBuilder::session()?.name(NAME)?, sender)
+    }
+
+    fn with_builder(
+        builder: ConnectionBuilder,
+        sender: mpsc::Sender<Event>,
+    ) -> Result<Self> {
+        let properties = Arc::new(Mutex::new(Properties::default()));
+        let connection = builder
+            .serve_at(
+                PATH,
+                Interface {
+                    properties: Arc::clone(&properties),
+                    sender,
+                },
+            )?
+            .build()?;
+
+        log::info!("service: serving {NAME} at {PATH}");
+
+        Ok(Self {
+            connection,
+            properties,
+            alert: None,
+        })
+    }
+
+    /// Publish the current state of the `Battery` and the `Notifier`.
+    ///
+    /// Changed properties are announced by the `PropertiesChanged` signal,
+    /// raised and removed alerts by `AlertRaised` and `AlertCleared`.
+    pub fn update(
+        &mut self,
+        battery: &device::Battery,
+        notifier: &Notifier,
+        message: &Message,
+    ) {
+        self.publish(
+            Properties::new(battery, notifier),
+            notifier.alert(),
+            &message.summary,
+        );
+    }
+
+    fn publish(
+        &mut self,
+        current: Properties,
+        alert: Option<u64>,
+        summary: &str,
+    ) {
+        let changed = {
+            let mut properties = self
+                .properties
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let changed = current.changed(&properties);
+            *properties = current;
+
+            changed
+        };
+
+        if !changed.is_empty() {
+            log::trace!("service: properties changed = {changed:?}");
+
+            common::warn_on_err(
+                "service",
+                self.connection.emit_signal(
+                    None::<&str>,
+                    PATH,
+                    PROPERTIES_INTERFACE,
+                    "PropertiesChanged",
+                    &(NAME, changed, Vec::<&str>::new()),
+                ),
+            );
+        }
+
+        if alert == self.alert {
+            return;
+        }
+
+        if let Some(id) = self.alert {
+            log::debug!("service: alert {id} cleared");
+
+            common::warn_on_err(
+                "service",
+                self.connection.emit_signal(
+                    None::<&str>,
+                    PATH,
+                    NAME,
+                    "AlertCleared",
+                    &id,
+                ),
+            );
+        }
+
+        if let Some(id) = alert {
+            log::debug!("service: alert {id} raised");
+
+            common::warn_on_err(
+                "service",
+                self.connection.emit_signal(
+                    None::<&str>,
+                    PATH,
+                    NAME,
+                    "AlertRaised",
+                    &(id, summary),
+                ),
+            );
+        }
+
+        self.alert = alert;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::net::UnixStream;
+    use std::thread;
+
+    use zbus::blocking::{MessageIterator, Proxy, ProxyBuilder};
+    use zbus::CacheProperties;
+
+    use super::*;
+
+    /// Serve the service on a private peer-to-peer connection.
+    ///
+    /// Return the service, the client connection and the `Receiver`
+    /// of the forwarded requests.
+    fn create_service() -> (Service, Connection, mpsc::Receiver<Event>) {
+        let (service_stream, client_stream) = UnixStream::pair().unwrap();
+        let (sender, receiver) = mpsc::channel();
+
+        let service = thread::spawn(move || {
+            let guid = zbus::Guid::generate();
+            let builder = ConnectionBuilder::unix_stream(service_stream)
+                .server(&guid)
+                .p2p();
+
+            Service::with_builder(builder, sender).unwrap()
+        });
+
+        let client = ConnectionBuilder::unix_stream(client_stream)
+            .p2p()
+            .build()
+            .unwrap();
+
+        (service.join().unwrap(), client, receiver)
+    }
+
+    fn create_proxy(client: &Connection) -> Proxy<'_> {
+        ProxyBuilder::<Proxy>::new_bare(client)
+            .destination(NAME)
+            .unwrap()
+            .path(PATH)
+            .unwrap()
+            .interface(NAME)
+            .unwrap()
+            .cache_properties(CacheProperties::No)
+            .build()
+            .unwrap()
+    }
+
+    fn create_properties(alert_active: bool) -> Properties {
+        Properties {
+            threshold: 80,
+            alert_active,
+            percentage: 85,
+            state: "Charging".into(),
+        }
+    }
+
+    /// Answer the next forwarded request with the `Response`.
+    ///
+    /// Return the answered request.
+    fn respond(
+        receiver: mpsc::Receiver<Event>,
+        response: control::Response,
+    ) -> thread::JoinHandle<Request> {
+        thread::spawn(move || match receiver.recv().unwrap() {
+            Event::Control(request, responder) => {
+                responder.send(response).unwrap();
+
+                request
+            }
+            event => panic!("unexpected event {event:?}"),
+        })
+    }
+
+    #[test]
+    fn test_service_properties() {
+        let (mut service, client, _receiver) = create_service();
+        let proxy = create_proxy(&client);
+
+        service.publish(create_properties(true), Some(1), "summary");
+
+        assert_eq!(proxy.get_property::<u8>("Threshold").unwrap(), 80);
+        assert!(proxy.get_property::<bool>("AlertActive").unwrap());
+        assert_eq!(proxy.get_property::<u8>("Percentage").unwrap(), 85);
+        assert_eq!(proxy.get_property::<String>("State").unwrap(), "Charging");
+    }
+
+    #[test]
+    fn test_service_signals() {
+        let (mut service, client, _receiver) = create_service();
+        let messages = MessageIterator::from(&client);
+
+        service.publish(create_properties(true), Some(1), "summary");
+        service.publish(create_properties(false), None, "summary");
+
+        let members = messages
+            .take(4)
+            .map(|message| {
+                message.unwrap().member().unwrap().as_str().to_owned()
+            })
+            .collect::<Vec<String>>();
+        assert_eq!(
+            members,
+            [
+                "PropertiesChanged",
+                "AlertRaised",
+                "PropertiesChanged",
+                "AlertCleared",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_service_set_threshold() {
+        let (_service, client, receiver) = create_service();
+        let handle = respond(receiver, Ok(serde_json::Value::Null));
+
+        create_proxy(&client)
+            .call::<_, _, ()>("SetThreshold", &(85_u8,))
+            .unwrap();
+
+        assert_eq!(handle.join().unwrap(), Request::SetThreshold(85));
+    }
+
+    #[test]
+    fn test_service_set_threshold_invalid() {
+        let (_service, client, _receiver) = create_service();
+
+        let result =
+            create_proxy(&client).call::<_, _, ()>("SetThreshold", &(101_u8,));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_service_snooze_failed() {
+        let (_service, client, receiver) = create_service();
+        let handle = respond(receiver, Err("loop failure".into()));
+
+        let result =
+            create_proxy(&client).call::<_, _, ()>("Snooze", &(90_u64,));
+
+        assert_eq!(
+            handle.join().unwrap(),
+            Request::Snooze(Duration::from_secs(90))
+        );
+        assert!(result.unwrap_err().to_string().contains("loop failure"));
+    }
+
+    #[test]
+    fn test_service_snooze_too_long() {
+        let (_service, client, receiver) = create_service();
+
+        let result =
+            create_proxy(&client).call::<_, _, ()>("Snooze", &(u64::MAX,));
+
+        assert!(matches!(
+            result,
+            Err(zbus::Error::MethodError(name, _, _))
+                if name.as_str() == "org.freedesktop.DBus.Error.InvalidArgs"
+        ));
+        assert!(receiver.try_recv().is_err());
+    }
+} // tests
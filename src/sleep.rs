@@ -0,0 +1,259 @@
+//! Suspend and resume of the system.
+//!
+//! logind announces both by its `PrepareForSleep` signal which the watching
+//! thread forwards to the `notify` loop. Without logind a resume is detected
+//! by `Clock` as a jump of the boot time clock, which keeps running while
+//! the system is suspended, ahead of the monotonic one, which doesn't.
+use std::sync::mpsc;
+use std::time::Duration;
+use std::{ptr, result, thread};
+
+use zbus::blocking::{Connection, Proxy, ProxyBuilder};
+use zbus::zvariant::OwnedFd;
+use zbus::CacheProperties;
+
+use crate::event::Event;
+use crate::{common, error};
+
+type Result<T> = result::Result<T, error::Sleep>;
+
+/// Well-known name of logind.
+const LOGIN_SERVICE: &str = "org.freedesktop.login1";
+/// Object path of the logind manager.
+pub const MANAGER_PATH: &str = "/org/freedesktop/login1";
+/// Interface of the logind manager.
+pub const MANAGER_INTERFACE: &str = "org.freedesktop.login1.Manager";
+/// Signal emitted before a suspend with `true` and after a resume with `false`.
+const PREPARE_FOR_SLEEP_SIGNAL: &str = "PrepareForSleep";
+
+/// Minimum difference of the clocks which is considered a suspend.
+const SUSPEND_TOLERANCE: Duration = Duration::from_secs(5);
+
+/// Delay inhibitor lock of logind, the suspend proceeds once it's dropped.
+#[derive(Debug)]
+pub struct Inhibitor {
+    /// Descriptor of the lock, closing it releases the lock.
+    _fd: OwnedFd,
+}
+
+/// Forward suspends and resumes announced by logind via the `Sender`.
+///
+/// If `inhibit` is set, a delay inhibitor lock is held while the system
+/// is awake and sent along with `Event::Suspend`.
+pub fn watch(sender: mpsc::Sender<Event>, inhibit: bool) -> Result<()> {
+    watch_on(&Connection::system()?, sender, inhibit)
+}
+
+fn watch_on(
+    connection: &Connection,
+    sender: mpsc::Sender<Event>,
+    inhibit: bool,
+) -> Result<()> {
+    let proxy = ProxyBuilder::<Proxy>::new_bare(connection)
+        .destination(LOGIN_SERVICE)?
+        .path(MANAGER_PATH)?
+        .interface(MANAGER_INTERFACE)?
+        .cache_properties(CacheProperties::No)
+        .build()?;
+    let signals = proxy.receive_signal(PREPARE_FOR_SLEEP_SIGNAL)?;
+    let mut inhibitor = acquire(&proxy, inhibit);
+
+    log::debug!("sleep: watching {PREPARE_FOR_SLEEP_SIGNAL}");
+
+    thread::spawn(move || {
+        for signal in signals {
+            let event = match signal.body::<bool>() {
+                Ok(true) => {
+                    log::info!("sleep: system is suspending");
+
+                    Event::Suspend(inhibitor.take())
+                }
+                Ok(false) => {
+                    log::info!("sleep: system resumed");
+
+                    // the lock of the suspend was sent to the loop
+                    inhibitor = acquire(&proxy, inhibit);
+
+                    Event::Resume
+                }
+                Err(e) => {
+                    log::warn!("sleep: {e}");
+
+                    continue;
+                }
+            };
+
+            if sender.send(event).is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Acquire delay inhibitor lock of the suspend if `inhibit` is set.
+fn acquire(proxy: &Proxy, inhibit: bool) -> Option<Inhibitor> {
+    if !inhibit {
+        return None;
+    }
+
+    common::warn_on_err(
+        "sleep",
+        proxy
+            .call(
+                "Inhibit",
+                &(
+                    "sleep",
+                    "rusty-battery",
+                    "Deliver a pending battery alert",
+                    "delay",
+                ),
+            )
+            .map(|fd| Inhibitor { _fd: fd }),
+    )
+}
+
+/// Readings of the monotonic and the boot time clock.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Clock {
+    monotonic: Duration,
+    boottime: Duration,
+}
+
+impl Clock {
+    pub fn now() -> Self {
+        Self {
+            monotonic: read(libc::CLOCK_MONOTONIC),
+            boottime: read(libc::CLOCK_BOOTTIME),
+        }
+    }
+
+    /// Return how long the system was suspended between the readings.
+    ///
+    /// Differences shorter than `SUSPEND_TOLERANCE` aren't considered
+    /// a suspend.
+    pub fn suspended(self, later: Self) -> Option<Duration> {
+        let suspended = later
+            .boottime
+            .saturating_sub(self.boottime)
+            .saturating_sub(later.monotonic.saturating_sub(self.monotonic));
+
+        (suspended >= SUSPEND_TOLERANCE).then_some(suspended)
+    }
+}
+
+/// Return the time of the clock.
+fn read(clock: libc::clockid_t) -> Duration {
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+
+    // SAFETY: the pointer is valid for the duration of the call
+    if unsafe { libc::clock_gettime(clock, ptr::addr_of_mut!(time)) } != 0 {
+        log::warn!("sleep: clock {clock}: {}", std::io::Error::last_os_error());
+    }
+
+    Duration::new(
+        u64::try_from(time.tv_sec).unwrap_or_default(),
+        u32::try_from(time.tv_nsec).unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing;
+
+    use super::*;
+
+    fn create_clock(monotonic: u64, boottime: u64) -> Clock {
+        Clock {
+            monotonic: Duration::from_secs(monotonic),
+            boottime: Duration::from_secs(boottime),
+        }
+    }
+
+    fn emit(service: &Connection, suspending: bool) {
+        service
+            .emit_signal(
+                None::<&str>,
+                MANAGER_PATH,
+                MANAGER_INTERFACE,
+                PREPARE_FOR_SLEEP_SIGNAL,
+                &suspending,
+            )
+            .unwrap();
+    }
+
+    fn receive(receiver: &mpsc::Receiver<Event>) -> Event {
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap()
+    }
+
+    #[test]
+    fn test_clock_suspended() {
+        let clock = create_clock(100, 150);
+
+        let suspended = clock.suspended(create_clock(130, 2870));
+
+        assert_eq!(suspended, Some(Duration::from_secs(2690)));
+    }
+
+    #[test]
+    fn test_clock_suspended_awake() {
+        let clock = create_clock(100, 150);
+
+        let suspended = clock.suspended(create_clock(130, 181));
+
+        assert_eq!(suspended, None);
+    }
+
+    #[test]
+    fn test_clock_now() {
+        let clock = Clock::now();
+
+        assert_eq!(clock.suspended(Clock::now()), None);
+    }
+
+    #[test]
+    fn test_watch() {
+        let (client, service) = testing::logind_service();
+        let (sender, receiver) = mpsc::channel();
+        watch_on(&client, sender, false).unwrap();
+
+        emit(&service, true);
+        let suspend = receive(&receiver);
+        emit(&service, false);
+        let resume = receive(&receiver);
+
+        assert!(matches!(suspend, Event::Suspend(None)));
+        assert!(matches!(resume, Event::Resume));
+        assert!(testing::logind_inhibitors(&service).is_empty());
+    }
+
+    #[test]
+    fn test_watch_inhibit() {
+        let (client, service) = testing::logind_service();
+        let (sender, receiver) = mpsc::channel();
+        watch_on(&client, sender, true).unwrap();
+
+        emit(&service, true);
+        let suspend = receive(&receiver);
+        emit(&service, false);
+        let resume = receive(&receiver);
+
+        assert!(matches!(suspend, Event::Suspend(Some(_))));
+        assert!(matches!(resume, Event::Resume));
+        let inhibitors = testing::logind_inhibitors(&service);
+        assert_eq!(inhibitors.len(), 2);
+        assert_eq!(
+            inhibitors[0],
+            [
+                "sleep",
+                "rusty-battery",
+                "Deliver a pending battery alert",
+                "delay"
+            ]
+        );
+    }
+} // tests
@@ -0,0 +1,325 @@
+//! Status of the running `notify` process.
+//!
+//! The `notify` loop writes its status into a file in the runtime directory
+//! after every battery refresh, the `status` subcommand reads it.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::{env, process, result};
+
+use serde_json::{json, Value};
+
+use crate::boost::{self, Boost};
+use crate::notification::Notifier;
+use crate::{common, device, error, locale};
+
+type Result<T> = result::Result<T, error::Status>;
+
+/// Name of the directory of the runtime files.
+const DIR_NAME: &str = "rusty-battery";
+/// Name of the status file.
+const FILE_NAME: &str = "status.json";
+
+/// Return the directory of the runtime files.
+///
+/// `$XDG_RUNTIME_DIR` is used if set, otherwise a directory of the current
+/// user in the temporary directory.
+pub fn runtime_dir() -> PathBuf {
+    env::var_os("XDG_RUNTIME_DIR").map_or_else(
+        || {
+            env::temp_dir()
+                .join(format!("{DIR_NAME}-{}", common::current_uid()))
+        },
+        |dir| PathBuf::from(dir).join(DIR_NAME),
+    )
+}
+
+/// Return path of the status file.
+pub fn path() -> PathBuf {
+    runtime_dir().join(FILE_NAME)
+}
+
+/// Write status of the battery, the boost and the notification channels.
+pub fn write(
+    battery: &device::Battery,
+    notifier: &mut Notifier,
+    boost: Option<Boost>,
+) -> Result<()> {
+    write_value(&path(), &collect(battery, notifier, boost))?;
+
+    log::trace!("status: written");
+
+    Ok(())
+}
+
+/// Return status of the battery, the boost and the notification channels.
+pub fn collect(
+    battery: &device::Battery,
+    notifier: &mut Notifier,
+    boost: Option<Boost>,
+) -> Value {
+    let channels = notifier
+        .channels()
+        .into_iter()
+        .map(|(channel, health, record)| {
+            json!({
+                "channel": channel.to_string(),
+                "circuit": health.circuit().to_string(),
+                "attempts": health.attempts,
+                "failures": health.failures,
+                "consecutive": health.consecutive,
+                "alert": record.map(|record| record.alert),
+                "sent_secs_ago": record
+                    .map(|record| record.sent_at.elapsed().as_secs()),
+            })
+        })
+        .collect::<Vec<Value>>();
+
+    json!({
+        "pid": process::id(),
+        "battery": {
+            "model": battery.model,
+            "percentage": battery.percentage,
+            "state": battery.state.to_string(),
+        },
+        "threshold": notifier.threshold,
+        "boost": boost.map(Boost::to_json),
+        "paused": notifier.paused(),
+        "channels": channels,
+    })
+}
+
+/// Remove the status file of the terminating process.
+pub fn remove() {
+    match fs::remove_file(path()) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => {
+            log::warn!("status: failed to remove: {e}");
+        }
+        _ => log::debug!("status: removed"),
+    }
+}
+
+/// Print status of the running `notify` process.
+pub fn print() -> Result<()> {
+    let status = read(&path())?;
+
+    for line in format(&status) {
+        println!("{line}");
+    }
+
+    Ok(())
+}
+
+/// Atomically write the JSON value into the file at the path.
+fn write_value(path: &Path, value: &Value) -> Result<()> {
+    common::write_atomic(path, &serde_json::to_vec_pretty(value)?)?;
+
+    Ok(())
+}
+
+/// Read status of the running process from the file at the path.
+fn read(path: &Path) -> Result<Value> {
+    let data = match fs::read(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(error::Status::NotRunning);
+        }
+        data => data?,
+    };
+    let status: Value = serde_json::from_slice(&data)?;
+
+    match status["pid"].as_u64() {
+        Some(pid) if running(pid) => Ok(status),
+        _ => Err(error::Status::NotRunning),
+    }
+}
+
+/// Return whether the process with the PID is running.
+///
+/// Processes are assumed to be running on systems without `/proc`.
+fn running(pid: u64) -> bool {
+    let proc = Path::new("/proc");
+
+    !proc.exists() || proc.join(pid.to_string()).exists()
+}
+
+/// Return localized lines of the status.
+pub fn format(status: &Value) -> Vec<String> {
+    let battery = &status["battery"];
+    let state = battery["state"].as_str().map_or_else(
+        || "None".into(),
+        |state| locale::format("battery-state", &[("state", state.into())]),
+    );
+    let mut lines = vec![
+        locale::format(
+            "status-running",
+            &[("pid", status["pid"].to_string().into())],
+        ),
+        locale::format(
+            "status-battery",
+            &[
+                ("model", battery["model"].to_string().into()),
+                ("percentage", battery["percentage"].to_string().into()),
+                ("state", state.into()),
+                ("threshold", status["threshold"].to_string().into()),
+            ],
+        ),
+    ];
+
+    if let Ok(boost) = Boost::from_json(&status["boost"]) {
+        lines.push(locale::format(
+            "status-boost",
+            &[("boost", boost::describe(boost).into())],
+        ));
+    }
+
+    if status["paused"].as_bool() == Some(true) {
+        lines.push(locale::text("status-paused"));
+    }
+
+    lines.extend([String::new(), locale::text("status-channels")]);
+
+    lines.extend(
+        status["channels"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .map(|(index, channel)| {
+                let alert = channel["alert"].as_u64().map_or_else(
+                    || locale::text("status-alert-none"),
+                    |alert| {
+                        locale::format(
+                            "status-alert",
+                            &[
+                                ("alert", alert.to_string().into()),
+                                (
+                                    "secs",
+                                    channel["sent_secs_ago"].to_string().into(),
+                                ),
+                            ],
+                        )
+                    },
+                );
+
+                locale::format(
+                    "status-channel",
+                    &[
+                        ("index", (index + 1).to_string().into()),
+                        (
+                            "channel",
+                            common::format_option(channel["channel"].as_str())
+                                .into(),
+                        ),
+                        (
+                            "circuit",
+                            common::format_option(channel["circuit"].as_str())
+                                .into(),
+                        ),
+                        ("attempts", channel["attempts"].to_string().into()),
+                        ("failures", channel["failures"].to_string().into()),
+                        (
+                            "consecutive",
+                            channel["consecutive"].to_string().into(),
+                        ),
+                        ("alert", alert.into()),
+                    ],
+                )
+            }),
+    );
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_status(pid: u32) -> Value {
+        json!({
+            "pid": pid,
+            "battery": {
+                "model": "test-model",
+                "percentage": 85,
+                "state": "Charging",
+            },
+            "threshold": 100,
+            "boost": {"threshold": 100, "until": null},
+            "paused": true,
+            "channels": [{
+                "channel": "desktop",
+                "circuit": "open",
+                "attempts": 9,
+                "failures": 6,
+                "consecutive": 2,
+                "alert": 3,
+                "sent_secs_ago": 120,
+            }, {
+                "channel": "email",
+                "circuit": "closed",
+                "attempts": 0,
+                "failures": 0,
+                "consecutive": 0,
+                "alert": null,
+                "sent_secs_ago": null,
+            }],
+        })
+    }
+
+    fn temporary_path(name: &str) -> PathBuf {
+        env::temp_dir()
+            .join(format!("rusty-battery-test-{}", process::id()))
+            .join(name)
+    }
+
+    #[test]
+    fn test_write_read() {
+        let path = temporary_path("status.json");
+        let status = create_status(process::id());
+
+        write_value(&path, &status).unwrap();
+        let result = read(&path).unwrap();
+
+        assert_eq!(result, status);
+    }
+
+    #[test]
+    fn test_read_missing() {
+        let result = read(&temporary_path("missing.json"));
+
+        assert!(matches!(result, Err(error::Status::NotRunning)));
+    }
+
+    #[test]
+    fn test_read_stale() {
+        let path = temporary_path("stale.json");
+        write_value(&path, &create_status(u32::MAX)).unwrap();
+
+        let result = read(&path);
+
+        assert!(matches!(result, Err(error::Status::NotRunning)));
+    }
+
+    #[test]
+    fn test_format() {
+        let status = create_status(42);
+
+        let result = format(&status);
+
+        assert_eq!(
+            result,
+            [
+                "rusty-battery is running: pid = 42",
+                "Battery: model = \"test-model\", percentage = 85%, \
+                state = Charging, threshold = 100%",
+                "Boost: threshold = 100% until the charger is unplugged",
+                "Alerts are paused",
+                "",
+                "Channels",
+                "1. desktop: circuit = open, attempts = 9, failures = 6, \
+                consecutive failures = 2, alert = 3 sent 120 seconds ago",
+                "2. email: circuit = closed, attempts = 0, failures = 0, \
+                consecutive failures = 0, alert = None",
+            ]
+        );
+    }
+} // tests
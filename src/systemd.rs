@@ -0,0 +1,156 @@
+//! systemd integration of the `notify` process.
+//!
+//! Services with `Type=notify` are reported ready once the battery
+//! and the notifiers are initialized, the `notify` loop pings the watchdog
+//! if `WatchdogSec` is set and publishes its state as the service status.
+//! Outside of systemd the notifications are no-ops.
+use std::time::Duration;
+
+use sd_notify::NotifyState;
+
+use crate::device::BatteryState;
+use crate::notification::Notifier;
+use crate::{common, device, logger};
+
+/// Notifications of the systemd service manager.
+#[derive(Debug)]
+pub struct Systemd {
+    /// Interval of the watchdog pings, half of `WatchdogSec`.
+    watchdog: Option<Duration>,
+    /// Latest sent status.
+    status: String,
+}
+
+impl Systemd {
+    pub fn new() -> Self {
+        let mut usec = 0;
+        let watchdog = sd_notify::watchdog_enabled(false, &mut usec)
+            .then(|| Duration::from_micros(usec) / 2);
+
+        if let Some(interval) = watchdog {
+            log::debug!("systemd: watchdog ping every {interval:?}");
+        }
+
+        Self {
+            watchdog,
+            status: String::new(),
+        }
+    }
+
+    /// Ping the watchdog and publish the current state of the `Battery`
+    /// and the `Notifier`.
+    ///
+    /// The state is also attached to the journal entries.
+    pub fn update(&mut self, battery: &device::Battery, notifier: &Notifier) {
+        let alert_active = notifier.alert().is_some();
+        let status = status(
+            battery.percentage,
+            battery.state,
+            notifier.threshold,
+            alert_active,
+        );
+        let mut notifications = vec![];
+
+        if self.watchdog.is_some() {
+            notifications.push(NotifyState::Watchdog);
+        }
+
+        if status != self.status {
+            notifications.push(NotifyState::Status(&status));
+        }
+
+        send(&notifications);
+
+        logger::set_fields(vec![
+            ("BATTERY_PERCENTAGE", battery.percentage.to_string()),
+            ("BATTERY_STATE", battery.state.to_string()),
+            ("THRESHOLD", notifier.threshold.to_string()),
+            ("ALERT_ACTIVE", alert_active.to_string()),
+        ]);
+
+        self.status = status;
+    }
+
+    /// Return how long the `notify` loop may wait before its next iteration.
+    ///
+    /// The refresh interval is shortened so the watchdog is pinged in time.
+    pub fn timeout(&self, refresh_duration: Duration) -> Duration {
+        self.watchdog
+            .map_or(refresh_duration, |interval| interval.min(refresh_duration))
+    }
+}
+
+/// Notify that the `notify` process finished its initialization.
+pub fn ready() {
+    log::debug!("systemd: ready");
+
+    send(&[NotifyState::Ready]);
+}
+
+/// Notify that the `notify` process is shutting down.
+pub fn stopping() {
+    send(&[NotifyState::Stopping]);
+}
+
+fn send(states: &[NotifyState]) {
+    if !states.is_empty() {
+        common::warn_on_err("systemd", sd_notify::notify(false, states));
+    }
+}
+
+/// Return the service status describing the battery and the alert.
+fn status(
+    percentage: u8,
+    state: BatteryState,
+    threshold: u8,
+    alert_active: bool,
+) -> String {
+    format!(
+        "percentage = {percentage}%, state = {state}, threshold = {threshold}%, \
+        alert = {}",
+        if alert_active { "active" } else { "inactive" }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_systemd(watchdog: Option<Duration>) -> Systemd {
+        Systemd {
+            watchdog,
+            status: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_status() {
+        let status = status(85, BatteryState::Charging, 80, true);
+
+        assert_eq!(
+            status,
+            "percentage = 85%, state = Charging, threshold = 80%, \
+            alert = active"
+        );
+    }
+
+    #[test]
+    fn test_systemd_timeout() {
+        let systemd = create_systemd(None);
+
+        let timeout = systemd.timeout(Duration::from_secs(30));
+
+        assert_eq!(timeout, Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_systemd_timeout_watchdog() {
+        let systemd = create_systemd(Some(Duration::from_secs(10)));
+
+        let watchdog = systemd.timeout(Duration::from_secs(30));
+        let refresh = systemd.timeout(Duration::from_secs(5));
+
+        assert_eq!(watchdog, Duration::from_secs(10));
+        assert_eq!(refresh, Duration::from_secs(5));
+    }
+} // tests
@@ -0,0 +1,558 @@
+//! Stand-ins for external services used by tests.
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::io::{FromRawFd, IntoRawFd};
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::{env, fs, process};
+
+/// HTTP request received by the stand-in server.
+#[derive(Debug)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Request {
+    /// Return value of the header with the given case insensitive name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Start a stand-in HTTP server on localhost.
+///
+/// Every connection is answered with the next response from `responses`,
+/// the server stops once all of them have been sent.
+///
+/// Return the base URL of the server and a `Receiver` of all the requests.
+pub fn http_server(
+    responses: Vec<(u16, String)>,
+) -> (String, mpsc::Receiver<Request>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        for (status, body) in responses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let request = read_request(&mut reader);
+            sender.send(request).unwrap();
+
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 {status} Stand-in\r\n\
+                Content-Type: application/json\r\n\
+                Content-Length: {}\r\n\
+                Connection: close\r\n\r\n{body}",
+                body.len(),
+            )
+            .unwrap();
+        }
+    });
+
+    (url, receiver)
+}
+
+fn read_request<R: BufRead>(reader: &mut R) -> Request {
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+
+    let mut request_line = line.split_whitespace();
+    let method = request_line.next().unwrap().to_owned();
+    let path = request_line.next().unwrap().to_owned();
+
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+
+        match line.trim_end().split_once(':') {
+            None => break,
+            Some((key, value)) => {
+                headers.push((key.trim().to_owned(), value.trim().to_owned()));
+            }
+        }
+    }
+
+    let length = headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("content-length"))
+        .map_or(0, |(_, value)| value.parse().unwrap());
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).unwrap();
+
+    Request {
+        method,
+        path,
+        headers,
+        body: String::from_utf8(body).unwrap(),
+    }
+}
+
+/// MQTT control packet received by the stand-in broker.
+#[derive(Debug)]
+pub struct MqttPacket {
+    /// Type of the packet including its flags.
+    pub kind: u8,
+    pub body: Vec<u8>,
+}
+
+impl MqttPacket {
+    /// Return whether the packet is a retained PUBLISH.
+    pub const fn retain(&self) -> bool {
+        self.kind & 0x01 == 0x01
+    }
+
+    /// Return whether the body contains the given bytes.
+    pub fn contains(&self, needle: &[u8]) -> bool {
+        self.body
+            .windows(needle.len())
+            .any(|window| window == needle)
+    }
+
+    /// Return topic and payload of a `QoS` 0 PUBLISH packet.
+    pub fn publish(&self) -> (String, String) {
+        assert_eq!(self.kind & 0xf0, 0x30);
+
+        let length =
+            usize::from(u16::from_be_bytes([self.body[0], self.body[1]]));
+        let (topic, payload) = self.body[2..].split_at(length);
+
+        (
+            String::from_utf8(topic.to_vec()).unwrap(),
+            String::from_utf8(payload.to_vec()).unwrap(),
+        )
+    }
+}
+
+/// Start a stand-in MQTT broker on localhost.
+///
+/// Every connection is acknowledged with the given CONNACK return code.
+///
+/// Return the `host:port` address of the broker and a `Receiver` of all
+/// the packets sent to it.
+pub fn mqtt_broker(return_code: u8) -> (String, mpsc::Receiver<MqttPacket>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        for mut stream in listener.incoming().map_while(Result::ok) {
+            let mut acknowledged = false;
+
+            while let Some(packet) = read_mqtt_packet(&mut stream) {
+                if !acknowledged {
+                    stream.write_all(&[0x20, 0x02, 0x00, return_code]).unwrap();
+                    acknowledged = true;
+                }
+
+                if sender.send(packet).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    (address, receiver)
+}
+
+fn read_mqtt_packet<R: Read>(reader: &mut R) -> Option<MqttPacket> {
+    let mut byte = [0];
+    reader.read_exact(&mut byte).ok()?;
+    let kind = byte[0];
+
+    let mut length = 0;
+    let mut multiplier = 1;
+    loop {
+        reader.read_exact(&mut byte).ok()?;
+        length += usize::from(byte[0] & 0x7f) * multiplier;
+        multiplier *= 128;
+
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(MqttPacket { kind, body })
+}
+
+/// Start a stand-in SMTP server on localhost.
+///
+/// Every command is accepted, including any authentication attempt.
+///
+/// Return the port of the server and a `Receiver` of the transcripts of all
+/// the sessions, every transcript is sent once its connection is closed.
+pub fn smtp_server() -> (u16, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        for stream in listener.incoming().map_while(Result::ok) {
+            let mut reader = BufReader::new(stream);
+            let transcript = smtp_session(&mut reader);
+
+            if sender.send(transcript).is_err() {
+                return;
+            }
+        }
+    });
+
+    (port, receiver)
+}
+
+fn smtp_session(reader: &mut BufReader<TcpStream>) -> String {
+    let mut transcript = String::new();
+    let mut data = false;
+    let mut line = String::new();
+
+    reader.get_mut().write_all(b"220 localhost\r\n").unwrap();
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            break;
+        }
+        transcript.push_str(&line);
+
+        let reply: &[u8] = if data {
+            if line != ".\r\n" {
+                continue;
+            }
+            data = false;
+
+            b"250 queued\r\n"
+        } else {
+            match line
+                .get(..4)
+                .unwrap_or_default()
+                .to_ascii_uppercase()
+                .as_str()
+            {
+                "EHLO" => b"250-localhost\r\n250 AUTH PLAIN LOGIN\r\n",
+                "AUTH" => b"235 authenticated\r\n",
+                "DATA" => {
+                    data = true;
+
+                    b"354 end data with .\r\n"
+                }
+                "QUIT" => {
+                    reader.get_mut().write_all(b"221 bye\r\n").unwrap();
+
+                    break;
+                }
+                _ => b"250 ok\r\n",
+            }
+        };
+
+        reader.get_mut().write_all(reply).unwrap();
+    }
+
+    transcript
+}
+
+/// Device served by the stand-in KDE Connect D-Bus service.
+#[derive(Debug, Clone)]
+pub struct KDEConnectDevice {
+    pub id: String,
+    pub name: String,
+    pub kind: String,
+    pub reachable: bool,
+    pub paired: bool,
+    /// Charge and charging state reported by the battery plugin,
+    /// `None` if the device doesn't provide the plugin.
+    pub battery: Option<(i32, bool)>,
+}
+
+impl KDEConnectDevice {
+    /// Return a reachable paired phone with the given battery.
+    pub fn phone(id: &str, name: &str, battery: Option<(i32, bool)>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            kind: "smartphone".into(),
+            reachable: true,
+            paired: true,
+            battery,
+        }
+    }
+}
+
+/// Stand-in `org.kde.kdeconnect.daemon` interface.
+pub struct KDEConnectDaemon {
+    pub devices: Vec<KDEConnectDevice>,
+}
+
+#[zbus::dbus_interface(name = "org.kde.kdeconnect.daemon")]
+impl KDEConnectDaemon {
+    #[dbus_interface(name = "devices")]
+    fn devices(&self, only_reachable: bool, only_paired: bool) -> Vec<String> {
+        self.devices
+            .iter()
+            .filter(|device| !only_reachable || device.reachable)
+            .filter(|device| !only_paired || device.paired)
+            .map(|device| device.id.clone())
+            .collect()
+    }
+}
+
+/// Stand-in `org.kde.kdeconnect.device` interface.
+struct KDEConnectDeviceInterface {
+    device: KDEConnectDevice,
+}
+
+#[zbus::dbus_interface(name = "org.kde.kdeconnect.device")]
+impl KDEConnectDeviceInterface {
+    #[dbus_interface(property, name = "name")]
+    fn name(&self) -> String {
+        self.device.name.clone()
+    }
+
+    #[dbus_interface(property, name = "type")]
+    fn kind(&self) -> String {
+        self.device.kind.clone()
+    }
+
+    #[dbus_interface(property, name = "supportedPlugins")]
+    fn supported_plugins(&self) -> Vec<String> {
+        let mut plugins =
+            vec!["kdeconnect_ping".into(), "kdeconnect_share".into()];
+
+        if self.device.battery.is_some() {
+            plugins.push("kdeconnect_battery".into());
+        }
+
+        plugins
+    }
+}
+
+/// Stand-in `org.kde.kdeconnect.device.battery` interface.
+pub struct KDEConnectBattery {
+    pub charge: i32,
+    pub charging: bool,
+}
+
+#[zbus::dbus_interface(name = "org.kde.kdeconnect.device.battery")]
+impl KDEConnectBattery {
+    #[dbus_interface(property, name = "charge")]
+    fn charge(&self) -> i32 {
+        self.charge
+    }
+
+    #[dbus_interface(property, name = "isCharging")]
+    fn is_charging(&self) -> bool {
+        self.charging
+    }
+}
+
+/// Stand-in `org.kde.kdeconnect.device.ping` interface.
+#[derive(Default)]
+pub struct KDEConnectPing {
+    pub messages: Vec<String>,
+}
+
+#[zbus::dbus_interface(name = "org.kde.kdeconnect.device.ping")]
+impl KDEConnectPing {
+    #[dbus_interface(name = "sendPing")]
+    fn send_ping(&mut self, message: String) {
+        self.messages.push(message);
+    }
+}
+
+/// Stand-in `org.kde.kdeconnect.device.share` interface.
+#[derive(Default)]
+pub struct KDEConnectShare {
+    pub texts: Vec<String>,
+}
+
+#[zbus::dbus_interface(name = "org.kde.kdeconnect.device.share")]
+impl KDEConnectShare {
+    #[dbus_interface(name = "shareText")]
+    fn share_text(&mut self, text: String) {
+        self.texts.push(text);
+    }
+}
+
+/// Start a stand-in KDE Connect D-Bus service serving the given devices.
+///
+/// Every device provides the ping and share plugins but not the find
+/// my phone plugin. The service runs on a peer-to-peer connection,
+/// so no bus is needed.
+///
+/// Return the client connection and the service connection whose object
+/// server can be used to change the served values.
+pub fn kde_connect_service(
+    devices: Vec<KDEConnectDevice>,
+) -> (zbus::blocking::Connection, zbus::blocking::Connection) {
+    use zbus::blocking::ConnectionBuilder;
+
+    use crate::device::kde_connect::dbus;
+
+    let (service_stream, client_stream) = UnixStream::pair().unwrap();
+
+    let service = thread::spawn(move || {
+        let guid = zbus::Guid::generate();
+        let mut builder = ConnectionBuilder::unix_stream(service_stream)
+            .server(&guid)
+            .p2p();
+
+        for device in &devices {
+            builder = builder
+                .serve_at(
+                    dbus::device_path(&device.id),
+                    KDEConnectDeviceInterface {
+                        device: device.clone(),
+                    },
+                )
+                .unwrap()
+                .serve_at(
+                    dbus::plugin_path(&device.id, "ping"),
+                    KDEConnectPing::default(),
+                )
+                .unwrap()
+                .serve_at(
+                    dbus::plugin_path(&device.id, "share"),
+                    KDEConnectShare::default(),
+                )
+                .unwrap();
+
+            if let Some((charge, charging)) = device.battery {
+                builder = builder
+                    .serve_at(
+                        dbus::battery_path(&device.id),
+                        KDEConnectBattery { charge, charging },
+                    )
+                    .unwrap();
+            }
+        }
+
+        builder
+            .serve_at("/modules/kdeconnect", KDEConnectDaemon { devices })
+            .unwrap()
+            .build()
+            .unwrap()
+    });
+
+    let client = ConnectionBuilder::unix_stream(client_stream)
+        .p2p()
+        .build()
+        .unwrap();
+
+    (client, service.join().unwrap())
+}
+
+/// Stand-in `org.freedesktop.login1.Manager` interface.
+#[derive(Default)]
+pub struct LogindManager {
+    /// What, who, why and mode of the acquired inhibitor locks.
+    pub inhibitors: Vec<[String; 4]>,
+}
+
+#[zbus::dbus_interface(name = "org.freedesktop.login1.Manager")]
+impl LogindManager {
+    fn inhibit(
+        &mut self,
+        what: String,
+        who: String,
+        why: String,
+        mode: String,
+    ) -> zbus::zvariant::OwnedFd {
+        self.inhibitors.push([what, who, why, mode]);
+
+        let (lock, _) = UnixStream::pair().unwrap();
+
+        // SAFETY: the descriptor isn't owned by anything else
+        unsafe { zbus::zvariant::OwnedFd::from_raw_fd(lock.into_raw_fd()) }
+    }
+}
+
+/// Start a stand-in logind D-Bus service on a peer-to-peer connection.
+///
+/// Return the client connection and the service connection which can emit
+/// the `PrepareForSleep` signal.
+pub fn logind_service(
+) -> (zbus::blocking::Connection, zbus::blocking::Connection) {
+    use zbus::blocking::ConnectionBuilder;
+
+    use crate::sleep;
+
+    let (service_stream, client_stream) = UnixStream::pair().unwrap();
+
+    let service = thread::spawn(move || {
+        let guid = zbus::Guid::generate();
+
+        ConnectionBuilder::unix_stream(service_stream)
+            .server(&guid)
+            .p2p()
+            .serve_at(sleep::MANAGER_PATH, LogindManager::default())
+            .unwrap()
+            .build()
+            .unwrap()
+    });
+
+    let client = ConnectionBuilder::unix_stream(client_stream)
+        .p2p()
+        .build()
+        .unwrap();
+
+    (client, service.join().unwrap())
+}
+
+/// Return the inhibitor locks acquired from the stand-in logind.
+pub fn logind_inhibitors(
+    service: &zbus::blocking::Connection,
+) -> Vec<[String; 4]> {
+    service
+        .object_server()
+        .interface::<_, LogindManager>(crate::sleep::MANAGER_PATH)
+        .unwrap()
+        .get()
+        .inhibitors
+        .clone()
+}
+
+/// Write a stand-in KDE Connect CLI script with the given shell body.
+///
+/// Return path of the executable script.
+pub fn kde_connect_cli(body: &str) -> String {
+    script("kdeconnect-cli", body)
+}
+
+/// Write a stand-in `systemctl` script with the given shell body.
+///
+/// Return path of the executable script.
+pub fn systemctl(body: &str) -> String {
+    script("systemctl", body)
+}
+
+/// Write an executable shell script with the given body.
+///
+/// Return path of the executable script.
+fn script(name: &str, body: &str) -> String {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let path = env::temp_dir().join(format!(
+        "rusty-battery-{name}-{}-{}",
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst),
+    ));
+
+    fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+    path.to_string_lossy().into_owned()
+}
@@ -0,0 +1,159 @@
+//! Helpers shared by the integration tests running the `notify` process
+//! against the mock battery backend.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Child, Command, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Temporary directories of `notify` processes, the processes are killed
+/// and the directories removed once dropped.
+pub struct Daemon {
+    pub dir: PathBuf,
+    pub children: Vec<Child>,
+}
+
+impl Daemon {
+    /// Create directories of the test without starting any process.
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir()
+            .join(format!("rusty-battery-test-{}", process::id()))
+            .join(name);
+        fs::create_dir_all(dir.join("runtime")).unwrap();
+        fs::create_dir_all(dir.join("state")).unwrap();
+        fs::write(
+            dir.join("battery.json"),
+            r#"{"model": "mock-model", "percentage": 85, "state": "Charging"}"#,
+        )
+        .unwrap();
+
+        Self {
+            dir,
+            children: vec![],
+        }
+    }
+
+    /// Start `notify` and wait until it listens on the control socket.
+    pub fn start(name: &str) -> Self {
+        let mut daemon = Self::new(name);
+
+        let child = daemon
+            .notify(&[])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        daemon.children.push(child);
+
+        assert!(
+            wait_until(|| daemon.socket().exists()),
+            "control socket not created"
+        );
+
+        daemon
+    }
+
+    /// Return `notify` command with the alert hook appending summaries
+    /// to a file and the extra arguments.
+    pub fn notify(&self, args: &[&str]) -> Command {
+        let hook = format!(
+            "echo \"$RB_SUMMARY\" >> {}",
+            self.path("hook.log").display()
+        );
+
+        let mut command = self.command();
+        command
+            .args([
+                "notify",
+                "--disable-desktop",
+                "--refresh-secs",
+                "1",
+                "--on-alert",
+                &hook,
+            ])
+            .args(args);
+
+        command
+    }
+
+    /// Return `rusty-battery` command using the mock battery
+    /// and the temporary directories.
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_rusty-battery"));
+        command
+            .env("RUSTY_BATTERY_MOCK", self.path("battery.json"))
+            .env("XDG_RUNTIME_DIR", self.path("runtime"))
+            .env("XDG_STATE_HOME", self.path("state"))
+            .env("DBUS_SESSION_BUS_ADDRESS", "unix:path=/nonexistent")
+            .env("LC_ALL", "en_US.UTF-8");
+
+        command
+    }
+
+    /// Run `ctl` with the arguments.
+    pub fn ctl(&self, args: &[&str]) -> Output {
+        self.command().arg("ctl").args(args).output().unwrap()
+    }
+
+    /// Run `ctl` with the arguments and return its successful output.
+    pub fn ctl_ok(&self, args: &[&str]) -> String {
+        let output = self.ctl(args);
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(
+            output.status.success(),
+            "{args:?}: {stdout}{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        stdout
+    }
+
+    pub fn socket(&self) -> PathBuf {
+        self.path("runtime/rusty-battery/control.sock")
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        for child in &mut self.children {
+            child.kill().ok();
+            child.wait().ok();
+        }
+
+        fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+/// Wait up to 10 seconds until the condition holds.
+///
+/// Return whether it holds.
+pub fn wait_until<F>(mut condition: F) -> bool
+where
+    F: FnMut() -> bool,
+{
+    let deadline = Instant::now() + Duration::from_secs(10);
+
+    while Instant::now() < deadline {
+        if condition() {
+            return true;
+        }
+
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    false
+}
+
+/// Wait until the file at the path contains the text.
+pub fn wait_for(path: &Path, text: &str) -> bool {
+    wait_until(|| {
+        fs::read_to_string(path).is_ok_and(|content| content.contains(text))
+    })
+}
@@ -0,0 +1,123 @@
+//! Integration tests of the `ctl` subcommand against a `notify` process
+//! monitoring the mock battery backend.
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+
+use common::{wait_for, Daemon};
+
+mod common;
+
+#[test]
+fn test_ctl_status() {
+    let daemon = Daemon::start("status");
+
+    let stdout = daemon.ctl_ok(&["status"]);
+
+    assert!(
+        stdout.contains(
+            "Battery: model = \"mock-model\", percentage = 85%, \
+            state = Charging, threshold = 80%"
+        ),
+        "{stdout}"
+    );
+    assert!(stdout.contains("1. hook: circuit = closed"), "{stdout}");
+}
+
+#[test]
+fn test_ctl_socket_permissions() {
+    let daemon = Daemon::start("permissions");
+
+    let socket = fs::metadata(daemon.socket()).unwrap().permissions();
+    let dir = fs::metadata(daemon.socket().parent().unwrap())
+        .unwrap()
+        .permissions();
+
+    assert_eq!(socket.mode() & 0o777, 0o600);
+    assert_eq!(dir.mode() & 0o777, 0o700);
+}
+
+#[test]
+fn test_ctl_set_threshold() {
+    let daemon = Daemon::start("set-threshold");
+
+    let stdout = daemon.ctl_ok(&["set-threshold", "90"]);
+    let status = daemon.ctl_ok(&["status"]);
+
+    assert_eq!(stdout, "Threshold set to 90%\n");
+    assert!(status.contains("threshold = 90%"), "{status}");
+}
+
+#[test]
+fn test_ctl_pause_resume() {
+    let daemon = Daemon::start("pause-resume");
+
+    let paused = daemon.ctl_ok(&["pause"]);
+    let status = daemon.ctl_ok(&["status"]);
+    let resumed = daemon.ctl_ok(&["resume"]);
+
+    assert_eq!(paused, "Alerts paused\n");
+    assert!(status.contains("Alerts are paused\n"), "{status}");
+    assert_eq!(resumed, "Alerts resumed\n");
+    assert!(!daemon.ctl_ok(&["status"]).contains("paused"));
+}
+
+#[test]
+fn test_ctl_snooze() {
+    let daemon = Daemon::start("snooze");
+
+    let stdout = daemon.ctl_ok(&["snooze", "30m"]);
+    let invalid = daemon.ctl(&["snooze", "30x"]);
+
+    assert!(stdout.starts_with("Alerts snoozed until "), "{stdout}");
+    assert!(!invalid.status.success());
+}
+
+#[test]
+fn test_ctl_snooze_too_long() {
+    let daemon = Daemon::start("snooze-too-long");
+
+    let output = daemon.ctl(&["snooze", "5124095576030431h"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("snooze duration is too long"));
+    assert!(!daemon.ctl_ok(&["status"]).contains("paused"));
+}
+
+#[test]
+fn test_ctl_reload() {
+    let daemon = Daemon::start("reload");
+    fs::write(
+        daemon.path("battery.json"),
+        r#"{"model": "mock-model", "percentage": 42, "state": "Discharging"}"#,
+    )
+    .unwrap();
+
+    let stdout = daemon.ctl_ok(&["reload"]);
+
+    assert_eq!(
+        stdout,
+        "Reloaded: percentage = 42%, state = Discharging, threshold = 80%\n"
+    );
+}
+
+#[test]
+fn test_ctl_test_notify() {
+    let daemon = Daemon::start("test-notify");
+
+    let stdout = daemon.ctl_ok(&["test-notify"]);
+
+    assert_eq!(stdout, "hook: delivered\n");
+    assert!(wait_for(&daemon.path("hook.log"), "Test notification\n"));
+}
+
+#[test]
+fn test_ctl_not_running() {
+    let daemon = Daemon::new("not-running");
+
+    let output = daemon.ctl(&["status"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("rusty-battery notify isn't running"));
+}
@@ -0,0 +1,178 @@
+//! Integration tests of the `install` and `uninstall` subcommands
+//! against a temporary home directory.
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::process::{self, Command, Output};
+
+const BIN: &str = env!("CARGO_BIN_EXE_rusty-battery");
+
+/// Temporary home directory with a stand-in `systemctl` logging
+/// its arguments, the directory is removed once dropped.
+struct Home {
+    dir: PathBuf,
+}
+
+impl Home {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir()
+            .join(format!("rusty-battery-test-install-{}", process::id()))
+            .join(name);
+        fs::create_dir_all(dir.join("bin")).unwrap();
+
+        let systemctl = dir.join("bin/systemctl");
+        fs::write(
+            &systemctl,
+            format!(
+                "#!/bin/sh\necho \"$@\" >> {}\n",
+                dir.join("systemctl.log").display()
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&systemctl, fs::Permissions::from_mode(0o755))
+            .unwrap();
+
+        Self { dir }
+    }
+
+    fn run(&self, args: &[&str]) -> Output {
+        let path = format!(
+            "{}:{}",
+            self.path("bin").display(),
+            std::env::var("PATH").unwrap_or_default()
+        );
+
+        Command::new(BIN)
+            .args(args)
+            .env("HOME", &self.dir)
+            .env("PATH", path)
+            .env_remove("XDG_CONFIG_HOME")
+            .output()
+            .unwrap()
+    }
+
+    fn run_ok(&self, args: &[&str]) -> String {
+        let output = self.run(args);
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(
+            output.status.success(),
+            "{args:?}: {stdout}{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        stdout
+    }
+
+    fn systemctl_log(&self) -> String {
+        fs::read_to_string(self.path("systemctl.log")).unwrap_or_default()
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+}
+
+impl Drop for Home {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+#[test]
+fn test_install_systemd() {
+    let home = Home::new("systemd");
+    let unit = home.path(".config/systemd/user/rusty-battery.service");
+
+    let stdout = home.run_ok(&[
+        "install",
+        "--systemd",
+        "--enable",
+        "--",
+        "--threshold",
+        "90",
+    ]);
+
+    assert_eq!(
+        stdout,
+        format!(
+            "Installed {}\nEnabled and started rusty-battery.service\n",
+            unit.display()
+        )
+    );
+    assert!(fs::read_to_string(&unit).unwrap().contains(&format!(
+        "ExecStart={BIN} --log-target journald notify --threshold 90\n"
+    )));
+    assert_eq!(
+        home.systemctl_log(),
+        "--user daemon-reload\n\
+        --user enable rusty-battery.service\n\
+        --user restart rusty-battery.service\n"
+    );
+}
+
+#[test]
+fn test_install_autostart() {
+    let home = Home::new("autostart");
+    let entry = home.path(".config/autostart/rusty-battery.desktop");
+
+    home.run_ok(&[
+        "install",
+        "--autostart",
+        "--",
+        "--disable-desktop",
+        "--kde-connect",
+    ]);
+
+    assert!(fs::read_to_string(entry).unwrap().contains(&format!(
+        "Exec={BIN} notify --disable-desktop --kde-connect\n"
+    )));
+    assert_eq!(home.systemctl_log(), "");
+}
+
+#[test]
+fn test_install_invalid_options() {
+    let home = Home::new("invalid-options");
+
+    let output =
+        home.run(&["install", "--systemd", "--", "--threshold", "101"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("invalid notify options"));
+    assert!(!home.path(".config").exists());
+}
+
+#[test]
+fn test_install_enable_autostart() {
+    let home = Home::new("enable-autostart");
+
+    let output = home.run(&["install", "--autostart", "--enable"]);
+
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_uninstall() {
+    let home = Home::new("uninstall");
+    home.run_ok(&["install", "--systemd"]);
+    home.run_ok(&["install", "--autostart"]);
+
+    let stdout = home.run_ok(&["uninstall"]);
+    let again = home.run_ok(&["uninstall"]);
+
+    assert_eq!(
+        stdout,
+        format!(
+            "Removed {}\nRemoved {}\n",
+            home.path(".config/systemd/user/rusty-battery.service")
+                .display(),
+            home.path(".config/autostart/rusty-battery.desktop")
+                .display()
+        )
+    );
+    assert!(home
+        .systemctl_log()
+        .contains("--user disable --now rusty-battery.service\n"));
+    assert_eq!(again, "Nothing to uninstall\n");
+}
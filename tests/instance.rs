@@ -0,0 +1,75 @@
+//! Integration tests of the single running `notify` process.
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::process::Stdio;
+
+use common::{wait_until, Daemon};
+
+mod common;
+
+fn lock_pid(daemon: &Daemon) -> String {
+    fs::read_to_string(daemon.path("runtime/rusty-battery/notify.lock"))
+        .unwrap()
+}
+
+#[test]
+fn test_notify_already_running() {
+    let daemon = Daemon::start("already-running");
+
+    let output = daemon.notify(&[]).output().unwrap();
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains(&format!(
+            "rusty-battery notify is already running: pid = {}",
+            daemon.children[0].id()
+        )),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_notify_replace() {
+    let mut daemon = Daemon::start("replace");
+    let pid = daemon.children[0].id().to_string();
+    assert_eq!(lock_pid(&daemon), pid);
+
+    let child = daemon
+        .notify(&["--replace"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let replacing = child.id().to_string();
+    daemon.children.push(child);
+
+    assert!(wait_until(|| daemon.children[0]
+        .try_wait()
+        .unwrap()
+        .is_some()));
+    assert!(wait_until(|| lock_pid(&daemon) == replacing));
+    assert!(wait_until(|| daemon.ctl(&["status"]).status.success()));
+}
+
+#[test]
+fn test_notify_without_runtime_dir() {
+    let mut daemon = Daemon::new("without-runtime-dir");
+    let tmp = daemon.path("tmp");
+    fs::create_dir_all(&tmp).unwrap();
+    let uid = fs::metadata(&tmp).unwrap().uid();
+
+    let child = daemon
+        .notify(&[])
+        .env_remove("XDG_RUNTIME_DIR")
+        .env("TMPDIR", &tmp)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    daemon.children.push(child);
+
+    assert!(wait_until(|| tmp
+        .join(format!("rusty-battery-{uid}/control.sock"))
+        .exists()));
+}
@@ -0,0 +1,64 @@
+//! Integration tests of the KDE Connect CLI fallback
+//! against the stand-in `kdeconnect-cli` of the fixture corpus.
+use std::env;
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// KDE Connect versions of the fixture corpus.
+const VERSIONS: [&str; 4] = ["1.3", "1.4", "21.12", "23.08"];
+
+/// Run `rusty-battery` with the stand-in `kdeconnect-cli` first in `PATH`
+/// and without a reachable session bus.
+fn run(version: &str, args: &[&str]) -> Output {
+    let bin = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/kdeconnect-cli/bin");
+    let path = env::join_paths(
+        [bin]
+            .into_iter()
+            .chain(env::split_paths(&env::var_os("PATH").unwrap_or_default())),
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_rusty-battery"))
+        .args(args)
+        .env("PATH", path)
+        .env("DBUS_SESSION_BUS_ADDRESS", "unix:path=/nonexistent")
+        .env("KDECONNECT_CLI_VERSION", version)
+        .env("LC_ALL", "en_US.UTF-8")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_kde_connect_devices() {
+    for version in VERSIONS {
+        let output = run(version, &["kde-connect-devices"]);
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(output.status.success(), "{version}: {stdout}");
+        assert!(
+            stdout.starts_with("\nFound ")
+                && stdout.contains(" KDE Connect devices\n")
+                && stdout.contains("Lukas's Pixel 7 (")
+                && stdout.contains("): unknown type, reachable, paired\n"),
+            "{version}: {stdout}"
+        );
+        assert!(
+            stdout.contains("Work: Galaxy Tab S8 ("),
+            "{version}: {stdout}"
+        );
+    }
+}
+
+#[test]
+fn test_kde_connect_devices_localized() {
+    let output = run("23.08", &["kde-connect-devices", "--locale", "cs"]);
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}");
+    assert!(
+        stdout.starts_with("\nNalezena 3 zařízení KDE Connect\n")
+            && stdout.contains("): neznámý typ, dostupné, nespárované\n"),
+        "{stdout}"
+    );
+}